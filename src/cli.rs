@@ -1,4 +1,6 @@
-use clap::{Parser, Subcommand};
+use crate::hook::kind::{ChainOrder, HookExec, HookKind};
+use clap::{Parser, Subcommand, ValueEnum};
+use clap_complete::Shell;
 use std::path::PathBuf;
 
 #[derive(Parser)]
@@ -12,35 +14,121 @@ pub struct Cli {
 pub enum Commands {
     /// Equivalent to init + install
     II {
-        /// hook name, currently only supports commit-msg and pre-commit
-        hook: String,
+        /// hook to set up
+        #[arg(value_enum)]
+        hook: HookKind,
 
         #[arg(short = 'f', long = "force")]
         force: bool,
+
+        /// Rule preset: conventional (default), angular, gitmoji, kernel or minimal
+        #[arg(long)]
+        preset: Option<String>,
+
+        /// Keep an existing hook as `<hook>.local` and run it before or after gitru
+        #[arg(long, value_enum)]
+        chain: Option<ChainOrder>,
+
+        /// How the hook finds gitru: absolute (default), path, repo[:<path>] or cargo
+        #[arg(long, value_name = "STRATEGY")]
+        exec: Option<HookExec>,
     },
 
     /// Initialize configuration file in the repository root
     Init {
-        /// hook name, currently only supports commit-msg
-        hook: String,
+        /// hook to write the configuration of
+        #[arg(value_enum)]
+        hook: HookKind,
 
         #[arg(short = 'f', long = "force")]
         force: bool,
+
+        /// Rule preset: conventional (default), angular, gitmoji, kernel or minimal
+        #[arg(long)]
+        preset: Option<String>,
     },
 
     /// Install git hook script into the .git/hooks directory
     Install {
-        /// hook name, currently only supports commit-msg
-        hook: String,
+        /// hook to install
+        #[arg(value_enum, required_unless_present = "all")]
+        hook: Option<HookKind>,
+
+        /// Install the hooks of the hook manifest (.gitru/hooks.toml), same as `sync`
+        #[arg(long, conflicts_with_all = ["hook", "chain", "exec", "recursive"])]
+        all: bool,
 
         #[arg(short = 'f', long = "force")]
         force: bool,
+
+        /// Keep an existing hook as `<hook>.local` and run it before or after gitru
+        #[arg(long, value_enum)]
+        chain: Option<ChainOrder>,
+
+        /// How the hook finds gitru: absolute (default), path, repo[:<path>] or cargo
+        #[arg(long, value_name = "STRATEGY")]
+        exec: Option<HookExec>,
+
+        /// Also install into every worktree and submodule, recursively
+        #[arg(short = 'r', long)]
+        recursive: bool,
     },
 
     /// Uninstall git hook script from the .git/hooks directory
     Uninstall {
-        /// hook name, currently only supports commit-msg
-        hook: String,
+        /// hook to remove
+        #[arg(value_enum)]
+        hook: HookKind,
+
+        /// Also remove a hook that gitru did not write or that was modified
+        #[arg(short = 'f', long = "force")]
+        force: bool,
+
+        /// Also uninstall from every worktree and submodule, recursively
+        #[arg(short = 'r', long)]
+        recursive: bool,
+    },
+
+    /// Install, update and remove hooks to match the hook manifest (.gitru/hooks.toml)
+    Sync {
+        /// Also replace or remove hooks that were modified or not written by gitru
+        #[arg(short = 'f', long = "force")]
+        force: bool,
+    },
+
+    /// Check that the installed hooks match the hook manifest, exiting with 1 when they do not
+    CheckInstalled,
+
+    /// Show how each hook is installed in the current repository
+    Status,
+
+    /// Validate the commit messages of a revision range, e.g. `origin/main..HEAD`
+    Lint {
+        /// revision range, anything accepted by `git log`
+        range: String,
+
+        /// Path to rule file, defaults to the rule file in the repository root
+        #[arg(long)]
+        rule: Option<PathBuf>,
+
+        /// Treat checks of severity `warning` as errors
+        #[arg(long)]
+        strict: bool,
+    },
+
+    /// Inspect and validate the commit-msg rule file
+    Config {
+        #[command(subcommand)]
+        cmd: ConfigCmd,
+    },
+
+    /// Diagnose the hook installation and the rule file of the current repository
+    Doctor,
+
+    /// Print the shell completion script, e.g. `gitru completions bash > /etc/bash_completion.d/gitru`
+    Completions {
+        #[arg(value_enum)]
+        shell: Shell,
     },
 
     /// Run the specified git hook script
@@ -51,6 +139,50 @@ pub enum Commands {
     },
 }
 
+#[derive(Debug, Clone, Subcommand)]
+pub enum ConfigCmd {
+    /// Check the rule file for unknown keys, wrong types, conflicting values and deprecated keys
+    Check {
+        /// Path to rule file, defaults to the rule file in the repository root
+        #[arg(long)]
+        rule: Option<PathBuf>,
+    },
+
+    /// Print the JSON Schema of the rule file, for editor completion and validation
+    Schema,
+
+    /// Print the effective configuration: the rule after defaults and `extends`, the source
+    /// of each value, the rule file in use and the overrides in effect
+    Show {
+        /// Path to rule file, defaults to the one the commit-msg hook would use
+        #[arg(long)]
+        rule: Option<PathBuf>,
+
+        /// Output format
+        #[arg(long, value_enum, default_value_t = ShowFormat::Toml)]
+        format: ShowFormat,
+    },
+
+    /// Rewrite the rule file to the current format, keeping comments and layout
+    Migrate {
+        /// Path to rule file, defaults to the rule file in the repository root
+        #[arg(long)]
+        rule: Option<PathBuf>,
+
+        /// Print the migrated rule file instead of writing it
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+/// Output format of `gitru config show`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ShowFormat {
+    /// TOML, each value annotated with its source
+    Toml,
+    Json,
+}
+
 #[derive(Debug, Clone, Subcommand)]
 pub enum RunCmd {
     /// Validate commit message
@@ -60,16 +192,33 @@ pub enum RunCmd {
         #[arg(long)]
         msg: PathBuf,
 
-        /// Path to rule file
+        /// Path to rule file, discovered in the repository by default
         #[arg(long)]
-        rule: PathBuf,
+        rule: Option<PathBuf>,
+
+        /// Treat checks of severity `warning` as errors
+        #[arg(long)]
+        strict: bool,
     },
 
-    /// Run pre-commit hook
-    // #[command(name = "pre-commit")]
-    PreCommit {
-        /// Path to rule file
+    /// Validate the messages of the commits being pushed, then run the commands of the manifest
+    PrePush {
+        /// Name of the remote, as passed by git
+        remote: Option<String>,
+
+        /// URL of the remote, as passed by git
+        url: Option<String>,
+
+        /// Path to rule file, discovered in the repository by default
         #[arg(long)]
-        rule: PathBuf,
+        rule: Option<PathBuf>,
+
+        /// Treat checks of severity `warning` as errors
+        #[arg(long)]
+        strict: bool,
     },
+
+    /// Run the commands of any other hook listed in the hook manifest, with the arguments of git
+    #[command(external_subcommand)]
+    Hook(Vec<String>),
 }
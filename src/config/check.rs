@@ -0,0 +1,680 @@
+//! Rule file validation: unknown keys, values of the wrong type and conflicting values,
+//! each reported with the file and line it comes from. Deprecated keys and a newer minor
+//! `global.version` are reported as warnings.
+//!
+//! Runs whenever a rule file is loaded, and on demand with `gitru config check`.
+
+use crate::config::commit_msg_rule::ParsedCommitMsgRule;
+use crate::config::discovery::RuleFormat;
+use crate::config::extends::{LIST_OPERATIONS, Layer, RuleSources, load_layers, merge_layers};
+use crate::config::migrate::{RULE_VERSION, RuleVersion};
+use crate::config::schema::{Field, FieldKind, RULE_FIELDS};
+use crate::error::config_error::{ConfigDiagnostic, ConfigError};
+use crate::util::similarity::{SimilarityAlgorithm, SpellcheckOptions, suggest};
+use globset::Glob;
+use std::collections::HashMap;
+use std::path::Path;
+use toml::Value;
+use toml::de::{DeTable, DeValue};
+
+/// Similarity threshold for did-you-mean suggestions of keys and values (Jaro-Winkler)
+const KEY_SUGGESTION_THRESHOLD: f64 = 0.8;
+
+/// Outcome of checking a rule file
+#[derive(Debug)]
+pub struct CheckedRule {
+    /// The effective rule, `None` when it could not be deserialized
+    pub rule: Option<ParsedCommitMsgRule>,
+    pub diagnostics: Vec<ConfigDiagnostic>,
+    /// Problems that do not prevent using the rule, e.g. deprecated keys
+    pub warnings: Vec<ConfigDiagnostic>,
+}
+
+/// Resolve the `extends` chain of a rule file and check every layer and the merged result.
+///
+/// Errors that prevent checking at all (unreadable `extends` target, TOML syntax error,
+/// cycle) are returned as `Err`; problems in the rule itself are returned as diagnostics.
+pub fn check_commit_msg_rule(
+    content: &str,
+    origin: Option<&Path>,
+) -> Result<CheckedRule, ConfigError> {
+    check_layers(load_layers(content, origin)?)
+}
+
+/// Check every layer and their merged result. The last layer is the rule file reported
+/// for problems of the merged rule.
+pub fn check_layers(layers: Vec<Layer>) -> Result<CheckedRule, ConfigError> {
+    let root = layers.last().map(|l| l.name.clone()).unwrap_or_default();
+
+    // 1. unknown keys and value types, per layer, where spans are still available
+    let mut locations = KeyLocations::default();
+    let mut diagnostics = Vec::new();
+    let mut warnings = Vec::new();
+    for layer in &layers {
+        check_layer(layer, &mut locations, &mut diagnostics, &mut warnings)?;
+    }
+    if !diagnostics.is_empty() {
+        return Ok(CheckedRule {
+            rule: None,
+            diagnostics,
+            warnings,
+        });
+    }
+
+    // 2. the merged rule, missing keys take their default values
+    let (table, sources) = merge_layers(layers)?;
+    let locator = Locator {
+        sources: &sources,
+        locations: &locations,
+        root: &root,
+    };
+
+    let mut rule: ParsedCommitMsgRule =
+        Value::Table(table)
+            .try_into()
+            .map_err(|e: toml::de::Error| ConfigError::InvalidRuleFile {
+                source_name: root.clone(),
+                reason: e.to_string(),
+            })?;
+
+    // 3. conflicting values
+    check_semantics(&rule, &locator, &mut diagnostics);
+    rule.sources = sources;
+    rule.warnings = warnings.clone();
+
+    Ok(CheckedRule {
+        rule: Some(rule),
+        diagnostics,
+        warnings,
+    })
+}
+
+/// Line of each key, per layer: (layer name, dotted key) → 1-based line
+#[derive(Debug, Default)]
+struct KeyLocations(HashMap<(String, String), usize>);
+
+/// Finds the file and line a key of the merged rule comes from
+struct Locator<'a> {
+    sources: &'a RuleSources,
+    locations: &'a KeyLocations,
+    root: &'a str,
+}
+
+impl Locator<'_> {
+    fn diagnostic(&self, key: &str, message: String, help: Option<String>) -> ConfigDiagnostic {
+        // a value, or the first value of a section
+        let nested = format!("{}.", key);
+        let file = self
+            .sources
+            .source_of(key)
+            .and_then(|s| s.last())
+            .or_else(|| {
+                self.sources
+                    .iter()
+                    .find(|(k, _)| k.starts_with(&nested))
+                    .and_then(|(_, s)| s.last())
+            })
+            .map(String::as_str)
+            .unwrap_or(self.root);
+
+        ConfigDiagnostic {
+            file: file.to_string(),
+            line: self
+                .locations
+                .0
+                .get(&(file.to_string(), key.to_string()))
+                .copied(),
+            key: key.to_string(),
+            message,
+            help,
+        }
+    }
+}
+
+fn check_layer(
+    layer: &Layer,
+    locations: &mut KeyLocations,
+    diagnostics: &mut Vec<ConfigDiagnostic>,
+    warnings: &mut Vec<ConfigDiagnostic>,
+) -> Result<(), ConfigError> {
+    let invalid = |reason: String| ConfigError::InvalidRuleFile {
+        source_name: layer.name.clone(),
+        reason,
+    };
+
+    // JSON and YAML have no spans: check their TOML equivalent, without line numbers
+    let converted;
+    let content = if layer.format.is_toml() {
+        layer.content.as_str()
+    } else {
+        converted = toml::to_string(&layer.table).map_err(|e| invalid(e.to_string()))?;
+        converted.as_str()
+    };
+    let document = DeTable::parse(content).map_err(|e| invalid(e.to_string()))?;
+
+    let mut table = document.get_ref();
+    if let RuleFormat::TomlSection(section) = layer.format {
+        for key in section {
+            match table.get(*key).map(|v| v.get_ref()) {
+                Some(DeValue::Table(t)) => table = t,
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    let mut walker = LayerWalker {
+        layer,
+        content: layer.format.is_toml().then_some(content),
+        locations,
+        diagnostics,
+        warnings,
+    };
+    walker.check_table(table, &RULE_FIELDS, "");
+    Ok(())
+}
+
+/// Walks the raw TOML of one layer against the schema
+struct LayerWalker<'a> {
+    layer: &'a Layer,
+    /// Content the spans refer to, `None` when they do not match the file as written
+    content: Option<&'a str>,
+    locations: &'a mut KeyLocations,
+    diagnostics: &'a mut Vec<ConfigDiagnostic>,
+    warnings: &'a mut Vec<ConfigDiagnostic>,
+}
+
+impl LayerWalker<'_> {
+    fn line_of(&self, offset: usize) -> Option<usize> {
+        let content = self.content?;
+        let offset = offset.min(content.len());
+        Some(content[..offset].matches('\n').count() + 1)
+    }
+
+    fn diagnostic(
+        &self,
+        key: &str,
+        offset: usize,
+        message: String,
+        help: Option<String>,
+    ) -> ConfigDiagnostic {
+        ConfigDiagnostic {
+            file: self.layer.name.clone(),
+            line: self.line_of(offset),
+            key: key.to_string(),
+            message,
+            help,
+        }
+    }
+
+    fn report(&mut self, key: &str, offset: usize, message: String, help: Option<String>) {
+        let diagnostic = self.diagnostic(key, offset, message, help);
+        self.diagnostics.push(diagnostic);
+    }
+
+    fn warn(&mut self, key: &str, offset: usize, message: String, help: Option<String>) {
+        let diagnostic = self.diagnostic(key, offset, message, help);
+        self.warnings.push(diagnostic);
+    }
+
+    /// A newer major version is an error, a newer minor or patch version a warning:
+    /// keys added since then are reported as unknown
+    fn check_version(&mut self, version: &str, path: &str, offset: usize) {
+        let Some(declared) = RuleVersion::parse(version) else {
+            self.report(
+                path,
+                offset,
+                format!("invalid version `{}`", version),
+                Some(format!(
+                    "expected `major.minor.patch`, e.g. `{}`",
+                    RULE_VERSION
+                )),
+            );
+            return;
+        };
+
+        let current = RuleVersion::current();
+        if declared.major > current.major {
+            self.report(
+                path,
+                offset,
+                format!(
+                    "rule format {} is not supported, this gitru supports up to {}",
+                    declared, current
+                ),
+                Some("upgrade gitru".to_string()),
+            );
+        } else if declared > current {
+            self.warn(
+                path,
+                offset,
+                format!(
+                    "rule format {} is newer than {} supported by this gitru",
+                    declared, current
+                ),
+                Some("upgrade gitru".to_string()),
+            );
+        }
+    }
+
+    fn check_table(&mut self, table: &DeTable<'_>, fields: &[Field], prefix: &str) {
+        for (key, value) in table.iter() {
+            let name = key.get_ref().as_ref();
+            let path = join_key(prefix, name);
+            let offset = key.span().start;
+            if let Some(line) = self.line_of(offset) {
+                self.locations
+                    .0
+                    .insert((self.layer.name.clone(), path.clone()), line);
+            }
+
+            match fields.iter().find(|f| f.name == name) {
+                Some(field) => {
+                    if let Some(replacement) = &field.replaced_by {
+                        self.warn(
+                            &path,
+                            offset,
+                            format!("deprecated, use `{}` instead", replacement),
+                            Some("run `gitru config migrate` to update the rule file".to_string()),
+                        );
+                    }
+                    if path == "global.version"
+                        && let DeValue::String(version) = value.get_ref()
+                    {
+                        self.check_version(version, &path, offset);
+                    }
+                    self.check_value(value.get_ref(), &field.kind, &path, offset)
+                }
+                None => {
+                    let names: Vec<&str> = fields.iter().map(|f| f.name.as_str()).collect();
+                    self.report(
+                        &path,
+                        offset,
+                        "unknown key".to_string(),
+                        Some(did_you_mean(name, &names)),
+                    );
+                }
+            }
+        }
+    }
+
+    fn check_value(&mut self, value: &DeValue<'_>, kind: &FieldKind, path: &str, offset: usize) {
+        match (kind, value) {
+            (FieldKind::Bool, DeValue::Boolean(_))
+            | (FieldKind::Float, DeValue::Float(_))
+            | (FieldKind::String, DeValue::String(_)) => {}
+
+            (FieldKind::Integer, DeValue::Integer(i)) => {
+                if i.as_str().starts_with('-') {
+                    self.report(path, offset, format!("expected {}", kind.describe()), None);
+                }
+            }
+
+            (FieldKind::StringList, DeValue::Array(items)) => {
+                if !items.iter().all(|v| v.get_ref().is_str()) {
+                    self.report(path, offset, format!("expected {}", kind.describe()), None);
+                }
+            }
+
+            // list operation, e.g. `{ append = ["perf"] }`
+            (FieldKind::StringList, DeValue::Table(ops)) => {
+                for (op, items) in ops.iter() {
+                    let op_name = op.get_ref().as_ref();
+                    let op_path = join_key(path, op_name);
+                    if !LIST_OPERATIONS.contains(&op_name) {
+                        self.report(
+                            &op_path,
+                            op.span().start,
+                            "unknown list operation".to_string(),
+                            Some(did_you_mean(op_name, &LIST_OPERATIONS)),
+                        );
+                    } else {
+                        self.check_value(items.get_ref(), kind, &op_path, op.span().start);
+                    }
+                }
+            }
+
+            (FieldKind::Enum(values), DeValue::String(s)) => {
+                if !values.iter().any(|v| v == s.as_ref()) {
+                    let values: Vec<&str> = values.iter().map(String::as_str).collect();
+                    self.report(
+                        path,
+                        offset,
+                        format!("unknown value `{}`", s),
+                        Some(did_you_mean(s, &values)),
+                    );
+                }
+            }
+
+            (FieldKind::Table(fields), DeValue::Table(table)) => {
+                self.check_table(table, fields, path);
+            }
+
+            _ => self.report(
+                path,
+                offset,
+                format!("expected {}, found {}", kind.describe(), value.type_str()),
+                None,
+            ),
+        }
+    }
+}
+
+/// Report values that are individually valid but conflict with each other
+fn check_semantics(
+    rule: &ParsedCommitMsgRule,
+    locator: &Locator<'_>,
+    diagnostics: &mut Vec<ConfigDiagnostic>,
+) {
+    let mut report = |key: &str, message: String, help: Option<&str>| {
+        diagnostics.push(locator.diagnostic(key, message, help.map(str::to_string)));
+    };
+
+    let mut check_range = |prefix: &str, min_key: &str, min: usize, max_key: &str, max: usize| {
+        if min > max {
+            report(
+                &join_key(prefix, min_key),
+                format!(
+                    "{} ({}) is greater than {} ({})",
+                    min_key, min, max_key, max
+                ),
+                None,
+            );
+        }
+    };
+
+    let subject = &rule.header.subject;
+    check_range(
+        "header.subject",
+        "min_length",
+        subject.min_length,
+        "max_length",
+        subject.max_length,
+    );
+    if let Some(body) = &rule.body {
+        check_range(
+            "body",
+            "min_line_length",
+            body.min_line_length,
+            "max_line_length",
+            body.max_line_length,
+        );
+    }
+    if let Some(footer) = &rule.footer {
+        check_range(
+            "footer",
+            "min_line_length",
+            footer.min_line_length,
+            "max_line_length",
+            footer.max_line_length,
+        );
+    }
+
+    if rule
+        .header
+        .r#type
+        .allowed_types
+        .as_ref()
+        .is_some_and(|t| t.is_empty())
+    {
+        report(
+            "header.type.allowed_types",
+            "must not be empty".to_string(),
+            Some("remove `allowed_types` to accept any type"),
+        );
+    }
+
+    if let Some(scope) = &rule.header.scope {
+        match &scope.allowed_scopes {
+            Some(scopes) if scopes.is_empty() => report(
+                "header.scope.allowed_scopes",
+                "must not be empty".to_string(),
+                Some("remove `allowed_scopes` and set `required = false` to accept any scope"),
+            ),
+            None if scope.required == Some(true) => report(
+                "header.scope.required",
+                "a scope is required but `allowed_scopes` is not set".to_string(),
+                Some("list the allowed scopes in `allowed_scopes`"),
+            ),
+            _ => {}
+        }
+    }
+
+    if let Some(footer) = &rule.footer {
+        let spellcheck_enabled = footer
+            .start_key_words_spellcheck
+            .as_ref()
+            .is_some_and(|s| s.enable);
+        if spellcheck_enabled && footer.start_key_words.is_empty() {
+            report(
+                "footer.start_key_words",
+                "is empty while `start_key_words_spellcheck.enable = true`".to_string(),
+                Some("add footer keywords or disable the spellcheck"),
+            );
+        }
+
+        for keyword in footer.required_key_words.iter().flatten() {
+            if !footer.start_key_words.contains(keyword) {
+                report(
+                    "footer.required_key_words",
+                    format!(
+                        "`{}` is not one of `start_key_words`, it can never be found in the footer",
+                        keyword
+                    ),
+                    Some("add it to `start_key_words`"),
+                );
+            }
+        }
+
+        if let Some(spellcheck) = &footer.start_key_words_spellcheck {
+            check_threshold(
+                "footer.start_key_words_spellcheck.threshold",
+                Some(spellcheck.threshold),
+                &mut report,
+            );
+        }
+    }
+
+    if let Some(spellcheck) = &rule.spellcheck {
+        for (key, value) in [
+            ("spellcheck.type_threshold", spellcheck.type_threshold),
+            ("spellcheck.scope_threshold", spellcheck.scope_threshold),
+            ("spellcheck.footer_threshold", spellcheck.footer_threshold),
+        ] {
+            check_threshold(key, value, &mut report);
+        }
+    }
+
+    let skip_validation = rule
+        .global
+        .as_ref()
+        .and_then(|g| g.skip_validation.as_ref());
+    if let Some(skip_validation) = skip_validation {
+        for (key, patterns) in [
+            (
+                "global.skip_validation.allowed_branches",
+                &skip_validation.allowed_branches,
+            ),
+            (
+                "global.skip_validation.allowed_authors",
+                &skip_validation.allowed_authors,
+            ),
+        ] {
+            for pattern in patterns.iter().flatten() {
+                if let Err(e) = Glob::new(pattern) {
+                    report(
+                        key,
+                        format!("invalid glob pattern `{}`: {}", pattern, e),
+                        None,
+                    );
+                }
+            }
+        }
+    }
+}
+
+fn check_threshold(
+    key: &str,
+    value: Option<f64>,
+    report: &mut impl FnMut(&str, String, Option<&str>),
+) {
+    if let Some(value) = value
+        && !(0.0..=1.0).contains(&value)
+    {
+        report(
+            key,
+            format!("must be between 0.0 and 1.0, found {}", value),
+            None,
+        );
+    }
+}
+
+fn join_key(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", prefix, key)
+    }
+}
+
+/// "did you mean `x`?" when a candidate is close enough, the full list of candidates otherwise
+fn did_you_mean(word: &str, candidates: &[&str]) -> String {
+    let owned: Vec<String> = candidates.iter().map(|c| c.to_string()).collect();
+    // Jaro-Winkler favors a common prefix, which suits abbreviations like `warn` → `warning`
+    let options = SpellcheckOptions {
+        algorithm: SimilarityAlgorithm::JaroWinkler,
+        ..SpellcheckOptions::with_threshold(KEY_SUGGESTION_THRESHOLD)
+    };
+
+    match suggest(word, &owned, &options).first() {
+        Some(best) => format!("did you mean `{}`?", best.candidate),
+        None => format!(
+            "expected one of {}",
+            owned
+                .iter()
+                .map(|c| format!("`{}`", c))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constant::COMMIT_MSG_RULE_TEMPLATE;
+
+    fn check(content: &str) -> Vec<ConfigDiagnostic> {
+        check_commit_msg_rule(content, None).unwrap().diagnostics
+    }
+
+    #[test]
+    fn test_template_is_valid() {
+        let checked = check_commit_msg_rule(COMMIT_MSG_RULE_TEMPLATE, None).unwrap();
+        assert!(checked.diagnostics.is_empty());
+        assert!(checked.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_unknown_key_with_suggestion() {
+        let content = COMMIT_MSG_RULE_TEMPLATE.replace("max_length = 72", "max_lenght = 72");
+        let diagnostics = check(&content);
+
+        assert_eq!(diagnostics.len(), 1);
+        let d = &diagnostics[0];
+        assert_eq!(d.key, "header.subject.max_lenght");
+        assert_eq!(d.help.as_deref(), Some("did you mean `max_length`?"));
+        let line = content
+            .lines()
+            .position(|l| l.starts_with("max_lenght"))
+            .unwrap();
+        assert_eq!(d.line, Some(line + 1));
+    }
+
+    #[test]
+    fn test_wrong_type_and_unknown_enum_value() {
+        let content = COMMIT_MSG_RULE_TEMPLATE
+            .replace(
+                "forbid_trailing_period = true",
+                "forbid_trailing_period = 1",
+            )
+            .replace("subject_length = \"error\"", "subject_length = \"warn\"");
+        let diagnostics = check(&content);
+
+        assert_eq!(diagnostics.len(), 2);
+        assert!(diagnostics[0].message.starts_with("expected a boolean"));
+        assert_eq!(
+            diagnostics[1].help.as_deref(),
+            Some("did you mean `warning`?")
+        );
+    }
+
+    #[test]
+    fn test_conflict_with_default_value() {
+        let diagnostics = check("[header.subject]\nmin_length = 100\n");
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].key, "header.subject.min_length");
+        assert_eq!(
+            diagnostics[0].message,
+            "min_length (100) is greater than max_length (72)"
+        );
+        assert_eq!(diagnostics[0].line, Some(2));
+    }
+
+    #[test]
+    fn test_semantic_conflicts() {
+        let content = COMMIT_MSG_RULE_TEMPLATE
+            .replace("min_length = 2 ", "min_length = 80 ")
+            .replace("footer_threshold = 0.7", "footer_threshold = 7.0");
+        let diagnostics = check(&content);
+
+        let keys: Vec<&str> = diagnostics.iter().map(|d| d.key.as_str()).collect();
+        assert_eq!(
+            keys,
+            ["header.subject.min_length", "spellcheck.footer_threshold"]
+        );
+        assert!(diagnostics.iter().all(|d| d.line.is_some()));
+    }
+
+    #[test]
+    fn test_deprecated_key_warning() {
+        let checked = check_commit_msg_rule(
+            "[footer]\n[footer.start_key_words_spellcheck]\nthreshold = 0.6\n",
+            None,
+        )
+        .unwrap();
+
+        assert!(checked.diagnostics.is_empty());
+        assert_eq!(checked.warnings.len(), 1);
+        assert_eq!(
+            checked.warnings[0].key,
+            "footer.start_key_words_spellcheck.threshold"
+        );
+        assert_eq!(
+            checked.warnings[0].message,
+            "deprecated, use `spellcheck.footer_threshold` instead"
+        );
+        assert_eq!(checked.warnings[0].line, Some(3));
+        assert_eq!(checked.rule.unwrap().warnings, checked.warnings);
+    }
+
+    #[test]
+    fn test_version() {
+        let version = |v: &str| {
+            check_commit_msg_rule(&format!("[global]\nversion = \"{}\"\n", v), None).unwrap()
+        };
+
+        let checked = version("1.0.0");
+        assert!(checked.diagnostics.is_empty() && checked.warnings.is_empty());
+
+        let checked = version("1.99.0");
+        assert!(checked.diagnostics.is_empty());
+        assert_eq!(checked.warnings[0].key, "global.version");
+
+        assert_eq!(version("2.0.0").diagnostics[0].key, "global.version");
+        assert_eq!(
+            version("latest").diagnostics[0].message,
+            "invalid version `latest`"
+        );
+    }
+}
@@ -1,109 +1,466 @@
-use crate::constant::COMMIT_MSG_RULE_FILE_NAME;
+use crate::config::check::check_layers;
+use crate::config::discovery::repo_rule_file;
+use crate::config::extends::{Layer, RuleSources, load_layers};
+use crate::config::migrate::RULE_VERSION;
+use crate::config::user::{UserConfig, user_rule_layers};
 use crate::error::commit_msg_error::{CommitMsgError, SystemError};
+use crate::error::config_error::{ConfigDiagnostic, ConfigError};
 use crate::util::git_path::detect_current_repo;
-use serde::Deserialize;
+use crate::util::similarity::{SimilarityAlgorithm, SpellcheckOptions};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
 
-pub fn parse_commit_msg_rule(rule: &str) -> Result<ParsedCommitMsgRule, String> {
-    let parsed_rule: ParsedCommitMsgRule =
-        toml::from_str(rule).map_err(|e| format!("failed to parse commit msg rule: {}", e))?;
-
-    Ok(parsed_rule)
+/// Parse a rule file content. Relative `extends` entries are resolved against the current directory.
+pub fn parse_commit_msg_rule(rule: &str) -> Result<ParsedCommitMsgRule, ConfigError> {
+    load_layers(rule, None).and_then(build_commit_msg_rule)
 }
 
+/// Load the rule file of the current repository, see [`crate::config::discovery`], on top of
+/// the rules of the user config, see [`crate::config::user`].
 pub fn get_default_path_parsed_commit_msg_rule() -> Result<ParsedCommitMsgRule, CommitMsgError> {
     let git_kind = detect_current_repo()?;
-    let default_path = git_kind.config_path(COMMIT_MSG_RULE_FILE_NAME);
+    let user = UserConfig::load()?;
+
+    load_commit_msg_rule_with_user(&repo_rule_file(&git_kind), user.as_ref())
+}
 
-    let rule = std::fs::read_to_string(&default_path).map_err(|e| {
-        CommitMsgError::System(SystemError::IoPath {
-            path: default_path.clone(),
-            source: e,
-        })
-    })?;
+/// Read and parse the rule file at `path`, in the format given by its name.
+pub fn load_commit_msg_rule(path: &Path) -> Result<ParsedCommitMsgRule, CommitMsgError> {
+    load_commit_msg_rule_with_user(path, None)
+}
+
+/// Like [`load_commit_msg_rule`], applying the rule file over the `[rules]` of the user config.
+/// Without a file at `path`, the user rules are used alone.
+pub fn load_commit_msg_rule_with_user(
+    path: &Path,
+    user: Option<&UserConfig>,
+) -> Result<ParsedCommitMsgRule, CommitMsgError> {
+    let mut layers = user_rule_layers(user)?;
+
+    if layers.is_empty() || path.exists() {
+        let rule = std::fs::read_to_string(path).map_err(|e| {
+            CommitMsgError::System(SystemError::IoPath {
+                path: path.to_path_buf(),
+                source: e,
+            })
+        })?;
+        layers.extend(load_layers(&rule, Some(path))?);
+    }
 
-    parse_commit_msg_rule(&rule)
-        .map_err(|e| CommitMsgError::System(SystemError::Parse(e.to_string())))
+    Ok(build_commit_msg_rule(layers)?)
 }
 
-#[derive(Debug, Deserialize)]
+/// Check the layers of a rule and deserialize the merged result.
+fn build_commit_msg_rule(layers: Vec<Layer>) -> Result<ParsedCommitMsgRule, ConfigError> {
+    let checked = check_layers(layers)?;
+
+    match checked.rule {
+        Some(rule) if checked.diagnostics.is_empty() => Ok(rule),
+        _ => Err(ConfigError::Invalid(checked.diagnostics)),
+    }
+}
+
+/// Rule file of the commit-msg hook
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
 pub struct ParsedCommitMsgRule {
+    /// Global settings.
     pub global: Option<GlobalRule>,
+    /// Header validation: `type(scope): subject`.
+    #[serde(default)]
     pub header: HeaderRule,
+    /// Body validation. Without it, the body is not checked.
     pub body: Option<BodyRule>,
+    /// Footer validation. Without it, no footer is recognized.
     pub footer: Option<FooterRule>,
+    /// Policy for commits generated by git: merge, revert, fixup, squash and amend.
+    pub commit_kind: Option<CommitKindRule>,
+    /// Severity of each check: `error` blocks the commit, `warning` is printed only, `off`
+    /// disables it. Unlisted checks are errors.
+    #[schemars(schema_with = "crate::config::schema::severity_table")]
+    pub severity: Option<HashMap<RuleCheck, Severity>>,
+    /// Typo detection for commit types, scopes and footer keywords.
+    pub spellcheck: Option<SpellcheckRule>,
+
+    /// Rule file that contributed each effective value, see [`crate::config::extends`]
+    #[serde(skip)]
+    pub sources: RuleSources,
+
+    /// Non-blocking problems of the rule file, e.g. deprecated keys, see [`crate::config::check`]
+    #[serde(skip)]
+    pub warnings: Vec<ConfigDiagnostic>,
 }
 
-#[derive(Debug, Deserialize)]
+impl ParsedCommitMsgRule {
+    /// Configured severity of a check, `error` when not configured
+    pub fn severity_of(&self, check: RuleCheck) -> Severity {
+        self.severity
+            .as_ref()
+            .and_then(|s| s.get(&check).copied())
+            .unwrap_or_default()
+    }
+
+    /// Resolved spellcheck options for one kind of word.
+    ///
+    /// The footer threshold falls back to `footer.start_key_words_spellcheck.threshold`.
+    pub fn spellcheck_options(&self, target: SpellcheckTarget) -> SpellcheckOptions {
+        let cfg = self.spellcheck.as_ref();
+
+        let threshold = match target {
+            SpellcheckTarget::Type => cfg.and_then(|c| c.type_threshold).unwrap_or(0.8),
+            SpellcheckTarget::Scope => cfg.and_then(|c| c.scope_threshold).unwrap_or(0.8),
+            SpellcheckTarget::FooterKeyword => cfg
+                .and_then(|c| c.footer_threshold)
+                .or_else(|| {
+                    self.footer
+                        .as_ref()
+                        .and_then(|f| f.start_key_words_spellcheck.as_ref())
+                        .map(|s| s.threshold)
+                })
+                .unwrap_or(StartKeyWordsSpellcheck::default().threshold),
+        };
+
+        let mut options = SpellcheckOptions::with_threshold(threshold);
+        if let Some(cfg) = cfg {
+            options.algorithm = cfg.algorithm.unwrap_or(options.algorithm);
+            options.case_insensitive = cfg.case_insensitive.unwrap_or(options.case_insensitive);
+            options.max_suggestions = cfg.max_suggestions.unwrap_or(options.max_suggestions);
+        }
+        options
+    }
+}
+
+/// Kind of word checked for typos
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpellcheckTarget {
+    Type,
+    Scope,
+    FooterKeyword,
+}
+
+/// Typo detection settings shared by types, scopes and footer keywords.
+/// Unset settings take the values of [`SpellcheckRule::effective_defaults`].
+#[derive(Debug, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+#[schemars(default = "SpellcheckRule::effective_defaults")]
+pub struct SpellcheckRule {
+    /// Similarity algorithm used for typo detection.
+    pub algorithm: Option<SimilarityAlgorithm>,
+
+    /// Compare case-insensitively and suggest the canonical casing.
+    pub case_insensitive: Option<bool>,
+
+    /// Maximum number of suggestions shown.
+    pub max_suggestions: Option<usize>,
+
+    /// Similarity threshold for commit types.
+    #[schemars(range(min = 0.0, max = 1.0))]
+    pub type_threshold: Option<f64>,
+
+    /// Similarity threshold for scopes.
+    #[schemars(range(min = 0.0, max = 1.0))]
+    pub scope_threshold: Option<f64>,
+
+    /// Similarity threshold for footer keywords. Replaces
+    /// `footer.start_key_words_spellcheck.threshold`, which it defaults to.
+    #[schemars(range(min = 0.0, max = 1.0))]
+    pub footer_threshold: Option<f64>,
+}
+
+impl SpellcheckRule {
+    /// The settings of a rule file that configures no spellcheck
+    pub fn effective_defaults() -> Self {
+        let unset = ParsedCommitMsgRule {
+            global: None,
+            header: HeaderRule::default(),
+            body: None,
+            footer: None,
+            commit_kind: None,
+            severity: None,
+            spellcheck: None,
+            sources: Default::default(),
+            warnings: Vec::new(),
+        };
+        let options = unset.spellcheck_options(SpellcheckTarget::Type);
+        Self {
+            algorithm: Some(options.algorithm),
+            case_insensitive: Some(options.case_insensitive),
+            max_suggestions: Some(options.max_suggestions),
+            type_threshold: Some(options.threshold),
+            scope_threshold: Some(unset.spellcheck_options(SpellcheckTarget::Scope).threshold),
+            footer_threshold: Some(
+                unset
+                    .spellcheck_options(SpellcheckTarget::FooterKeyword)
+                    .threshold,
+            ),
+        }
+    }
+}
+
+/// A single check that can be given a severity in the `[severity]` table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleCheck {
+    Type,
+    Scope,
+    SubjectSpaces,
+    SubjectPeriod,
+    SubjectLength,
+    BodyRequired,
+    BodyBlankLines,
+    BodyTrailingWhitespace,
+    BodyLineLength,
+    FooterBlankLines,
+    FooterKeyword,
+    FooterKeywordTypo,
+    FooterLineLength,
+    FooterTrailingWhitespace,
+    FooterRequiredKeyword,
+}
+
+/// How a failed check is reported
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Block the commit
+    #[default]
+    Error,
+    /// Print a warning, do not block the commit (unless `--strict`)
+    Warning,
+    /// Do not run the check
+    Off,
+}
+
+/// `[global]`
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
 pub struct GlobalRule {
+    /// Version of the rule file format, `major.minor.patch`. Run `gitru config migrate` to
+    /// upgrade an older file.
     pub version: Option<String>,
+    /// Rule files (relative to this file) and presets (`preset:<name>`) this file inherits
+    /// from, e.g. `["preset:conventional", "./shared/base.toml"]`.
+    #[schemars(schema_with = "crate::config::schema::string_list")]
+    pub extends: Option<Vec<String>>,
+    /// Global validation switch.
     pub enable_validation: Option<bool>,
+    /// Skip validation when the first line of the commit message is one of these keywords
+    /// (case-sensitive).
+    #[schemars(schema_with = "crate::config::schema::string_list")]
     pub skip_validation_words: Option<Vec<String>>,
+    /// Policy for skipping validation with one of the `skip_validation_words`.
+    pub skip_validation: Option<SkipValidationRule>,
+    /// Rule files in subdirectories apply to commits that stage files below them: `all`
+    /// requires passing every applicable rule file, `any` one of them, `off` ignores them.
+    /// Only read from the root rule file.
+    pub nested_rules: Option<NestedRulesPolicy>,
 }
 
 impl Default for GlobalRule {
     fn default() -> Self {
         Self {
-            version: Some("1.0.0".into()),
+            version: Some(RULE_VERSION.into()),
+            extends: None,
             enable_validation: Some(true),
             skip_validation_words: Some(vec![]),
+            skip_validation: None,
+            nested_rules: Some(NestedRulesPolicy::All),
         }
     }
 }
 
-#[derive(Debug, Deserialize)]
+/// Which rule files in subdirectories a commit message must pass
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum NestedRulesPolicy {
+    /// Every rule file that applies to a staged path
+    #[default]
+    All,
+    /// At least one rule file that applies to a staged path
+    Any,
+    /// Only the root rule file, rule files in subdirectories are ignored
+    Off,
+}
+
+/// Policy for skipping validation with one of the `skip_validation_words`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct SkipValidationRule {
+    /// Require a reason after the skip word, e.g. `SKIP: hotfix for incident 123`.
+    pub require_reason: Option<bool>,
+
+    /// Branches on which skipping is allowed (glob patterns, e.g. `hotfix/*`).
+    /// Unset means any branch.
+    #[schemars(schema_with = "crate::config::schema::string_list")]
+    pub allowed_branches: Option<Vec<String>>,
+
+    /// Author emails allowed to skip (glob patterns, e.g. `*@example.com`).
+    /// Unset means any author.
+    #[schemars(schema_with = "crate::config::schema::string_list")]
+    pub allowed_authors: Option<Vec<String>>,
+
+    /// Record the bypass as a `Validation-Skipped: <reason>` trailer.
+    pub record_trailer: Option<bool>,
+}
+
+impl Default for SkipValidationRule {
+    fn default() -> Self {
+        Self {
+            require_reason: Some(false),
+            allowed_branches: None,
+            allowed_authors: None,
+            record_trailer: Some(true),
+        }
+    }
+}
+
+/// `[header]`: `type(scope): subject`
+#[derive(Debug, Default, PartialEq, Deserialize, Serialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
 pub struct HeaderRule {
+    /// Commit type validation.
     pub r#type: Type,
+    /// Scope validation. Without it, any scope is accepted.
     pub scope: Option<Scope>,
+    /// Subject validation.
     pub subject: Subject,
 }
 
-#[derive(Debug, Deserialize)]
+/// `[header.type]`, any type is accepted by default
+#[derive(Debug, Default, PartialEq, Deserialize, Serialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
 pub struct Type {
+    /// Allowed commit types. Must not be empty; remove it to accept any type.
+    #[schemars(schema_with = "crate::config::schema::string_list")]
     pub allowed_types: Option<Vec<String>>,
 }
 
-#[derive(Debug, Deserialize)]
+/// `[header.scope]`
+#[derive(Debug, PartialEq, Deserialize, Serialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
 pub struct Scope {
+    /// Whether a scope must be provided. A provided scope must always be one of
+    /// `allowed_scopes`.
     pub required: Option<bool>,
+    /// Allowed scopes. Must not be empty.
+    #[schemars(schema_with = "crate::config::schema::string_list")]
     pub allowed_scopes: Option<Vec<String>>,
 }
 
-#[derive(Debug, Deserialize)]
+impl Default for Scope {
+    fn default() -> Self {
+        Self {
+            required: Some(false),
+            allowed_scopes: None,
+        }
+    }
+}
+
+/// `[header.subject]`
+#[derive(Debug, PartialEq, Deserialize, Serialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
 pub struct Subject {
+    /// Number of spaces between the colon and the subject.
     pub spaces_after_colon: Option<usize>,
+    /// Forbid ending the subject with a period.
     pub forbid_trailing_period: bool,
+    /// Minimum subject length.
     pub min_length: usize,
+    /// Maximum subject length.
     pub max_length: usize,
 }
 
-#[derive(Debug, Deserialize)]
+impl Default for Subject {
+    fn default() -> Self {
+        Self {
+            spaces_after_colon: Some(1),
+            forbid_trailing_period: true,
+            min_length: 2,
+            max_length: 72,
+        }
+    }
+}
+
+/// `[body]`, the body is not checked when the section is missing
+#[derive(Debug, PartialEq, Deserialize, Serialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
 pub struct BodyRule {
+    /// Whether a body is required.
     pub required: bool,
+    /// Minimum length of a body line.
     pub min_line_length: usize,
+    /// Maximum length of a body line.
     pub max_line_length: usize,
+    /// Forbid trailing whitespace in body lines.
     pub forbid_trailing_whitespace: bool,
+    /// Minimum number of blank lines between the header and the body.
     pub min_blank_lines_before_body: usize,
 }
 
-#[derive(Debug, Deserialize)]
+impl Default for BodyRule {
+    fn default() -> Self {
+        Self {
+            required: false,
+            min_line_length: 2,
+            max_line_length: 72,
+            forbid_trailing_whitespace: true,
+            min_blank_lines_before_body: 1,
+        }
+    }
+}
+
+/// `[footer]`, no footer is recognized when the section is missing
+#[derive(Debug, PartialEq, Deserialize, Serialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
 pub struct FooterRule {
+    /// Keywords that start the footer, e.g. `BREAKING CHANGE`, `Closes`.
+    #[schemars(schema_with = "crate::config::schema::string_list")]
     pub start_key_words: Vec<String>,
+    /// Keywords that must appear at the start of a footer line, e.g. `Signed-off-by`.
+    #[schemars(schema_with = "crate::config::schema::string_list")]
+    pub required_key_words: Option<Vec<String>>,
+    /// Minimum number of blank lines before the footer.
     pub min_blank_lines_before_footer: usize,
+    /// Minimum length of a footer line.
     pub min_line_length: usize,
+    /// Maximum length of a footer line.
     pub max_line_length: usize,
+    /// Forbid trailing whitespace in footer lines.
     pub forbid_trailing_whitespace: bool,
+    /// Spell checking of footer keywords.
     pub start_key_words_spellcheck: Option<StartKeyWordsSpellcheck>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+impl Default for FooterRule {
+    fn default() -> Self {
+        Self {
+            start_key_words: ["BREAKING CHANGE", "Closes", "Fixes", "Signed-off-by"]
+                .iter()
+                .map(|k| k.to_string())
+                .collect(),
+            required_key_words: None,
+            min_blank_lines_before_footer: 1,
+            min_line_length: 2,
+            max_line_length: 72,
+            forbid_trailing_whitespace: true,
+            start_key_words_spellcheck: Some(StartKeyWordsSpellcheck::default()),
+        }
+    }
+}
+
+/// `[footer.start_key_words_spellcheck]`
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
 pub struct StartKeyWordsSpellcheck {
-    /// When enabled, if the commit contains only header + body,
-    /// the body will be checked to see whether it is a misspelled footer keyword.
-    /// Enabled by default.
+    /// When the commit has no footer, check whether a body line is a misspelled footer
+    /// keyword.
     pub enable: bool,
 
-    /// Similarity threshold. Default is 0.7.
-    /// When the similarity exceeds this threshold, it is treated as a spelling error.
+    /// Similarity above which a body line is treated as a misspelled footer keyword.
+    #[schemars(range(min = 0.0, max = 1.0))]
+    #[schemars(extend("x-replaced-by" = "spellcheck.footer_threshold"))]
     pub threshold: f64,
 }
 
@@ -116,17 +473,80 @@ impl Default for StartKeyWordsSpellcheck {
     }
 }
 
+/// Policy for commits generated by git itself (merge, revert, fixup, squash, amend),
+/// which do not follow the `type(scope): subject` format.
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct CommitKindRule {
+    /// Whether merge commits (`Merge branch 'x'`) are allowed.
+    pub allow_merge: Option<bool>,
+
+    /// Whether revert commits (`Revert "feat: xxx"`) must contain a
+    /// `This reverts commit <sha>` line. The quoted original header is always validated
+    /// against the header rules.
+    pub require_revert_reference: Option<bool>,
+
+    /// Whether `fixup!`, `squash!` and `amend!` commits are accepted by the commit-msg hook.
+    pub allow_autosquash: Option<bool>,
+
+    /// Whether `fixup!`, `squash!` and `amend!` commits are accepted by range linting
+    /// (`gitru lint`), which usually runs in CI before merging.
+    pub allow_autosquash_in_range: Option<bool>,
+}
+
+impl Default for CommitKindRule {
+    fn default() -> Self {
+        Self {
+            allow_merge: Some(true),
+            require_revert_reference: Some(true),
+            allow_autosquash: Some(true),
+            allow_autosquash_in_range: Some(false),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::constant::COMMIT_MSG_RULE_TEMPLATE;
+
     #[test]
     fn test_parse_commit_msg_rule() {
         let parsed_rule = get_default_path_parsed_commit_msg_rule().unwrap();
         assert_eq!(
             parsed_rule.global.as_ref().unwrap().version.as_deref(),
-            Some("1.0.0")
+            Some(RULE_VERSION)
         );
 
         println!("{:#?}", parsed_rule);
     }
+
+    #[test]
+    fn test_minimal_rule_uses_defaults() {
+        let rule = parse_commit_msg_rule(
+            r#"
+[header.type]
+allowed_types = ["feat", "fix"]
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(rule.header.subject, Subject::default());
+        assert!(rule.header.scope.is_none());
+        assert!(rule.body.is_none());
+        assert!(rule.footer.is_none());
+
+        let rule = parse_commit_msg_rule("[body]\n[footer]\n").unwrap();
+        assert_eq!(rule.body, Some(BodyRule::default()));
+        assert_eq!(rule.footer, Some(FooterRule::default()));
+    }
+
+    #[test]
+    fn test_defaults_match_template() {
+        let rule = parse_commit_msg_rule(COMMIT_MSG_RULE_TEMPLATE).unwrap();
+
+        assert_eq!(rule.header.subject, Subject::default());
+        assert_eq!(rule.body, Some(BodyRule::default()));
+        assert_eq!(rule.footer, Some(FooterRule::default()));
+    }
 }
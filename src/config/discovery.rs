@@ -0,0 +1,283 @@
+//! Where the rule file of a repository is found, and which format it is written in.
+//!
+//! Locations relative to the repository root, in the order they are tried:
+//! 1. `.commit-msg-rule.toml`, written by `gitru init`
+//! 2. `.gitru/commit-msg.toml` (or `.json`, `.yaml`, `.yml`)
+//! 3. `gitru.toml` (or `.json`, `.yaml`, `.yml`)
+//! 4. `[package.metadata.gitru]` in `Cargo.toml`
+//! 5. `[tool.gitru]` in `pyproject.toml`
+//!
+//! `Cargo.toml` and `pyproject.toml` are only used when they contain the table.
+
+use crate::config::overrides::{OverrideSource, RunOverrides};
+use crate::constant::COMMIT_MSG_RULE_FILE_NAME;
+use crate::error::config_error::ConfigError;
+use crate::error::git_error::GitKindError;
+use crate::util::git_path::{GitKind, detect_current_repo};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use toml::{Table, Value};
+
+/// Rule table of `Cargo.toml`
+const CARGO_SECTION: &[&str] = &["package", "metadata", "gitru"];
+
+/// Rule table of `pyproject.toml`
+const PYPROJECT_SECTION: &[&str] = &["tool", "gitru"];
+
+/// Rule file locations relative to the repository root, in discovery order
+pub const RULE_FILE_LOCATIONS: &[&str] = &[
+    COMMIT_MSG_RULE_FILE_NAME,
+    ".gitru/commit-msg.toml",
+    ".gitru/commit-msg.json",
+    ".gitru/commit-msg.yaml",
+    ".gitru/commit-msg.yml",
+    "gitru.toml",
+    "gitru.json",
+    "gitru.yaml",
+    "gitru.yml",
+    "Cargo.toml",
+    "pyproject.toml",
+];
+
+/// Format of a rule file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleFormat {
+    Toml,
+    /// A table of a TOML manifest, e.g. `[tool.gitru]` of `pyproject.toml`
+    TomlSection(&'static [&'static str]),
+    Json,
+    Yaml,
+}
+
+impl RuleFormat {
+    /// Format of a rule file, from its name
+    pub fn of(path: &Path) -> Self {
+        match path.file_name().and_then(|n| n.to_str()) {
+            Some("Cargo.toml") => RuleFormat::TomlSection(CARGO_SECTION),
+            Some("pyproject.toml") => RuleFormat::TomlSection(PYPROJECT_SECTION),
+            _ => match path.extension().and_then(|e| e.to_str()) {
+                Some("json") => RuleFormat::Json,
+                Some("yaml" | "yml") => RuleFormat::Yaml,
+                _ => RuleFormat::Toml,
+            },
+        }
+    }
+
+    /// Whether the file is TOML, so that keys can be located by line in diagnostics
+    pub fn is_toml(&self) -> bool {
+        matches!(self, RuleFormat::Toml | RuleFormat::TomlSection(_))
+    }
+}
+
+/// Parse the rule table of a rule file written in `format`
+pub fn parse_rule_table(
+    content: &str,
+    format: RuleFormat,
+    source_name: &str,
+) -> Result<Table, ConfigError> {
+    let invalid = |reason: String| ConfigError::InvalidRuleFile {
+        source_name: source_name.to_string(),
+        reason,
+    };
+
+    match format {
+        RuleFormat::Toml => toml::from_str(content).map_err(|e| invalid(e.to_string())),
+        RuleFormat::TomlSection(section) => {
+            let table: Table = toml::from_str(content).map_err(|e| invalid(e.to_string()))?;
+            section_of(&table, section)
+                .cloned()
+                .ok_or_else(|| ConfigError::MissingSection {
+                    source_name: source_name.to_string(),
+                    section: section.join("."),
+                })
+        }
+        RuleFormat::Json => serde_json::from_str(content).map_err(|e| invalid(e.to_string())),
+        RuleFormat::Yaml => serde_norway::from_str(content).map_err(|e| invalid(e.to_string())),
+    }
+}
+
+/// The table at `path` of a TOML document
+fn section_of<'a>(table: &'a Table, path: &[&str]) -> Option<&'a Table> {
+    path.iter()
+        .try_fold(table, |table, key| match table.get(*key) {
+            Some(Value::Table(t)) => Some(t),
+            _ => None,
+        })
+}
+
+/// The rule file of the repository at `root`: the first of [`RULE_FILE_LOCATIONS`] that exists
+pub fn discover_rule_file(root: &Path) -> Option<PathBuf> {
+    RULE_FILE_LOCATIONS
+        .iter()
+        .map(|location| root.join(location))
+        .find(|path| {
+            if !path.is_file() {
+                return false;
+            }
+            match RuleFormat::of(path) {
+                RuleFormat::TomlSection(section) => std::fs::read_to_string(path)
+                    .ok()
+                    .and_then(|content| toml::from_str::<Table>(&content).ok())
+                    .is_some_and(|table| section_of(&table, section).is_some()),
+                _ => true,
+            }
+        })
+}
+
+/// The rule file of a repository, or `.commit-msg-rule.toml` in its root when there is none
+pub fn repo_rule_file(git_kind: &GitKind) -> PathBuf {
+    discover_rule_file(git_kind.workdir())
+        .unwrap_or_else(|| git_kind.config_path(COMMIT_MSG_RULE_FILE_NAME))
+}
+
+/// How the rule file in effect was selected
+#[derive(Debug, Clone, PartialEq)]
+pub enum RuleFileOrigin {
+    /// `GITRU_RULE` or `gitru.ruleFile`
+    Override(OverrideSource),
+    /// `--rule` on the command line
+    Argument,
+    /// Found at one of [`RULE_FILE_LOCATIONS`]
+    Discovered,
+    /// No rule file found, `.commit-msg-rule.toml` in the repository root
+    Default,
+}
+
+impl fmt::Display for RuleFileOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuleFileOrigin::Override(source) => write!(f, "{}", source),
+            RuleFileOrigin::Argument => write!(f, "--rule argument"),
+            RuleFileOrigin::Discovered => write!(f, "discovered in the repository"),
+            RuleFileOrigin::Default => write!(f, "default location, not found"),
+        }
+    }
+}
+
+/// The rule file used by the commit-msg hook: the `--rule` argument first, then overrides,
+/// then discovery in the current repository.
+pub fn resolve_rule_file(
+    argument: Option<&PathBuf>,
+    overrides: &RunOverrides,
+) -> Result<(PathBuf, RuleFileOrigin), GitKindError> {
+    if let Some(path) = argument {
+        return Ok((path.clone(), RuleFileOrigin::Argument));
+    }
+    if let Some(rule_file) = &overrides.rule_file {
+        return Ok((
+            rule_file.value.clone(),
+            RuleFileOrigin::Override(rule_file.source.clone()),
+        ));
+    }
+
+    let git_kind = detect_current_repo()?;
+    Ok(match discover_rule_file(git_kind.workdir()) {
+        Some(path) => (path, RuleFileOrigin::Discovered),
+        None => (
+            git_kind.config_path(COMMIT_MSG_RULE_FILE_NAME),
+            RuleFileOrigin::Default,
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::commit_msg_rule::load_commit_msg_rule;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_discovery_order() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        assert_eq!(discover_rule_file(root), None);
+
+        // a manifest without the table is not a rule file
+        fs::write(root.join("Cargo.toml"), "[package]\nname = \"demo\"\n").unwrap();
+        assert_eq!(discover_rule_file(root), None);
+
+        fs::write(
+            root.join("pyproject.toml"),
+            "[tool.gitru.header.subject]\nmax_length = 50\n",
+        )
+        .unwrap();
+        assert_eq!(discover_rule_file(root), Some(root.join("pyproject.toml")));
+
+        fs::write(root.join("gitru.yaml"), "header: {}\n").unwrap();
+        assert_eq!(discover_rule_file(root), Some(root.join("gitru.yaml")));
+
+        fs::create_dir(root.join(".gitru")).unwrap();
+        fs::write(root.join(".gitru/commit-msg.json"), "{}").unwrap();
+        assert_eq!(
+            discover_rule_file(root),
+            Some(root.join(".gitru/commit-msg.json"))
+        );
+
+        fs::write(root.join(COMMIT_MSG_RULE_FILE_NAME), "").unwrap();
+        assert_eq!(
+            discover_rule_file(root),
+            Some(root.join(COMMIT_MSG_RULE_FILE_NAME))
+        );
+    }
+
+    #[test]
+    fn test_load_formats() {
+        let dir = TempDir::new().unwrap();
+        let files = [
+            (
+                "Cargo.toml",
+                "[package]\nname = \"demo\"\n\n[package.metadata.gitru.header.subject]\nmax_length = 50\n",
+            ),
+            (
+                "pyproject.toml",
+                "[project]\nname = \"demo\"\n\n[tool.gitru.header.subject]\nmax_length = 50\n",
+            ),
+            (
+                "gitru.json",
+                r#"{ "header": { "subject": { "max_length": 50 } } }"#,
+            ),
+            ("gitru.yaml", "header:\n  subject:\n    max_length: 50\n"),
+        ];
+
+        for (name, content) in files {
+            let path = dir.path().join(name);
+            fs::write(&path, content).unwrap();
+            let rule = load_commit_msg_rule(&path).unwrap();
+            assert_eq!(rule.header.subject.max_length, 50, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_diagnostics_in_other_formats() {
+        let dir = TempDir::new().unwrap();
+
+        // located by line inside the manifest
+        let cargo = dir.path().join("Cargo.toml");
+        fs::write(
+            &cargo,
+            "[package]\nname = \"demo\"\n\n[package.metadata.gitru.header.subject]\nmax_lenght = 50\n",
+        )
+        .unwrap();
+        let err = load_commit_msg_rule(&cargo).unwrap_err().to_string();
+        assert!(
+            err.contains("Cargo.toml:5: `header.subject.max_lenght`"),
+            "{}",
+            err
+        );
+
+        // JSON has no line numbers
+        let json = dir.path().join("gitru.json");
+        fs::write(
+            &json,
+            r#"{ "header": { "subject": { "max_lenght": 50 } } }"#,
+        )
+        .unwrap();
+        let err = load_commit_msg_rule(&json).unwrap_err().to_string();
+        assert!(
+            err.contains("gitru.json: `header.subject.max_lenght`"),
+            "{}",
+            err
+        );
+    }
+}
@@ -0,0 +1,426 @@
+//! Rule file inheritance via `global.extends`
+//!
+//! ```toml
+//! [global]
+//! extends = ["preset:conventional", "./shared/base.toml"]
+//! ```
+//!
+//! Entries are either embedded presets (`preset:<name>`) or paths relative to the
+//! file containing them, in any format of [`crate::config::discovery`]. Layers are applied in order: the extended files from left
+//! to right (each after its own `extends`), then the including file itself.
+//!
+//! Merge semantics:
+//! * tables are merged key by key
+//! * scalars and plain lists replace the inherited value
+//! * a list can be modified instead of replaced with an operation table:
+//!   `allowed_types = { append = ["perf"] }`, `{ remove = ["style"] }` or
+//!   `{ replace = [...] }`; operations are applied in the order replace, remove, append
+
+use crate::config::discovery::{RuleFormat, parse_rule_table};
+use crate::config::preset::{PRESET_PREFIX, preset, preset_names};
+use crate::error::config_error::ConfigError;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use toml::{Table, Value};
+
+pub(crate) const LIST_OPERATIONS: [&str; 3] = ["replace", "remove", "append"];
+
+/// The rule files that contributed each effective value, keyed by dotted path
+/// (e.g. `header.type.allowed_types`).
+///
+/// A list modified with `append` / `remove` lists every contributing file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RuleSources(BTreeMap<String, Vec<String>>);
+
+impl RuleSources {
+    /// Files that contributed the value at `key`
+    pub fn source_of(&self, key: &str) -> Option<&[String]> {
+        self.0.get(key).map(|s| s.as_slice())
+    }
+
+    /// All effective values and their sources, sorted by key
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Vec<String>)> {
+        self.0.iter()
+    }
+
+    fn set(&mut self, key: String, source: &str) {
+        self.0.insert(key, vec![source.to_string()]);
+    }
+
+    fn add(&mut self, key: String, source: &str) {
+        let sources = self.0.entry(key).or_default();
+        if !sources.iter().any(|s| s == source) {
+            sources.push(source.to_string());
+        }
+    }
+
+    /// Forget the sources of `key` and everything below it
+    fn clear(&mut self, key: &str) {
+        let nested = format!("{}.", key);
+        self.0.retain(|k, _| k != key && !k.starts_with(&nested));
+    }
+
+    /// Record `source` for every leaf value of `value`
+    fn record(&mut self, key: String, value: &Value, source: &str) {
+        match value {
+            Value::Table(table) => {
+                for (k, v) in table {
+                    self.record(format!("{}.{}", key, k), v, source);
+                }
+            }
+            _ => self.set(key, source),
+        }
+    }
+}
+
+/// One rule file (or preset) of the `extends` chain
+#[derive(Debug, Clone)]
+pub struct Layer {
+    /// Path of the rule file, or `preset:<name>`
+    pub name: String,
+    /// Raw file content, kept for locating keys in diagnostics
+    pub content: String,
+    pub format: RuleFormat,
+    pub table: Table,
+}
+
+/// Resolve the `extends` chain of a rule file and merge all layers.
+///
+/// `origin` is the path of the rule file, which also determines its format; relative
+/// `extends` entries are resolved against its directory, or against the current
+/// directory without one.
+pub fn resolve_rule(
+    content: &str,
+    origin: Option<&Path>,
+) -> Result<(Table, RuleSources), ConfigError> {
+    merge_layers(load_layers(content, origin)?)
+}
+
+/// The layers of the `extends` chain of a rule file, in the order they are applied.
+/// The rule file itself is the last layer.
+pub fn load_layers(content: &str, origin: Option<&Path>) -> Result<Vec<Layer>, ConfigError> {
+    let format = origin.map(RuleFormat::of).unwrap_or(RuleFormat::Toml);
+    load_layers_as(content, origin, format)
+}
+
+/// Like [`load_layers`], for a rule table whose format does not follow from the file name,
+/// e.g. `[rules]` of the user config
+pub fn load_layers_as(
+    content: &str,
+    origin: Option<&Path>,
+    format: RuleFormat,
+) -> Result<Vec<Layer>, ConfigError> {
+    let (name, id, base_dir) = match origin {
+        Some(path) => (
+            path.display().to_string(),
+            canonical_id(path),
+            path.parent().map(Path::to_path_buf).unwrap_or_default(),
+        ),
+        None => ("<rule>".to_string(), "<rule>".to_string(), PathBuf::new()),
+    };
+
+    let mut layers = Vec::new();
+    let mut stack = Vec::new();
+    collect_layers(
+        content,
+        format,
+        name,
+        id,
+        &base_dir,
+        &mut stack,
+        &mut layers,
+    )?;
+
+    Ok(layers)
+}
+
+/// Merge layers in order, recording which layer contributed each value
+pub fn merge_layers(layers: Vec<Layer>) -> Result<(Table, RuleSources), ConfigError> {
+    let mut merged = Table::new();
+    let mut sources = RuleSources::default();
+    for layer in layers {
+        merge_table(&mut merged, layer.table, "", &layer.name, &mut sources)?;
+    }
+
+    Ok((merged, sources))
+}
+
+/// Depth-first walk of the `extends` chain, pushing each layer after the layers it extends.
+/// `stack` holds the ids of the layers currently being resolved, to detect cycles.
+fn collect_layers(
+    content: &str,
+    format: RuleFormat,
+    name: String,
+    id: String,
+    base_dir: &Path,
+    stack: &mut Vec<String>,
+    layers: &mut Vec<Layer>,
+) -> Result<(), ConfigError> {
+    if stack.contains(&id) {
+        let mut chain = stack.clone();
+        chain.push(id);
+        return Err(ConfigError::ExtendsCycle {
+            chain: chain.join(" -> "),
+        });
+    }
+
+    let table = parse_rule_table(content, format, &name)?;
+
+    stack.push(id);
+    for entry in extends_entries(&table, &name)? {
+        if let Some(preset_name) = entry.strip_prefix(PRESET_PREFIX) {
+            let content = preset(preset_name).ok_or_else(|| ConfigError::UnknownPreset {
+                name: preset_name.to_string(),
+                available: preset_names().join(", "),
+            })?;
+            // presets have no directory of their own, relative entries stay relative to the includer
+            collect_layers(
+                content,
+                RuleFormat::Toml,
+                entry.clone(),
+                entry.clone(),
+                base_dir,
+                stack,
+                layers,
+            )?;
+        } else {
+            let path = base_dir.join(&entry);
+            let content = std::fs::read_to_string(&path).map_err(|e| ConfigError::ExtendsRead {
+                path: path.clone(),
+                source: e,
+            })?;
+            let dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+            collect_layers(
+                &content,
+                RuleFormat::of(&path),
+                path.display().to_string(),
+                canonical_id(&path),
+                &dir,
+                stack,
+                layers,
+            )?;
+        }
+    }
+    stack.pop();
+
+    layers.push(Layer {
+        name,
+        content: content.to_string(),
+        format,
+        table,
+    });
+    Ok(())
+}
+
+/// Entries of `global.extends`, empty when not set
+fn extends_entries(table: &Table, name: &str) -> Result<Vec<String>, ConfigError> {
+    let Some(extends) = table.get("global").and_then(|g| g.get("extends")) else {
+        return Ok(Vec::new());
+    };
+
+    let invalid = || ConfigError::InvalidExtends {
+        source_name: name.to_string(),
+        reason: "expected a list of strings".to_string(),
+    };
+
+    extends
+        .as_array()
+        .ok_or_else(invalid)?
+        .iter()
+        .map(|v| v.as_str().map(str::to_string).ok_or_else(invalid))
+        .collect()
+}
+
+/// Identity of a rule file for cycle detection
+fn canonical_id(path: &Path) -> String {
+    path.canonicalize()
+        .unwrap_or_else(|_| path.to_path_buf())
+        .display()
+        .to_string()
+}
+
+/// Merge `overlay` (from the rule file `source`) into `base`
+fn merge_table(
+    base: &mut Table,
+    overlay: Table,
+    prefix: &str,
+    source: &str,
+    sources: &mut RuleSources,
+) -> Result<(), ConfigError> {
+    for (key, value) in overlay {
+        let path = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+
+        match (base.get_mut(&key), value) {
+            (inherited, Value::Table(op)) if is_list_operation(&op) => {
+                let replaced = op.contains_key("replace");
+                let list = apply_list_operation(inherited.map(|v| &*v), op, &path, source)?;
+                base.insert(key, Value::Array(list));
+                if replaced {
+                    sources.set(path, source);
+                } else {
+                    sources.add(path, source);
+                }
+            }
+            (Some(Value::Table(inherited)), Value::Table(table)) => {
+                merge_table(inherited, table, &path, source, sources)?;
+            }
+            (_, value) => {
+                sources.clear(&path);
+                sources.record(path, &value, source);
+                base.insert(key, value);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether a table is a list operation, e.g. `{ append = ["perf"] }`
+fn is_list_operation(table: &Table) -> bool {
+    !table.is_empty()
+        && table
+            .iter()
+            .all(|(k, v)| LIST_OPERATIONS.contains(&k.as_str()) && v.is_array())
+}
+
+fn apply_list_operation(
+    inherited: Option<&Value>,
+    op: Table,
+    key: &str,
+    source: &str,
+) -> Result<Vec<Value>, ConfigError> {
+    let mut list = match inherited {
+        None => Vec::new(),
+        Some(Value::Array(list)) => list.clone(),
+        Some(_) => {
+            return Err(ConfigError::InvalidListOperation {
+                key: key.to_string(),
+                operation: op.keys().cloned().collect::<Vec<_>>().join("/"),
+                source_name: source.to_string(),
+            });
+        }
+    };
+
+    let items = |name: &str| match op.get(name) {
+        Some(Value::Array(items)) => items.clone(),
+        _ => Vec::new(),
+    };
+
+    if op.contains_key("replace") {
+        list = items("replace");
+    }
+
+    let removed = items("remove");
+    list.retain(|v| !removed.contains(v));
+
+    for item in items("append") {
+        if !list.contains(&item) {
+            list.push(item);
+        }
+    }
+
+    Ok(list)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn allowed_types(table: &Table) -> Vec<&str> {
+        table["header"]["type"]["allowed_types"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| v.as_str().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_extends_preset_and_file() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir(dir.path().join("shared")).unwrap();
+        fs::write(
+            dir.path().join("shared/base.toml"),
+            r#"
+[header.type]
+allowed_types = { append = ["perf"], remove = ["style"] }
+
+[header.subject]
+max_length = 100
+"#,
+        )
+        .unwrap();
+
+        let root = dir.path().join(".commit-msg-rule.toml");
+        let content = r#"
+[global]
+extends = ["preset:conventional", "./shared/base.toml"]
+
+[header.subject]
+max_length = 80
+"#;
+        fs::write(&root, content).unwrap();
+
+        let (table, sources) = resolve_rule(content, Some(&root)).unwrap();
+
+        let types = allowed_types(&table);
+        assert!(types.contains(&"perf"));
+        assert!(!types.contains(&"style"));
+        assert!(types.contains(&"feat"));
+        assert_eq!(
+            table["header"]["subject"]["max_length"].as_integer(),
+            Some(80)
+        );
+
+        let base = dir.path().join("./shared/base.toml").display().to_string();
+        assert_eq!(
+            sources.source_of("header.type.allowed_types").unwrap(),
+            ["preset:conventional".to_string(), base]
+        );
+        assert_eq!(
+            sources.source_of("header.subject.max_length").unwrap(),
+            [root.display().to_string()]
+        );
+        assert_eq!(
+            sources.source_of("header.subject.min_length").unwrap(),
+            ["preset:conventional".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_extends_cycle() {
+        let dir = TempDir::new().unwrap();
+        fs::write(
+            dir.path().join("a.toml"),
+            "[global]\nextends = [\"b.toml\"]",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("b.toml"),
+            "[global]\nextends = [\"a.toml\"]",
+        )
+        .unwrap();
+
+        let root = dir.path().join("a.toml");
+        let content = fs::read_to_string(&root).unwrap();
+
+        assert!(matches!(
+            resolve_rule(&content, Some(&root)),
+            Err(ConfigError::ExtendsCycle { .. })
+        ));
+    }
+
+    #[test]
+    fn test_unknown_preset() {
+        assert!(matches!(
+            resolve_rule("[global]\nextends = [\"preset:nope\"]", None),
+            Err(ConfigError::UnknownPreset { .. })
+        ));
+    }
+}
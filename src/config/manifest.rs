@@ -0,0 +1,241 @@
+//! The hook manifest: the hooks every clone of a repository should have installed
+//!
+//! Committed at `.gitru/hooks.toml` in the repository root:
+//!
+//! ```toml
+//! [hooks]
+//! commit-msg = true
+//! # or with the options of `gitru install`
+//! # commit-msg = { chain = "after", exec = "path" }
+//!
+//! # commands run by the hook, with the arguments git passes to it as "$@";
+//! # `glob` and `{files}` are for pre-commit, prepare-commit-msg and commit-msg
+//! [hooks.pre-commit]
+//! parallel = true
+//! commands = [
+//!     { name = "fmt", run = "cargo fmt --check -- {files}", glob = ["*.rs"] },
+//!     { run = "typos {files}" },
+//! ]
+//! ```
+//!
+//! `gitru sync` (or `gitru install --all`) installs missing hooks, updates outdated ones and
+//! removes the gitru hooks the manifest no longer lists; `gitru check-installed` only reports
+//! the differences. Options left out are not enforced, a local `--exec` choice is kept.
+//!
+//! The commands of a hook are run by `gitru run <hook>`, see [`crate::hook::runner`].
+
+use crate::error::config_error::ConfigError;
+use crate::hook::kind::{ChainOrder, HookExec, HookKind};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use toml::Value;
+
+/// Location of the manifest, relative to the repository root
+pub const HOOK_MANIFEST_PATH: &str = ".gitru/hooks.toml";
+
+/// Placeholder of a command replaced with the staged files it applies to
+pub const FILES_PLACEHOLDER: &str = "{files}";
+
+/// Install options and commands of a hook in the manifest
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HookOptions {
+    pub chain: Option<ChainOrder>,
+    pub exec: Option<HookExec>,
+    /// Run the commands at the same time, not allowed for hooks that edit the commit message
+    #[serde(default)]
+    pub parallel: bool,
+    #[serde(default)]
+    pub commands: Vec<HookCommand>,
+}
+
+/// A command run by a hook
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HookCommand {
+    /// Name shown in the output, the command itself by default
+    pub name: Option<String>,
+    /// Shell command, `{files}` is replaced with the staged files it applies to. The command
+    /// is skipped when there are none. Only for hooks with staged files, see
+    /// [`HookKind::has_staged_files`].
+    pub run: String,
+    /// Globs of the staged files the command applies to, all files when empty. The command is
+    /// skipped when no staged file matches. Only for hooks with staged files.
+    #[serde(default)]
+    pub glob: Vec<String>,
+}
+
+impl HookCommand {
+    /// Name shown in the output
+    pub fn display_name(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.run)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ManifestFile {
+    #[serde(default)]
+    hooks: BTreeMap<String, Value>,
+}
+
+/// The hook manifest of a repository, see the module documentation
+#[derive(Debug, Clone, PartialEq)]
+pub struct HookManifest {
+    pub path: PathBuf,
+    /// The hooks to install, with their options. Hooks set to `false` are left out. Kept by
+    /// name, a manifest may list hooks that this version of gitru does not know.
+    pub hooks: BTreeMap<String, HookOptions>,
+}
+
+impl HookManifest {
+    /// Read the manifest of the repository at `workdir`, `None` when there is none
+    pub fn load(workdir: &Path) -> Result<Option<Self>, ConfigError> {
+        let path = workdir.join(HOOK_MANIFEST_PATH);
+        match std::fs::read_to_string(&path) {
+            Ok(content) => Self::parse(&content, path).map(Some),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(ConfigError::ManifestRead { path, source: e }),
+        }
+    }
+
+    /// Parse the content of the manifest at `path`
+    pub fn parse(content: &str, path: PathBuf) -> Result<Self, ConfigError> {
+        let invalid = |reason: String| ConfigError::InvalidManifest {
+            path: path.clone(),
+            reason,
+        };
+        let file: ManifestFile = toml::from_str(content).map_err(|e| invalid(e.to_string()))?;
+
+        let mut hooks = BTreeMap::new();
+        for (hook, entry) in file.hooks {
+            let options = match entry {
+                Value::Boolean(false) => continue,
+                Value::Boolean(true) => HookOptions::default(),
+                Value::Table(table) => HookOptions::deserialize(table)
+                    .map_err(|e| invalid(format!("`hooks.{}`: {}", hook, e.message())))?,
+                other => {
+                    return Err(invalid(format!(
+                        "`hooks.{}` must be a boolean or a table of options, found {}",
+                        hook,
+                        other.type_str()
+                    )));
+                }
+            };
+            if options.parallel && hook.parse().is_ok_and(|k: HookKind| k.edits_message()) {
+                return Err(invalid(format!(
+                    "`hooks.{}`: commands of {} edit the commit message and cannot run in parallel",
+                    hook, hook
+                )));
+            }
+            let staged = hook.parse().is_ok_and(|k: HookKind| k.has_staged_files());
+            for command in &options.commands {
+                if !staged && (!command.glob.is_empty() || command.run.contains(FILES_PLACEHOLDER))
+                {
+                    return Err(invalid(format!(
+                        "`hooks.{}`: {} runs without staged files, `glob` and `{}` are only \
+                         available to pre-commit, prepare-commit-msg and commit-msg",
+                        hook, hook, FILES_PLACEHOLDER
+                    )));
+                }
+                for glob in &command.glob {
+                    globset::Glob::new(glob)
+                        .map_err(|e| invalid(format!("`hooks.{}`: {}", hook, e)))?;
+                }
+            }
+            hooks.insert(hook, options);
+        }
+
+        Ok(Self { path, hooks })
+    }
+
+    /// Options of `hook`, `None` when the manifest does not install it
+    pub fn options(&self, hook: HookKind) -> Option<&HookOptions> {
+        self.hooks.get(hook.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_manifest() {
+        let manifest = HookManifest::parse(
+            "[hooks]\ncommit-msg = { chain = \"after\", exec = \"repo:tools/gitru\" }\n\
+             pre-push = false\n",
+            PathBuf::from(HOOK_MANIFEST_PATH),
+        )
+        .unwrap();
+
+        assert_eq!(
+            manifest.options(HookKind::CommitMsg),
+            Some(&HookOptions {
+                chain: Some(ChainOrder::After),
+                exec: Some(HookExec::Repo("tools/gitru".to_string())),
+                ..Default::default()
+            })
+        );
+        assert_eq!(manifest.options(HookKind::PrePush), None);
+
+        let err = HookManifest::parse(
+            "[hooks]\ncommit-msg = { exec = \"nix\" }\n",
+            PathBuf::from(HOOK_MANIFEST_PATH),
+        )
+        .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("`hooks.commit-msg`: unknown strategy `nix`")
+        );
+
+        assert!(
+            HookManifest::parse("[hooks]\ncommit-msg = 1\n", PathBuf::from("hooks.toml")).is_err()
+        );
+    }
+
+    #[test]
+    fn test_parse_manifest_commands() {
+        let manifest = HookManifest::parse(
+            "[hooks.pre-commit]\nparallel = true\ncommands = [\n\
+             { name = \"fmt\", run = \"cargo fmt --check -- {files}\", glob = [\"*.rs\"] },\n\
+             { run = \"typos\" },\n]\n",
+            PathBuf::from(HOOK_MANIFEST_PATH),
+        )
+        .unwrap();
+        let options = manifest.options(HookKind::PreCommit).unwrap();
+        assert!(options.parallel);
+        assert_eq!(options.commands.len(), 2);
+        assert_eq!(options.commands[0].display_name(), "fmt");
+        assert_eq!(options.commands[0].glob, ["*.rs"]);
+        assert_eq!(options.commands[1].display_name(), "typos");
+
+        let err = HookManifest::parse(
+            "[hooks.commit-msg]\nparallel = true\n",
+            PathBuf::from(HOOK_MANIFEST_PATH),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("cannot run in parallel"));
+
+        assert!(
+            HookManifest::parse(
+                "[hooks.pre-commit]\ncommands = [{ run = \"x\", glob = [\"[\"] }]\n",
+                PathBuf::from(HOOK_MANIFEST_PATH),
+            )
+            .is_err()
+        );
+
+        // pre-push has no staged files
+        for command in [
+            "{ run = \"x {files}\" }",
+            "{ run = \"x\", glob = [\"*.rs\"] }",
+        ] {
+            let err = HookManifest::parse(
+                &format!("[hooks.pre-push]\ncommands = [{}]\n", command),
+                PathBuf::from(HOOK_MANIFEST_PATH),
+            )
+            .unwrap_err();
+            assert!(err.to_string().contains("runs without staged files"));
+        }
+    }
+}
@@ -0,0 +1,298 @@
+//! Rule file format versions and `gitru config migrate`.
+//!
+//! `global.version` declares the format a rule file was written for. Keys that are
+//! renamed in a later format stay accepted with a warning (see `replaced_by` in
+//! [`crate::config::schema`]) until the file is migrated.
+//!
+//! History:
+//! * 1.0.0: initial format
+//! * 1.1.0: `footer.start_key_words_spellcheck.threshold` moved to `spellcheck.footer_threshold`
+
+use crate::config::discovery::RuleFormat;
+use crate::config::schema::deprecated_keys;
+use crate::error::config_error::ConfigError;
+use std::fmt;
+use toml_edit::{DocumentMut, Item, Key, TableLike, Value};
+
+/// Rule file format written by this version of gitru
+pub const RULE_VERSION: &str = "1.1.0";
+
+/// A `major.minor.patch` rule file format version
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct RuleVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl RuleVersion {
+    /// Parse `major.minor.patch`
+    pub fn parse(version: &str) -> Option<Self> {
+        let mut parts = version.trim().split('.').map(|p| p.parse::<u32>().ok());
+        let version = Self {
+            major: parts.next()??,
+            minor: parts.next()??,
+            patch: parts.next()??,
+        };
+        parts.next().is_none().then_some(version)
+    }
+
+    /// The format written by this version of gitru
+    pub fn current() -> Self {
+        Self::parse(RULE_VERSION).unwrap_or(Self {
+            major: 1,
+            minor: 0,
+            patch: 0,
+        })
+    }
+}
+
+impl fmt::Display for RuleVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Result of migrating a rule file
+#[derive(Debug, Clone, PartialEq)]
+pub struct Migration {
+    /// The migrated rule file, with comments and layout preserved
+    pub content: String,
+    /// One line per change made, empty when the file is already up to date
+    pub changes: Vec<String>,
+}
+
+/// Rewrite a rule file to the current format: deprecated keys are moved to their
+/// replacement and `global.version` is updated.
+///
+/// Only the given file is migrated, not the files it extends. In a manifest such as
+/// `Cargo.toml`, only the rule table is touched.
+pub fn migrate_rule(
+    content: &str,
+    format: RuleFormat,
+    source_name: &str,
+) -> Result<Migration, ConfigError> {
+    let section = match format {
+        RuleFormat::Toml => &[][..],
+        RuleFormat::TomlSection(section) => section,
+        RuleFormat::Json | RuleFormat::Yaml => {
+            return Err(ConfigError::MigrateUnsupported {
+                source_name: source_name.to_string(),
+            });
+        }
+    };
+
+    let mut doc: DocumentMut =
+        content
+            .parse()
+            .map_err(|e: toml_edit::TomlError| ConfigError::InvalidRuleFile {
+                source_name: source_name.to_string(),
+                reason: e.to_string(),
+            })?;
+
+    let root =
+        table_at(doc.as_table_mut(), section).ok_or_else(|| ConfigError::MissingSection {
+            source_name: source_name.to_string(),
+            section: section.join("."),
+        })?;
+
+    let mut changes = Vec::new();
+
+    if let Some(item) = root.get("global").and_then(|g| g.get("version")) {
+        let version = item.as_str().unwrap_or_default();
+        match RuleVersion::parse(version) {
+            None => {
+                return Err(ConfigError::InvalidVersion {
+                    source_name: source_name.to_string(),
+                    version: version.to_string(),
+                });
+            }
+            Some(v) if v > RuleVersion::current() => {
+                return Err(ConfigError::UnsupportedVersion {
+                    source_name: source_name.to_string(),
+                    version: version.to_string(),
+                    supported: RULE_VERSION.to_string(),
+                });
+            }
+            Some(_) => {}
+        }
+    }
+
+    for (key, replacement) in deprecated_keys() {
+        if let Some(change) = move_key(root, &key, &replacement) {
+            changes.push(change);
+        }
+    }
+
+    if let Some(version) = root
+        .get_mut("global")
+        .and_then(|g| g.get_mut("version"))
+        .and_then(Item::as_value_mut)
+        && version.as_str() != Some(RULE_VERSION)
+    {
+        changes.push(format!(
+            "updated `global.version` from {} to {}",
+            version.as_str().unwrap_or_default(),
+            RULE_VERSION
+        ));
+        let decor = version.decor().clone();
+        *version = Value::from(RULE_VERSION);
+        *version.decor_mut() = decor;
+    }
+
+    Ok(Migration {
+        content: doc.to_string(),
+        changes,
+    })
+}
+
+/// Move the value at dotted `from` to dotted `to`, keeping its comments.
+/// When `to` is already set, the value at `from` is dropped since `to` takes precedence.
+fn move_key(root: &mut dyn TableLike, from: &str, to: &str) -> Option<String> {
+    let (from_parent, from_leaf) = split_key(from);
+    let parent = table_at(root, &from_parent)?;
+    let leaf_decor = parent.get_key_value(from_leaf)?.0.leaf_decor().clone();
+    let item = parent.remove(from_leaf)?;
+
+    let (to_parent, to_leaf) = split_key(to);
+    let target = table_at_or_insert(root, &to_parent)?;
+    if target.contains_key(to_leaf) {
+        return Some(format!("removed `{}`, `{}` is already set", from, to));
+    }
+
+    let key = Key::new(to_leaf).with_leaf_decor(leaf_decor);
+    target.entry_format(&key).or_insert(item);
+    Some(format!("moved `{}` to `{}`", from, to))
+}
+
+/// `a.b.c` → (`[a, b]`, `c`)
+fn split_key(key: &str) -> (Vec<&str>, &str) {
+    let mut parts: Vec<&str> = key.split('.').collect();
+    let leaf = parts.pop().unwrap_or_default();
+    (parts, leaf)
+}
+
+fn table_at<'a>(root: &'a mut dyn TableLike, path: &[&str]) -> Option<&'a mut dyn TableLike> {
+    path.iter().try_fold(root, |table, key| {
+        table.get_mut(key).and_then(Item::as_table_like_mut)
+    })
+}
+
+/// Like [`table_at`], creating missing tables as `[section]` headers at the end of the file
+fn table_at_or_insert<'a>(
+    root: &'a mut dyn TableLike,
+    path: &[&str],
+) -> Option<&'a mut dyn TableLike> {
+    path.iter().try_fold(root, |table, key| {
+        table
+            .entry(key)
+            .or_insert_with(toml_edit::table)
+            .as_table_like_mut()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::commit_msg_rule::parse_commit_msg_rule;
+
+    const OLD_RULE: &str = r#"[global]
+version = "1.0.0" # format version
+
+[footer]
+start_key_words = ["Closes"]
+
+[footer.start_key_words_spellcheck]
+enable = true
+# Similarity threshold
+threshold = 0.6
+"#;
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(
+            RuleVersion::parse("1.2.3"),
+            Some(RuleVersion {
+                major: 1,
+                minor: 2,
+                patch: 3
+            })
+        );
+        assert!(RuleVersion::parse("1.2").is_none());
+        assert!(RuleVersion::parse("1.2.3.4").is_none());
+        assert!(RuleVersion::parse("v1.2.3").is_none());
+        assert!(RuleVersion::parse("1.10.0") > RuleVersion::parse("1.9.0"));
+    }
+
+    #[test]
+    fn test_migrate_preserves_comments() {
+        let migration = migrate_rule(OLD_RULE, RuleFormat::Toml, "<rule>").unwrap();
+
+        assert_eq!(
+            migration.content,
+            r#"[global]
+version = "1.1.0" # format version
+
+[footer]
+start_key_words = ["Closes"]
+
+[footer.start_key_words_spellcheck]
+enable = true
+
+[spellcheck]
+# Similarity threshold
+footer_threshold = 0.6
+"#
+        );
+        assert_eq!(migration.changes.len(), 2);
+
+        let rule = parse_commit_msg_rule(&migration.content).unwrap();
+        assert_eq!(rule.spellcheck.unwrap().footer_threshold, Some(0.6));
+
+        // already up to date
+        let again = migrate_rule(&migration.content, RuleFormat::Toml, "<rule>").unwrap();
+        assert!(again.changes.is_empty());
+        assert_eq!(again.content, migration.content);
+    }
+
+    #[test]
+    fn test_migrate_keeps_existing_replacement() {
+        let content = format!("{}\n[spellcheck]\nfooter_threshold = 0.9\n", OLD_RULE);
+        let migration = migrate_rule(&content, RuleFormat::Toml, "<rule>").unwrap();
+
+        assert!(!migration.content.contains("threshold = 0.6"));
+        assert!(migration.content.contains("footer_threshold = 0.9"));
+    }
+
+    #[test]
+    fn test_migrate_manifest_section() {
+        let content = "[package]\nname = \"demo\"\nversion = \"0.1.0\"\n\n[package.metadata.gitru.global]\nversion = \"1.0.0\"\n";
+        let migration = migrate_rule(
+            content,
+            RuleFormat::of(std::path::Path::new("Cargo.toml")),
+            "Cargo.toml",
+        )
+        .unwrap();
+
+        assert_eq!(
+            migration.content,
+            content.replace("version = \"1.0.0\"", "version = \"1.1.0\"")
+        );
+        assert!(matches!(
+            migrate_rule("{}", RuleFormat::Json, "gitru.json"),
+            Err(ConfigError::MigrateUnsupported { .. })
+        ));
+    }
+
+    #[test]
+    fn test_migrate_newer_version() {
+        assert!(matches!(
+            migrate_rule(
+                "[global]\nversion = \"2.0.0\"\n",
+                RuleFormat::Toml,
+                "<rule>"
+            ),
+            Err(ConfigError::UnsupportedVersion { .. })
+        ));
+    }
+}
@@ -1 +1,12 @@
+pub mod check;
 pub mod commit_msg_rule;
+pub mod discovery;
+pub mod extends;
+pub mod manifest;
+pub mod migrate;
+pub mod nested;
+pub mod overrides;
+pub mod preset;
+pub mod schema;
+pub mod show;
+pub mod user;
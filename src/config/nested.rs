@@ -0,0 +1,108 @@
+//! Rule files in subdirectories, for monorepos.
+//!
+//! A rule file in a subdirectory (found at the same locations as the root one, see
+//! [`crate::config::discovery`]) applies to the staged files below it; each file uses
+//! the nearest rule file above it, or the root rule file. When the staged files span
+//! several rule files, `global.nested_rules` of the root rule file decides whether the
+//! message must pass all of them or any of them.
+
+use crate::config::discovery::discover_rule_file;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+/// Rule files that apply to a commit, each with the staged paths it covers.
+///
+/// `staged` holds paths relative to `workdir`. Files outside every subdirectory rule
+/// file are covered by `root_rule`, or by nothing when `root_rule` is `None`.
+pub fn applicable_rule_files(
+    workdir: &Path,
+    root_rule: Option<&Path>,
+    staged: &[String],
+) -> BTreeMap<PathBuf, Vec<String>> {
+    let mut finder = NearestRuleFile {
+        workdir,
+        cache: HashMap::new(),
+    };
+
+    let mut applicable: BTreeMap<PathBuf, Vec<String>> = BTreeMap::new();
+    for path in staged {
+        let rule_file = finder
+            .find(&workdir.join(path))
+            .or_else(|| root_rule.map(Path::to_path_buf));
+        if let Some(rule_file) = rule_file {
+            applicable.entry(rule_file).or_default().push(path.clone());
+        }
+    }
+    applicable
+}
+
+/// Finds the nearest rule file below the repository root, caching lookups per directory
+struct NearestRuleFile<'a> {
+    workdir: &'a Path,
+    cache: HashMap<PathBuf, Option<PathBuf>>,
+}
+
+impl NearestRuleFile<'_> {
+    fn find(&mut self, path: &Path) -> Option<PathBuf> {
+        let dir = path.parent()?;
+        if dir == self.workdir || !dir.starts_with(self.workdir) {
+            return None;
+        }
+        if let Some(found) = self.cache.get(dir) {
+            return found.clone();
+        }
+
+        let found = discover_rule_file(dir).or_else(|| self.find(dir));
+        self.cache.insert(dir.to_path_buf(), found.clone());
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_nearest_rule_file() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("frontend/src/components")).unwrap();
+        fs::create_dir_all(root.join("backend/api")).unwrap();
+        fs::create_dir_all(root.join("docs")).unwrap();
+        fs::write(root.join("frontend/.commit-msg-rule.toml"), "").unwrap();
+        fs::write(
+            root.join("backend/Cargo.toml"),
+            "[package.metadata.gitru.header.type]\nallowed_types = [\"fix\"]\n",
+        )
+        .unwrap();
+        let root_rule = root.join(".commit-msg-rule.toml");
+
+        let staged: Vec<String> = [
+            "frontend/src/components/button.ts",
+            "frontend/package.json",
+            "backend/api/main.rs",
+            "docs/readme.md",
+            "Makefile",
+        ]
+        .iter()
+        .map(|p| p.to_string())
+        .collect();
+
+        let applicable = applicable_rule_files(root, Some(&root_rule), &staged);
+        assert_eq!(
+            applicable[&root.join("frontend/.commit-msg-rule.toml")],
+            ["frontend/src/components/button.ts", "frontend/package.json"]
+        );
+        assert_eq!(
+            applicable[&root.join("backend/Cargo.toml")],
+            ["backend/api/main.rs"]
+        );
+        assert_eq!(applicable[&root_rule], ["docs/readme.md", "Makefile"]);
+
+        // without a root rule file, files outside subdirectory rule files are not covered
+        let applicable = applicable_rule_files(root, None, &staged);
+        assert_eq!(applicable.len(), 2);
+    }
+}
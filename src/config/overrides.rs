@@ -0,0 +1,261 @@
+//! Environment-variable and git-config overrides for validation behavior
+//!
+//! These let CI, rebase scripts and individual developers adjust behavior
+//! without editing the committed rule file.
+//!
+//! | Setting   | Environment variable | git config key   | Values                  |
+//! |-----------|----------------------|------------------|-------------------------|
+//! | skip      | `GITRU_SKIP`         | `gitru.skip`     | bool (`1`/`0`, `true`…) |
+//! | rule file | `GITRU_RULE`         | `gitru.ruleFile` | path                    |
+//! | format    | `GITRU_FORMAT`       | `gitru.format`   | `text` / `json`         |
+//! | strict    | `GITRU_STRICT`       | `gitru.strict`   | bool                    |
+//!
+//! Precedence (highest first):
+//! 1. command-line arguments (e.g. `--rule`, `--strict`)
+//! 2. `GITRU_*` environment variables
+//! 3. `git config gitru.*` (local, then global, then system, as resolved by git)
+//! 4. `[preferences]` of the user config (format only), see [`crate::config::user`]
+//! 5. the rule file itself (`global.enable_validation`)
+
+use crate::config::user::UserConfig;
+use crate::error::config_error::ConfigError;
+use crate::util::git_log::run_git_checked;
+use serde::Deserialize;
+use std::fmt;
+use std::path::PathBuf;
+
+/// Output format of hook results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OutputFormat::Text => write!(f, "text"),
+            OutputFormat::Json => write!(f, "json"),
+        }
+    }
+}
+
+/// Where an override value comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OverrideSource {
+    Env(&'static str),
+    GitConfig(&'static str),
+    /// A key of the user config
+    UserConfig(&'static str),
+}
+
+impl OverrideSource {
+    /// The environment variable or config key, e.g. `GITRU_SKIP`
+    pub fn key(&self) -> &'static str {
+        match self {
+            OverrideSource::Env(key)
+            | OverrideSource::GitConfig(key)
+            | OverrideSource::UserConfig(key) => key,
+        }
+    }
+}
+
+impl fmt::Display for OverrideSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OverrideSource::Env(name) => write!(f, "environment variable {}", name),
+            OverrideSource::GitConfig(key) => write!(f, "git config {}", key),
+            OverrideSource::UserConfig(key) => write!(f, "user config {}", key),
+        }
+    }
+}
+
+/// A value together with the place it was read from
+#[derive(Debug, Clone, PartialEq)]
+pub struct Override<T> {
+    pub value: T,
+    pub source: OverrideSource,
+}
+
+/// All overrides read by the commit-msg runner
+#[derive(Debug, Clone, Default)]
+pub struct RunOverrides {
+    pub skip: Option<Override<bool>>,
+    pub rule_file: Option<Override<PathBuf>>,
+    pub format: Option<Override<OutputFormat>>,
+    pub strict: Option<Override<bool>>,
+}
+
+impl RunOverrides {
+    /// Read overrides from the process environment and `git config`, then the preferences
+    /// of the user config.
+    pub fn load(user: Option<&UserConfig>) -> Result<Self, ConfigError> {
+        Ok(Self::resolve(|name| std::env::var(name).ok(), git_config_get)?.or_user_config(user))
+    }
+
+    /// Fill in settings that are not overridden from the preferences of the user config
+    pub fn or_user_config(mut self, user: Option<&UserConfig>) -> Self {
+        let preferences = user.map(|u| &u.preferences);
+        if self.format.is_none()
+            && let Some(format) = preferences.and_then(|p| p.format)
+        {
+            self.format = Some(Override {
+                value: format,
+                source: OverrideSource::UserConfig("preferences.format"),
+            });
+        }
+        self
+    }
+
+    /// Resolve overrides from the given lookups, environment first.
+    pub fn resolve(
+        env: impl Fn(&str) -> Option<String>,
+        git_config: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self, ConfigError> {
+        let lookup = |env_name: &'static str, git_key: &'static str| {
+            if let Some(v) = env(env_name).filter(|v| !v.is_empty()) {
+                return Some((v, OverrideSource::Env(env_name)));
+            }
+            git_config(git_key)
+                .filter(|v| !v.is_empty())
+                .map(|v| (v, OverrideSource::GitConfig(git_key)))
+        };
+
+        let skip = lookup("GITRU_SKIP", "gitru.skip")
+            .map(|(v, source)| parse_bool(&v, &source).map(|value| Override { value, source }))
+            .transpose()?;
+
+        let rule_file = lookup("GITRU_RULE", "gitru.ruleFile").map(|(v, source)| Override {
+            value: PathBuf::from(v),
+            source,
+        });
+
+        let format = lookup("GITRU_FORMAT", "gitru.format")
+            .map(|(v, source)| parse_format(&v, &source).map(|value| Override { value, source }))
+            .transpose()?;
+
+        let strict = lookup("GITRU_STRICT", "gitru.strict")
+            .map(|(v, source)| parse_bool(&v, &source).map(|value| Override { value, source }))
+            .transpose()?;
+
+        Ok(Self {
+            skip,
+            rule_file,
+            format,
+            strict,
+        })
+    }
+
+    /// Whether validation is skipped by an override
+    pub fn is_skipped(&self) -> bool {
+        self.skip.as_ref().is_some_and(|s| s.value)
+    }
+
+    /// Whether warnings are treated as errors.
+    /// The `--strict` command-line flag takes precedence over the override.
+    pub fn strict_or(&self, cli_strict: bool) -> bool {
+        cli_strict || self.strict.as_ref().is_some_and(|s| s.value)
+    }
+
+    /// Effective output format, text by default
+    pub fn output_format(&self) -> OutputFormat {
+        self.format.as_ref().map(|f| f.value).unwrap_or_default()
+    }
+}
+
+/// Read a single `git config` value. Missing keys and git failures both yield `None`.
+fn git_config_get(key: &str) -> Option<String> {
+    // `--type=path` expands `~/` for the rule file; other keys are normalized by our own parsers
+    let args: &[&str] = if key == "gitru.ruleFile" {
+        &["config", "--type=path", "--get", key]
+    } else {
+        &["config", "--get", key]
+    };
+
+    run_git_checked(args).ok().map(|v| v.trim().to_string())
+}
+
+/// Parse a boolean the way git does: `1/true/yes/on` and `0/false/no/off`.
+fn parse_bool(value: &str, source: &OverrideSource) -> Result<bool, ConfigError> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Ok(true),
+        "0" | "false" | "no" | "off" => Ok(false),
+        _ => Err(ConfigError::InvalidOverride {
+            source_name: source.to_string(),
+            value: value.to_string(),
+            expected: "a boolean (1/0, true/false, yes/no, on/off)".to_string(),
+        }),
+    }
+}
+
+fn parse_format(value: &str, source: &OverrideSource) -> Result<OutputFormat, ConfigError> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "text" => Ok(OutputFormat::Text),
+        "json" => Ok(OutputFormat::Json),
+        _ => Err(ConfigError::InvalidOverride {
+            source_name: source.to_string(),
+            value: value.to_string(),
+            expected: "`text` or `json`".to_string(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn lookup(map: HashMap<&'static str, &'static str>) -> impl Fn(&str) -> Option<String> {
+        move |k| map.get(k).map(|v| v.to_string())
+    }
+
+    #[test]
+    fn test_env_takes_precedence_over_git_config() {
+        let env = lookup(HashMap::from([
+            ("GITRU_SKIP", "0"),
+            ("GITRU_FORMAT", "json"),
+        ]));
+        let git = lookup(HashMap::from([
+            ("gitru.skip", "true"),
+            ("gitru.strict", "yes"),
+            ("gitru.ruleFile", "/tmp/rule.toml"),
+        ]));
+
+        let overrides = RunOverrides::resolve(env, git).unwrap();
+
+        assert!(!overrides.is_skipped());
+        assert_eq!(
+            overrides.skip.as_ref().unwrap().source,
+            OverrideSource::Env("GITRU_SKIP")
+        );
+        assert!(overrides.strict_or(false));
+        assert!(overrides.strict_or(true));
+        assert_eq!(overrides.output_format(), OutputFormat::Json);
+        assert_eq!(
+            overrides.rule_file.unwrap().source,
+            OverrideSource::GitConfig("gitru.ruleFile")
+        );
+    }
+
+    #[test]
+    fn test_cli_flag_takes_precedence() {
+        let env = lookup(HashMap::from([("GITRU_STRICT", "0")]));
+        let overrides = RunOverrides::resolve(env, lookup(HashMap::new())).unwrap();
+
+        assert!(!overrides.strict_or(false));
+        assert!(overrides.strict_or(true));
+    }
+
+    #[test]
+    fn test_invalid_override_value() {
+        let env = lookup(HashMap::from([("GITRU_FORMAT", "yaml")]));
+        let git = lookup(HashMap::new());
+
+        assert!(matches!(
+            RunOverrides::resolve(env, git),
+            Err(ConfigError::InvalidOverride { .. })
+        ));
+    }
+}
@@ -0,0 +1,107 @@
+//! Rule presets embedded in the binary.
+//!
+//! A preset can be referenced from a rule file as `extends = ["preset:<name>"]`.
+
+use crate::constant::{
+    COMMIT_MSG_RULE_PRESET_ANGULAR, COMMIT_MSG_RULE_PRESET_GITMOJI, COMMIT_MSG_RULE_PRESET_KERNEL,
+    COMMIT_MSG_RULE_PRESET_MINIMAL, COMMIT_MSG_RULE_TEMPLATE,
+};
+
+/// Prefix of preset references in `global.extends`
+pub const PRESET_PREFIX: &str = "preset:";
+
+/// Preset written by `gitru init commit-msg` without `--preset`
+pub const DEFAULT_PRESET: &str = "conventional";
+
+/// Embedded presets as (name, rule file content)
+pub const PRESETS: &[(&str, &str)] = &[
+    // Conventional Commits, the default template
+    ("conventional", COMMIT_MSG_RULE_TEMPLATE),
+    // Angular type list, scope required
+    ("angular", COMMIT_MSG_RULE_PRESET_ANGULAR),
+    // Emoji types, e.g. `✨(parser): add footer parsing`
+    ("gitmoji", COMMIT_MSG_RULE_PRESET_GITMOJI),
+    // Linux kernel `subsystem: subject`, `Signed-off-by` required
+    ("kernel", COMMIT_MSG_RULE_PRESET_KERNEL),
+    // Subject length only
+    ("minimal", COMMIT_MSG_RULE_PRESET_MINIMAL),
+];
+
+/// Content of the preset with the given name
+pub fn preset(name: &str) -> Option<&'static str> {
+    PRESETS
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, content)| *content)
+}
+
+/// Names of all embedded presets
+pub fn preset_names() -> Vec<&'static str> {
+    PRESETS.iter().map(|(name, _)| *name).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::commit_msg_rule::parse_commit_msg_rule;
+    use crate::error::commit_msg_error::CommitMsgError;
+    use crate::error::footer_error::FooterError;
+    use crate::validator::commit_msg::{ValidationMode, validate_raw_commit_msg};
+
+    fn validate(preset_name: &str, msg: &str) -> Result<(), CommitMsgError> {
+        let rule = parse_commit_msg_rule(preset(preset_name).unwrap()).unwrap();
+        validate_raw_commit_msg(msg, &rule, ValidationMode::Hook).map(|_| ())
+    }
+
+    #[test]
+    fn test_all_presets_parse() {
+        for name in preset_names() {
+            assert!(
+                parse_commit_msg_rule(preset(name).unwrap()).is_ok(),
+                "preset `{}` should parse",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_preset_messages() {
+        assert!(validate("angular", "perf(core): cache compiled templates").is_ok());
+        assert!(validate("angular", "perf: cache compiled templates").is_err());
+
+        assert!(validate("gitmoji", "✨(parser): support multi-line footers").is_ok());
+        assert!(validate("gitmoji", "feat: support multi-line footers").is_err());
+
+        assert!(validate("minimal", "anything: goes here.").is_ok());
+
+        let signed = "net/ipv4: fix use-after-free in tcp_close\n\nThe socket was freed before the timer.\n\nSigned-off-by: Random J Developer <random@developer.example.org>";
+        assert!(validate("kernel", signed).is_ok());
+
+        let unsigned =
+            "net/ipv4: fix use-after-free in tcp_close\n\nThe socket was freed before the timer.";
+        assert!(matches!(
+            validate("kernel", unsigned),
+            Err(CommitMsgError::Footer(
+                FooterError::MissingRequiredKeyword { .. }
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_extends_preset() {
+        let rule = parse_commit_msg_rule(
+            r#"
+[global]
+extends = ["preset:angular"]
+
+[header.scope]
+allowed_scopes = { append = ["cli"] }
+"#,
+        )
+        .unwrap();
+
+        let scopes = rule.header.scope.unwrap().allowed_scopes.unwrap();
+        assert!(scopes.contains(&"cli".to_string()));
+        assert!(scopes.contains(&"core".to_string()));
+    }
+}
@@ -0,0 +1,416 @@
+//! JSON Schema of the rule file, generated from the structs in
+//! [`crate::config::commit_msg_rule`].
+//!
+//! `gitru config schema` prints it for editor completion and validation, and
+//! [`RULE_FIELDS`] reads it back to report unknown keys and values of the wrong type with
+//! their location before the rule is deserialized. Descriptions are the doc comments of the
+//! fields, defaults come from the `Default` impls.
+
+use crate::config::commit_msg_rule::{ParsedCommitMsgRule, RuleCheck, Severity};
+use crate::config::extends::LIST_OPERATIONS;
+use schemars::generate::SchemaSettings;
+use schemars::transform::RecursiveTransform;
+use schemars::{Schema, SchemaGenerator};
+use serde_json::{Map, Value, json};
+use std::sync::LazyLock;
+
+/// Where editors fetch the JSON Schema from, see the `#:schema` directive of the rule templates
+pub const SCHEMA_URL: &str =
+    "https://raw.githubusercontent.com/xiyixiaodao/gitru/main/schema/commit-msg-rule.schema.json";
+
+/// Schema keyword naming the dotted key that replaces a deprecated key,
+/// set with `#[schemars(extend("x-replaced-by" = "..."))]`
+const REPLACED_BY: &str = "x-replaced-by";
+
+/// Keys of the rule file, read from the generated schema
+pub static RULE_FIELDS: LazyLock<Vec<Field>> = LazyLock::new(|| fields(&rule_schema()));
+
+/// Type of a rule file value
+#[derive(Debug, Clone)]
+pub enum FieldKind {
+    Bool,
+    /// Non-negative integer
+    Integer,
+    Float,
+    String,
+    /// List of strings, or a list operation such as `{ append = [...] }`
+    StringList,
+    /// One of the given strings
+    Enum(Vec<String>),
+    Table(Vec<Field>),
+}
+
+impl FieldKind {
+    /// Human-readable name used in diagnostics
+    pub fn describe(&self) -> String {
+        match self {
+            FieldKind::Bool => "a boolean".to_string(),
+            FieldKind::Integer => "a non-negative integer".to_string(),
+            FieldKind::Float => "a float, e.g. `0.8`".to_string(),
+            FieldKind::String => "a string".to_string(),
+            FieldKind::StringList => "a list of strings".to_string(),
+            FieldKind::Enum(values) => format!("one of {}", quote_list(values)),
+            FieldKind::Table(_) => "a table".to_string(),
+        }
+    }
+
+    fn of(schema: &Value) -> Self {
+        if schema.get("properties").is_some() {
+            return FieldKind::Table(fields(schema));
+        }
+        // see `string_list`
+        if schema.get("anyOf").is_some() {
+            return FieldKind::StringList;
+        }
+        let values = enum_values(schema);
+        if !values.is_empty() {
+            return FieldKind::Enum(values);
+        }
+        match schema.get("type").and_then(Value::as_str) {
+            Some("boolean") => FieldKind::Bool,
+            Some("integer") => FieldKind::Integer,
+            Some("number") => FieldKind::Float,
+            _ => FieldKind::String,
+        }
+    }
+}
+
+/// A key of the rule file
+#[derive(Debug, Clone)]
+pub struct Field {
+    pub name: String,
+    pub kind: FieldKind,
+    /// Dotted key that replaces this deprecated key, see [`crate::config::migrate`]
+    pub replaced_by: Option<String>,
+}
+
+/// Fields of an object schema
+fn fields(schema: &Value) -> Vec<Field> {
+    let Some(Value::Object(properties)) = schema.get("properties") else {
+        return Vec::new();
+    };
+    properties
+        .iter()
+        .map(|(name, schema)| Field {
+            name: name.clone(),
+            kind: FieldKind::of(schema),
+            replaced_by: schema
+                .get(REPLACED_BY)
+                .and_then(Value::as_str)
+                .map(str::to_string),
+        })
+        .collect()
+}
+
+/// Values of a string enum schema, given as `enum` or as `oneOf` documented constants
+fn enum_values(schema: &Value) -> Vec<String> {
+    if let Some(Value::Array(values)) = schema.get("enum") {
+        return values
+            .iter()
+            .filter_map(|v| v.as_str().map(str::to_string))
+            .collect();
+    }
+    match schema.get("oneOf") {
+        Some(Value::Array(variants)) => variants.iter().flat_map(enum_values).collect(),
+        _ => schema
+            .get("const")
+            .and_then(Value::as_str)
+            .map(|v| vec![v.to_string()])
+            .unwrap_or_default(),
+    }
+}
+
+/// Schema of a list of strings, which extending rule files may also edit with a list operation
+pub fn string_list(_: &mut SchemaGenerator) -> Schema {
+    let list = json!({ "type": "array", "items": { "type": "string" } });
+    let operations: Map<String, Value> = LIST_OPERATIONS
+        .iter()
+        .map(|op| (op.to_string(), list.clone()))
+        .collect();
+    schemars::json_schema!({
+        "anyOf": [
+            list,
+            { "type": "object", "properties": operations, "additionalProperties": false },
+        ]
+    })
+}
+
+/// Schema of `[severity]`: a table keyed by [`RuleCheck`] holding a [`Severity`]
+pub fn severity_table(generator: &mut SchemaGenerator) -> Schema {
+    let severity = generator.subschema_for::<Severity>().to_value();
+    let properties: Map<String, Value> =
+        enum_values(generator.subschema_for::<RuleCheck>().as_value())
+            .into_iter()
+            .map(|check| (check, severity.clone()))
+            .collect();
+    schemars::json_schema!({
+        "type": "object",
+        "properties": properties,
+        "additionalProperties": false,
+    })
+}
+
+/// Schema as derived from the rule structs
+fn rule_schema() -> Value {
+    let mut settings = SchemaSettings::draft07();
+    settings.inline_subschemas = true;
+    settings
+        .transforms
+        .push(Box::new(RecursiveTransform(forbid_null)));
+    settings
+        .transforms
+        .push(Box::new(RecursiveTransform(unwrap_description)));
+    settings
+        .into_generator()
+        .into_root_schema_for::<ParsedCommitMsgRule>()
+        .to_value()
+}
+
+/// TOML has no null, an unset `Option` field is left out instead
+fn forbid_null(schema: &mut Schema) {
+    let Some(map) = schema.as_object_mut() else {
+        return;
+    };
+    if let Some(Value::Array(types)) = map.get_mut("type") {
+        types.retain(|t| t != "null");
+        if let [single] = types.as_slice() {
+            let single = single.clone();
+            map.insert("type".to_string(), single);
+        }
+    }
+    if let Some(Value::Array(values)) = map.get_mut("enum") {
+        values.retain(|v| !v.is_null());
+    }
+    if let Some(Value::Array(variants)) = map.get_mut("anyOf") {
+        variants.retain(|v| v.get("type") != Some(&json!("null")));
+        if let [Value::Object(inner)] = variants.as_slice() {
+            let inner = inner.clone();
+            map.remove("anyOf");
+            for (key, value) in inner {
+                map.entry(key).or_insert(value);
+            }
+        }
+    }
+}
+
+/// Doc comments are wrapped, editors show descriptions as a single paragraph
+fn unwrap_description(schema: &mut Schema) {
+    if let Some(Value::String(description)) = schema.get("description") {
+        let unwrapped = description.replace('\n', " ");
+        schema.insert("description".to_string(), json!(unwrapped));
+    }
+}
+
+/// Deprecated keys of the rule file as (dotted key, replacement)
+pub fn deprecated_keys() -> Vec<(String, String)> {
+    fn collect(fields: &[Field], prefix: &str, keys: &mut Vec<(String, String)>) {
+        for field in fields {
+            let key = if prefix.is_empty() {
+                field.name.clone()
+            } else {
+                format!("{}.{}", prefix, field.name)
+            };
+            if let Some(replacement) = &field.replaced_by {
+                keys.push((key.clone(), replacement.clone()));
+            }
+            if let FieldKind::Table(fields) = &field.kind {
+                collect(fields, &key, keys);
+            }
+        }
+    }
+
+    let mut keys = Vec::new();
+    collect(&RULE_FIELDS, "", &mut keys);
+    keys
+}
+
+/// JSON Schema (draft 7) of the rule file
+pub fn json_schema() -> Value {
+    let mut schema = rule_schema();
+    publish(&mut schema);
+    if let Value::Object(map) = &mut schema {
+        map.insert("$id".to_string(), json!(SCHEMA_URL));
+        map.insert("title".to_string(), json!("gitru commit-msg rule file"));
+    }
+    schema
+}
+
+/// Adapt the generated schema for editors: drop the defaults of tables, which list the
+/// defaults of their keys instead, and spell out deprecations
+fn publish(schema: &mut Value) {
+    let Some(Value::Object(properties)) = schema.get_mut("properties") else {
+        return;
+    };
+    for property in properties.values_mut() {
+        let is_table = property.get("properties").is_some();
+        let Value::Object(map) = property else {
+            continue;
+        };
+        if is_table || map.get("default").is_some_and(Value::is_null) {
+            map.remove("default");
+        }
+        if let Some(Value::String(replacement)) = map.remove(REPLACED_BY) {
+            // `deprecated` is draft 2019-09, `deprecationMessage` is understood by VS Code
+            map.insert("deprecated".to_string(), json!(true));
+            map.insert(
+                "deprecationMessage".to_string(),
+                json!(format!("Deprecated, use `{}` instead.", replacement)),
+            );
+        }
+        publish(property);
+    }
+}
+
+/// `a`, `b` and `c` → "`a`, `b`, `c`"
+fn quote_list(values: &[String]) -> String {
+    values
+        .iter()
+        .map(|v| format!("`{}`", v))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::migrate::RULE_VERSION;
+    use crate::config::preset::PRESETS;
+    use crate::constant::COMMIT_MSG_RULE_TEMPLATE;
+    use std::collections::BTreeSet;
+
+    fn field<'a>(fields: &'a [Field], name: &str) -> &'a Field {
+        fields.iter().find(|f| f.name == name).unwrap()
+    }
+
+    #[test]
+    fn test_field_kinds() {
+        let FieldKind::Table(severity) = &field(&RULE_FIELDS, "severity").kind else {
+            panic!("`severity` is not a table");
+        };
+        assert_eq!(severity.len(), 15);
+        assert!(matches!(
+            &field(severity, "footer_keyword_typo").kind,
+            FieldKind::Enum(values) if values == &["error", "warning", "off"]
+        ));
+
+        let FieldKind::Table(global) = &field(&RULE_FIELDS, "global").kind else {
+            panic!("`global` is not a table");
+        };
+        assert!(matches!(
+            field(global, "extends").kind,
+            FieldKind::StringList
+        ));
+        assert!(matches!(
+            field(global, "enable_validation").kind,
+            FieldKind::Bool
+        ));
+        assert!(matches!(field(global, "version").kind, FieldKind::String));
+        assert!(matches!(
+            &field(global, "nested_rules").kind,
+            FieldKind::Enum(values) if values == &["all", "any", "off"]
+        ));
+
+        let FieldKind::Table(spellcheck) = &field(&RULE_FIELDS, "spellcheck").kind else {
+            panic!("`spellcheck` is not a table");
+        };
+        assert!(matches!(
+            field(spellcheck, "max_suggestions").kind,
+            FieldKind::Integer
+        ));
+        assert!(matches!(
+            field(spellcheck, "type_threshold").kind,
+            FieldKind::Float
+        ));
+    }
+
+    /// Dotted keys of `fields`, deprecated keys excluded
+    fn schema_keys(fields: &[Field], prefix: &str, keys: &mut BTreeSet<String>) {
+        for field in fields.iter().filter(|f| f.replaced_by.is_none()) {
+            let key = format!("{}{}", prefix, field.name);
+            if let FieldKind::Table(fields) = &field.kind {
+                schema_keys(fields, &format!("{}.", key), keys);
+            }
+            keys.insert(key);
+        }
+    }
+
+    /// Dotted keys of a rule file, list operations such as `{ append = [...] }` are values
+    fn toml_keys(table: &toml::Table, prefix: &str, keys: &mut BTreeSet<String>) {
+        for (name, value) in table {
+            let key = format!("{}{}", prefix, name);
+            if let Some(table) = value.as_table()
+                && !table.keys().all(|k| LIST_OPERATIONS.contains(&k.as_str()))
+            {
+                toml_keys(table, &format!("{}.", key), keys);
+            }
+            keys.insert(key);
+        }
+    }
+
+    #[test]
+    fn test_schema_matches_templates() {
+        let mut schema = BTreeSet::new();
+        schema_keys(&RULE_FIELDS, "", &mut schema);
+
+        for (name, content) in PRESETS {
+            let mut keys = BTreeSet::new();
+            toml_keys(&toml::from_str(content).unwrap(), "", &mut keys);
+            let unknown: Vec<_> = keys.difference(&schema).collect();
+            assert!(
+                unknown.is_empty(),
+                "preset `{}` keys missing from the schema: {:?}",
+                name,
+                unknown
+            );
+        }
+
+        // the full template lists every key, optional ones as `# key = value` examples
+        let uncommented: String = COMMIT_MSG_RULE_TEMPLATE
+            .lines()
+            .map(|line| match line.strip_prefix("# ") {
+                Some(example)
+                    if example.starts_with(|c: char| c.is_ascii_lowercase())
+                        && example.contains(" = ") =>
+                {
+                    example
+                }
+                _ => line,
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let mut template = BTreeSet::new();
+        toml_keys(&toml::from_str(&uncommented).unwrap(), "", &mut template);
+        let undocumented: Vec<_> = schema.difference(&template).collect();
+        assert!(
+            undocumented.is_empty(),
+            "schema keys missing from the template: {:?}",
+            undocumented
+        );
+    }
+
+    #[test]
+    fn test_deprecated_keys() {
+        assert_eq!(
+            deprecated_keys(),
+            [(
+                "footer.start_key_words_spellcheck.threshold".to_string(),
+                "spellcheck.footer_threshold".to_string()
+            )]
+        );
+        assert_eq!(
+            json_schema()["properties"]["global"]["properties"]["version"]["default"],
+            json!(RULE_VERSION)
+        );
+    }
+
+    #[test]
+    fn test_published_schema_is_up_to_date() {
+        let generated = serde_json::to_string_pretty(&json_schema()).unwrap() + "\n";
+        let published = include_str!("../../schema/commit-msg-rule.schema.json");
+
+        assert!(
+            generated == published,
+            "schema/commit-msg-rule.schema.json is outdated, regenerate it with `gitru config schema`"
+        );
+    }
+}
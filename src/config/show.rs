@@ -0,0 +1,133 @@
+//! The effective rule after defaults and `extends` are applied, with the source of each
+//! value, as printed by `gitru config show`.
+
+use crate::config::commit_msg_rule::ParsedCommitMsgRule;
+use crate::error::config_error::ConfigError;
+use std::collections::BTreeMap;
+use toml::{Table, Value};
+
+/// Source label of values that no rule file sets
+pub const DEFAULT_SOURCE: &str = "default";
+
+/// The effective rule as a table. Sections that are not configured (and therefore not
+/// validated), such as a missing `[body]`, are left out.
+pub fn effective_table(rule: &ParsedCommitMsgRule) -> Result<Table, ConfigError> {
+    Table::try_from(rule).map_err(|e| ConfigError::InvalidRuleFile {
+        source_name: "<effective rule>".to_string(),
+        reason: e.to_string(),
+    })
+}
+
+/// Source of every effective value, keyed by dotted path: the rule files that set it,
+/// or [`DEFAULT_SOURCE`]
+pub fn value_sources(rule: &ParsedCommitMsgRule, table: &Table) -> BTreeMap<String, Vec<String>> {
+    let mut sources = BTreeMap::new();
+    collect_sources(rule, table, "", &mut sources);
+    sources
+}
+
+fn collect_sources(
+    rule: &ParsedCommitMsgRule,
+    table: &Table,
+    prefix: &str,
+    sources: &mut BTreeMap<String, Vec<String>>,
+) {
+    for (key, value) in table {
+        let path = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+
+        match value {
+            Value::Table(table) => collect_sources(rule, table, &path, sources),
+            _ => {
+                let source = rule
+                    .sources
+                    .source_of(&path)
+                    .map(<[String]>::to_vec)
+                    .unwrap_or_else(|| vec![DEFAULT_SOURCE.to_string()]);
+                sources.insert(path, source);
+            }
+        }
+    }
+}
+
+/// The effective rule as TOML, each value followed by a `# <source>` comment
+pub fn annotated_toml(rule: &ParsedCommitMsgRule) -> Result<String, ConfigError> {
+    let table = effective_table(rule)?;
+    let sources = value_sources(rule, &table);
+
+    let invalid = |reason: String| ConfigError::InvalidRuleFile {
+        source_name: "<effective rule>".to_string(),
+        reason,
+    };
+    let content = toml::to_string(&table).map_err(|e| invalid(e.to_string()))?;
+    let mut doc: toml_edit::DocumentMut = content
+        .parse()
+        .map_err(|e: toml_edit::TomlError| invalid(e.to_string()))?;
+
+    annotate(doc.as_table_mut(), "", &sources);
+    Ok(doc.to_string())
+}
+
+fn annotate(table: &mut toml_edit::Table, prefix: &str, sources: &BTreeMap<String, Vec<String>>) {
+    for (key, item) in table.iter_mut() {
+        let path = if prefix.is_empty() {
+            key.get().to_string()
+        } else {
+            format!("{}.{}", prefix, key.get())
+        };
+
+        match item {
+            toml_edit::Item::Table(table) => annotate(table, &path, sources),
+            toml_edit::Item::Value(value) => {
+                if let Some(source) = sources.get(&path) {
+                    value
+                        .decor_mut()
+                        .set_suffix(format!("  # {}", source.join(" + ")));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::commit_msg_rule::load_commit_msg_rule;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_value_sources() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(".commit-msg-rule.toml");
+        fs::write(
+            &path,
+            "[global]\nextends = [\"preset:minimal\"]\n\n[header.subject]\nmax_length = 50\n\n[footer]\n",
+        )
+        .unwrap();
+
+        let rule = load_commit_msg_rule(&path).unwrap();
+        let table = effective_table(&rule).unwrap();
+        let sources = value_sources(&rule, &table);
+
+        let file = path.display().to_string();
+        assert_eq!(sources["header.subject.max_length"], [file]);
+        assert_eq!(
+            sources["header.subject.min_length"],
+            ["preset:minimal".to_string()]
+        );
+        assert_eq!(
+            sources["footer.max_line_length"],
+            [DEFAULT_SOURCE.to_string()]
+        );
+        assert!(!sources.keys().any(|k| k.starts_with("body.")));
+
+        let annotated = annotated_toml(&rule).unwrap();
+        assert!(annotated.contains(&format!("max_length = 50  # {}\n", path.display())));
+        assert!(annotated.contains("max_line_length = 72  # default\n"));
+    }
+}
@@ -0,0 +1,243 @@
+//! User-level configuration, shared by all repositories of a user
+//!
+//! Read from `$XDG_CONFIG_HOME/gitru/config.toml`, or `~/.config/gitru/config.toml` when
+//! `XDG_CONFIG_HOME` is not set (`%APPDATA%\gitru\config.toml` on Windows).
+//!
+//! ```toml
+//! [preferences]
+//! color = "auto"    # "auto" | "always" | "never"
+//! format = "text"   # "text" | "json", output of the commit-msg hook
+//! locale = "en"
+//! auto_fix = false  # rewrite misspelled types and scopes in the commit message
+//!
+//! # Default rules, in the layout of a rule file
+//! [rules.header.subject]
+//! max_length = 72
+//! ```
+//!
+//! Rule precedence (highest first):
+//! 1. the repository rule file, and the files it extends
+//! 2. `[rules]` of the user config, and the files it extends
+//! 3. built-in defaults
+//!
+//! Without a repository rule file, `[rules]` is used alone. Preferences rank below
+//! environment variables and `git config`, see [`crate::config::overrides`].
+
+use crate::config::discovery::RuleFormat;
+use crate::config::extends::{Layer, load_layers_as};
+use crate::config::overrides::OutputFormat;
+use crate::error::config_error::ConfigError;
+use crate::util::colored_print::ColorChoice;
+use serde::Deserialize;
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+/// Directory of the user config, below the user configuration directory
+const USER_CONFIG_DIR: &str = "gitru";
+
+const USER_CONFIG_FILE_NAME: &str = "config.toml";
+
+/// Table of the user config holding the default rules
+const USER_RULES_SECTION: &[&str] = &["rules"];
+
+/// Personal preferences of the user config
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Preferences {
+    pub color: Option<ColorChoice>,
+    pub format: Option<OutputFormat>,
+    pub locale: Option<String>,
+    pub auto_fix: Option<bool>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct UserConfigFile {
+    #[serde(default)]
+    preferences: Preferences,
+    rules: Option<toml::Table>,
+}
+
+/// The user config, see the module documentation
+#[derive(Debug, Clone)]
+pub struct UserConfig {
+    pub path: PathBuf,
+    pub preferences: Preferences,
+    /// Raw file content, kept for locating keys of `[rules]` in diagnostics
+    content: String,
+    has_rules: bool,
+}
+
+impl UserConfig {
+    /// Read the user config, `None` when there is none
+    pub fn load() -> Result<Option<Self>, ConfigError> {
+        let Some(path) = user_config_path(|name| std::env::var_os(name)) else {
+            return Ok(None);
+        };
+
+        match std::fs::read_to_string(&path) {
+            Ok(content) => Self::parse(content, path).map(Some),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(ConfigError::UserConfigRead { path, source: e }),
+        }
+    }
+
+    /// Parse the content of the user config at `path`
+    pub fn parse(content: String, path: PathBuf) -> Result<Self, ConfigError> {
+        let file: UserConfigFile =
+            toml::from_str(&content).map_err(|e| ConfigError::InvalidRuleFile {
+                source_name: path.display().to_string(),
+                reason: e.to_string(),
+            })?;
+
+        Ok(Self {
+            path,
+            preferences: file.preferences,
+            content,
+            has_rules: file.rules.is_some(),
+        })
+    }
+
+    /// The `extends` chain of `[rules]`, to be applied under a repository rule file.
+    /// Empty without `[rules]`.
+    pub fn rule_layers(&self) -> Result<Vec<Layer>, ConfigError> {
+        if !self.has_rules {
+            return Ok(Vec::new());
+        }
+
+        load_layers_as(
+            &self.content,
+            Some(&self.path),
+            RuleFormat::TomlSection(USER_RULES_SECTION),
+        )
+    }
+
+    /// Whether misspelled types and scopes are fixed in the commit message
+    pub fn auto_fix(&self) -> bool {
+        self.preferences.auto_fix.unwrap_or(false)
+    }
+
+    /// Preferences that have no effect
+    pub fn warnings(&self) -> Vec<String> {
+        match &self.preferences.locale {
+            Some(locale) if !is_english(locale) => vec![format!(
+                "warning: {}: `preferences.locale = \"{}\"` has no effect, messages are only available in English",
+                self.path.display(),
+                locale
+            )],
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// `en`, `en-US`, `en_GB.UTF-8`..., and the POSIX locales
+fn is_english(locale: &str) -> bool {
+    let language = locale.split(['-', '_', '.']).next().unwrap_or_default();
+    matches!(language.to_ascii_lowercase().as_str(), "en" | "c" | "posix")
+}
+
+/// Location of the user config, from the given environment lookup.
+/// A relative `XDG_CONFIG_HOME` is ignored, as required by the XDG base directory spec.
+fn user_config_path(var: impl Fn(&str) -> Option<OsString>) -> Option<PathBuf> {
+    let dir = |name: &str| {
+        var(name)
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+            .filter(|p| p.is_absolute())
+    };
+
+    dir("XDG_CONFIG_HOME")
+        .or_else(|| dir("HOME").map(|home| home.join(".config")))
+        .or_else(|| dir("APPDATA"))
+        .map(|dir| dir.join(USER_CONFIG_DIR).join(USER_CONFIG_FILE_NAME))
+}
+
+/// The rule layers of `user`, empty without a user config
+pub fn user_rule_layers(user: Option<&UserConfig>) -> Result<Vec<Layer>, ConfigError> {
+    user.map_or_else(|| Ok(Vec::new()), UserConfig::rule_layers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::commit_msg_rule::load_commit_msg_rule_with_user;
+    use std::collections::HashMap;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_user_config_path() {
+        let env = |vars: HashMap<&'static str, &'static str>| {
+            move |name: &str| vars.get(name).map(OsString::from)
+        };
+
+        assert_eq!(
+            user_config_path(env(HashMap::from([
+                ("XDG_CONFIG_HOME", "/xdg"),
+                ("HOME", "/home/me")
+            ]))),
+            Some(PathBuf::from("/xdg/gitru/config.toml"))
+        );
+        assert_eq!(
+            user_config_path(env(HashMap::from([
+                ("XDG_CONFIG_HOME", "relative"),
+                ("HOME", "/home/me")
+            ]))),
+            Some(PathBuf::from("/home/me/.config/gitru/config.toml"))
+        );
+        assert_eq!(user_config_path(env(HashMap::new())), None);
+    }
+
+    #[test]
+    fn test_parse_preferences() {
+        let user = UserConfig::parse(
+            "[preferences]\ncolor = \"never\"\nformat = \"json\"\nlocale = \"de_DE.UTF-8\"\n"
+                .to_string(),
+            PathBuf::from("/xdg/gitru/config.toml"),
+        )
+        .unwrap();
+
+        assert_eq!(user.preferences.color, Some(ColorChoice::Never));
+        assert_eq!(user.preferences.format, Some(OutputFormat::Json));
+        assert!(!user.auto_fix());
+        assert_eq!(user.warnings().len(), 1);
+        assert!(user.rule_layers().unwrap().is_empty());
+
+        assert!(
+            UserConfig::parse(
+                "[preferences]\ncolour = \"never\"\n".to_string(),
+                PathBuf::from("config.toml")
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_rules_merge_under_repository_rules() {
+        let dir = TempDir::new().unwrap();
+        let user_path = dir.path().join("config.toml");
+        fs::write(
+            &user_path,
+            "[preferences]\nauto_fix = true\n\n[rules.header.subject]\nmax_length = 50\nmin_length = 5\n",
+        )
+        .unwrap();
+        let user =
+            UserConfig::parse(fs::read_to_string(&user_path).unwrap(), user_path.clone()).unwrap();
+
+        // without a repository rule file, the user rules are used alone
+        let missing = dir.path().join(".commit-msg-rule.toml");
+        let rule = load_commit_msg_rule_with_user(&missing, Some(&user)).unwrap();
+        assert_eq!(rule.header.subject.max_length, 50);
+        assert_eq!(rule.header.subject.min_length, 5);
+
+        // the repository rule file takes precedence
+        fs::write(&missing, "[header.subject]\nmax_length = 72\n").unwrap();
+        let rule = load_commit_msg_rule_with_user(&missing, Some(&user)).unwrap();
+        assert_eq!(rule.header.subject.max_length, 72);
+        assert_eq!(rule.header.subject.min_length, 5);
+        assert_eq!(
+            rule.sources.source_of("header.subject.min_length"),
+            Some(&[user_path.display().to_string()][..])
+        );
+    }
+}
@@ -1,12 +1,19 @@
+use crate::hook::kind::{HookExec, HookKind};
 use std::env;
-use std::sync::LazyLock;
 
 pub const COMMIT_MSG_RULE_TEMPLATE: &str =
     include_str!("../template/commit-msg-rule-template.toml");
-const COMMIT_MSG_HOOK_TEMPLATE_RAW: &str = include_str!("../template/commit-msg-hook-template.sh");
+pub const COMMIT_MSG_RULE_PRESET_ANGULAR: &str = include_str!("../template/presets/angular.toml");
+pub const COMMIT_MSG_RULE_PRESET_GITMOJI: &str = include_str!("../template/presets/gitmoji.toml");
+pub const COMMIT_MSG_RULE_PRESET_KERNEL: &str = include_str!("../template/presets/kernel.toml");
+pub const COMMIT_MSG_RULE_PRESET_MINIMAL: &str = include_str!("../template/presets/minimal.toml");
+const HOOK_TEMPLATE_RAW: &str = include_str!("../template/hook-template.sh");
 
 pub const COMMIT_MSG_RULE_FILE_NAME: &str = ".commit-msg-rule.toml";
 
+/// Trailer recording that validation of a commit was skipped
+pub const SKIP_TRAILER_KEY: &str = "Validation-Skipped";
+
 // fn get_program_path() -> Option<String> {
 //     let exe_path = env::current_exe().ok()?;
 //     let exe_dir = exe_path.parent()?.to_path_buf();
@@ -29,16 +36,32 @@ fn get_program_path() -> Option<String> {
     Some(exe_path.to_string_lossy().into_owned())
 }
 
-pub static COMMIT_MSG_HOOK_CONTENT: LazyLock<String> = LazyLock::new(|| match get_program_path() {
-    Some(program_path) => COMMIT_MSG_HOOK_TEMPLATE_RAW
-        .replace("{{program_exec}}", &program_path)
-        .replace("\r\n", "\n")
-        .replace('\r', ""),
+/// The script of `hook`, chained with `<hook>.local` in the given order (`before` / `after`,
+/// empty for none), see `gitru install --chain`, finding gitru with `exec`, see
+/// `gitru install --exec`. It runs `gitru run <hook>` with the arguments of git.
+pub fn hook_content(hook: HookKind, chain_order: &str, exec: &HookExec) -> String {
+    let program_exec = match exec {
+        HookExec::Absolute => get_program_path().unwrap_or_else(|| "gitru".to_string()),
+        HookExec::Path => "gitru".to_string(),
+        HookExec::Repo(path) => path.clone(),
+        HookExec::Cargo => "cargo".to_string(),
+    };
+
+    let gitru_args = match hook {
+        HookKind::CommitMsg => "commit-msg --msg \"$1\"".to_string(),
+        _ => format!("{} \"$@\"", hook),
+    };
 
-    None => COMMIT_MSG_HOOK_TEMPLATE_RAW
+    HOOK_TEMPLATE_RAW
+        .replace("{{hook_name}}", hook.as_str())
+        .replace("{{gitru_args}}", &gitru_args)
+        .replace("{{reads_stdin}}", &hook.reads_stdin().to_string())
+        .replace("{{program_exec}}", &program_exec)
+        .replace("{{exec_strategy}}", exec.strategy())
+        .replace("{{chain_order}}", chain_order)
         .replace("\r\n", "\n")
-        .replace('\r', ""),
-});
+        .replace('\r', "")
+}
 
 #[cfg(test)]
 mod tests {
@@ -49,7 +72,21 @@ mod tests {
     }
 
     #[test]
-    fn test_get_commit_msg_hook_content() {
-        println!("{}", COMMIT_MSG_HOOK_CONTENT.as_str());
+    fn test_get_hook_content() {
+        let content = hook_content(HookKind::CommitMsg, "after", &HookExec::Absolute);
+        assert!(content.contains("CHAIN_ORDER=\"after\""));
+        assert!(content.contains("EXEC_STRATEGY=\"absolute\""));
+        assert!(content.contains("run_gitru run commit-msg --msg \"$1\""));
+
+        let content = hook_content(
+            HookKind::PrePush,
+            "",
+            &HookExec::Repo(HookExec::DEFAULT_REPO_PATH.to_string()),
+        );
+        assert!(content.contains("VALIDATOR_SCRIPT=\"./tools/gitru\""));
+        assert!(content.contains("LOCAL_HOOK=\"$(dirname \"$0\")/pre-push.local\""));
+        assert!(content.contains("if [ \"true\" = \"true\" ]"));
+        assert!(!content.contains("{{"));
+        println!("{}", content);
     }
 }
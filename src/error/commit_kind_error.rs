@@ -0,0 +1,21 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum CommitKindError {
+    #[error("merge commits are not allowed by configuration: `{header}`")]
+    MergeForbidden { header: String },
+
+    #[error(
+        "revert commit must reference the reverted commit, add a line like `This reverts commit <sha>.`"
+    )]
+    MissingRevertReference,
+
+    #[error("reverted header `{header}` is invalid: {reason}")]
+    InvalidRevertedHeader { header: String, reason: String },
+
+    #[error(
+        "`{marker}` commits are not allowed here: `{header}`\n\
+     help: squash them with `git rebase -i --autosquash` before pushing"
+    )]
+    AutosquashForbidden { marker: String, header: String },
+}
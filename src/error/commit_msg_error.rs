@@ -1,7 +1,10 @@
 use crate::error::body_error::BodyError;
+use crate::error::commit_kind_error::CommitKindError;
+use crate::error::config_error::ConfigError;
 use crate::error::footer_error::FooterError;
 use crate::error::git_error::{ConfigStatusCheckError, GitKindError};
 use crate::error::header_error::HeaderError;
+use crate::error::skip_error::SkipError;
 use std::path::PathBuf;
 
 use thiserror::Error;
@@ -17,6 +20,15 @@ pub enum CommitMsgError {
     #[error("{0}")]
     Footer(#[from] FooterError),
 
+    #[error("{0}")]
+    CommitKind(#[from] CommitKindError),
+
+    #[error("{0}")]
+    Skip(#[from] SkipError),
+
+    #[error("{0}")]
+    Config(#[from] ConfigError),
+
     #[error("{0}")]
     ConfigStatus(#[from] ConfigStatusCheckError),
 
@@ -0,0 +1,119 @@
+use std::fmt;
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// A problem in a rule file, located at a file and line when possible
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigDiagnostic {
+    /// Rule file path, or `preset:<name>`
+    pub file: String,
+    /// 1-based line number
+    pub line: Option<usize>,
+    /// Dotted key, e.g. `header.subject.max_length`
+    pub key: String,
+    pub message: String,
+    pub help: Option<String>,
+}
+
+impl fmt::Display for ConfigDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{}: ", self.file, line)?,
+            None => write!(f, "{}: ", self.file)?,
+        }
+        write!(f, "`{}`: {}", self.key, self.message)?;
+        if let Some(help) = &self.help {
+            write!(f, "\n  help: {}", help)?;
+        }
+        Ok(())
+    }
+}
+
+fn format_diagnostics(diagnostics: &[ConfigDiagnostic]) -> String {
+    diagnostics
+        .iter()
+        .map(|d| d.to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("invalid value `{value}` for {source_name}, expected {expected}")]
+    InvalidOverride {
+        source_name: String,
+        value: String,
+        expected: String,
+    },
+
+    #[error("failed to parse rule file `{source_name}`: {reason}")]
+    InvalidRuleFile { source_name: String, reason: String },
+
+    #[error("invalid rule file:\n{}", format_diagnostics(.0))]
+    Invalid(Vec<ConfigDiagnostic>),
+
+    #[error("`{source_name}` has no `[{section}]` table")]
+    MissingSection {
+        source_name: String,
+        section: String,
+    },
+
+    #[error("cannot migrate `{source_name}`: only TOML rule files can be migrated")]
+    MigrateUnsupported { source_name: String },
+
+    #[error("invalid `global.extends` in `{source_name}`: {reason}")]
+    InvalidExtends { source_name: String, reason: String },
+
+    #[error("failed to read user config `{path}`: {source}")]
+    UserConfigRead {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("failed to read hook manifest `{path}`: {source}")]
+    ManifestRead {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("invalid hook manifest `{path}`: {reason}")]
+    InvalidManifest { path: PathBuf, reason: String },
+
+    #[error("failed to read extended rule file `{path}`: {source}")]
+    ExtendsRead {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("circular `extends` chain: {chain}")]
+    ExtendsCycle { chain: String },
+
+    #[error("unknown preset `{name}`, available presets: {available}")]
+    UnknownPreset { name: String, available: String },
+
+    #[error(
+        "invalid `global.version` `{version}` in `{source_name}`, expected `major.minor.patch`"
+    )]
+    InvalidVersion {
+        source_name: String,
+        version: String,
+    },
+
+    #[error(
+        "`{source_name}` is written for rule format {version}, this gitru supports up to {supported}; upgrade gitru"
+    )]
+    UnsupportedVersion {
+        source_name: String,
+        version: String,
+        supported: String,
+    },
+
+    #[error(
+        "cannot {operation} items of `{key}` in `{source_name}`: the inherited value is not a list"
+    )]
+    InvalidListOperation {
+        key: String,
+        operation: String,
+        source_name: String,
+    },
+}
@@ -1,3 +1,4 @@
+use crate::error::other_suggestions;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -37,18 +38,24 @@ pub enum FooterError {
         actual: usize,
     },
 
+    /// A required footer keyword is missing
+    #[error("footer keyword `{keyword}` is required but missing")]
+    MissingRequiredKeyword { keyword: String },
+
     /// Trailing whitespace in footer line
     #[error("footer line {line_number} contains trailing whitespace")]
     FooterTrailingWhitespace { line_number: usize },
 
     /// Footer keyword appears misspelled
     #[error(
-        "footer keyword appears misspelled:\n  \"{wrong}\" → \"{correct}\"\n  similarity = {similarity:.2} (threshold = {threshold:.2})"
+        "footer keyword appears misspelled:\n  \"{wrong}\" → \"{correct}\"\n  similarity = {similarity:.2} (threshold = {threshold:.2}){}",
+        other_suggestions(.others)
     )]
     FooterKeywordTypoError {
         wrong: String,
         correct: String,
         similarity: f64,
         threshold: f64,
+        others: Vec<String>,
     },
 }
@@ -29,3 +29,12 @@ pub enum GitKindError {
     #[error("invalid path structure")]
     InvalidPath,
 }
+
+#[derive(Error, Debug)]
+pub enum GitLogError {
+    #[error("failed to run git: {0}")]
+    Spawn(#[from] std::io::Error),
+
+    #[error("git {args} failed: {stderr}")]
+    CommandFailed { args: String, stderr: String },
+}
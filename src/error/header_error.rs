@@ -1,3 +1,4 @@
+use crate::error::other_suggestions;
 use thiserror::Error;
 
 #[derive(Debug, Error, PartialEq)]
@@ -51,13 +52,15 @@ Explanation:
 
     #[error(
         "unknown commit type `{wrong}`\n\
-     help: did you mean `{correct}`? (similarity {similarity:.2})\n\
-     note: allowed types: {allowed_types:?}"
+     help: did you mean `{correct}`? (similarity {similarity:.2}){}\n\
+     note: allowed types: {allowed_types:?}",
+        other_suggestions(.others)
     )]
     TypeTypo {
         wrong: String,
         correct: String,
         similarity: f64,
+        others: Vec<String>,
         allowed_types: Vec<String>,
     },
 
@@ -92,6 +95,20 @@ Explanation:
         allowed_scopes: Vec<String>,
     },
 
+    #[error(
+        "unknown scope `{wrong}`\n\
+     help: did you mean `{correct}`? (similarity {similarity:.2}){}\n\
+     note: allowed scopes: {allowed_scopes:?}",
+        other_suggestions(.others)
+    )]
+    ScopeTypo {
+        wrong: String,
+        correct: String,
+        similarity: f64,
+        others: Vec<String>,
+        allowed_scopes: Vec<String>,
+    },
+
     // Subject validation module
     #[error("full-width space after colon is not allowed (use half-width space ' ')")]
     FullWidthSpaceNotAllowed,
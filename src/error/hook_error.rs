@@ -0,0 +1,237 @@
+use crate::error::commit_msg_error::CommitMsgError;
+use crate::error::config_error::ConfigError;
+use crate::error::git_error::{ConfigStatusCheckError, GitKindError, GitLogError};
+use crate::error::skip_error::SkipError;
+use crate::hook::kind::HookKind;
+use std::path::PathBuf;
+use std::process::ExitStatus;
+use thiserror::Error;
+
+/// Errors of the hook lifecycle (init, install, uninstall, status and sync), of hook runs and
+/// of the `lint`, `config` and `doctor` commands
+#[derive(Error, Debug)]
+pub enum HookError {
+    #[error("unknown hook `{name}`{}", unknown_hook_help(suggestion.as_deref()))]
+    UnknownHook {
+        name: String,
+        suggestion: Option<String>,
+    },
+
+    #[error("failed to detect git kind: {0}")]
+    GitKind(#[from] GitKindError),
+
+    #[error("unknown preset `{name}`, available presets: {}", available.join(", "))]
+    UnknownPreset {
+        name: String,
+        available: Vec<&'static str>,
+    },
+
+    #[error(
+        "commit-msg config file already exists at {}. Use -f or --force to write {file_name} anyway.",
+        path.display()
+    )]
+    RuleFileExists {
+        path: PathBuf,
+        file_name: &'static str,
+    },
+
+    #[error(
+        "{hook} hook already exists at {}. Use -f or --force to overwrite, \
+         or --chain before|after to keep it and run it with gitru.",
+        path.display()
+    )]
+    HookExists { hook: HookKind, path: PathBuf },
+
+    #[error("cannot keep the existing {hook} hook: {} already exists", path.display())]
+    LocalHookExists { hook: HookKind, path: PathBuf },
+
+    #[error(
+        "{hook} hook at {} was modified after gitru installed it. \
+         Use -f or --force to overwrite it.",
+        path.display()
+    )]
+    HookModified { hook: HookKind, path: PathBuf },
+
+    #[error(
+        "{hook} hook at {} {reason}. Use -f or --force to remove it anyway.",
+        path.display()
+    )]
+    HookNotRemovable {
+        hook: HookKind,
+        path: PathBuf,
+        reason: &'static str,
+    },
+
+    #[error("unable to {action}: {source}")]
+    Io {
+        action: String,
+        source: std::io::Error,
+    },
+
+    /// The overrides or the hook manifest
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+
+    #[error(transparent)]
+    RuleFile(#[from] CommitMsgError),
+
+    #[error(transparent)]
+    Git(#[from] GitLogError),
+
+    #[error(transparent)]
+    Skip(#[from] SkipError),
+
+    #[error(transparent)]
+    ConfigStatus(#[from] ConfigStatusCheckError),
+
+    #[error("{warning} (strict mode)")]
+    UntrackedRuleFile { warning: String },
+
+    #[error("no rule file found at {}", path.display())]
+    NoRuleFile { path: PathBuf },
+
+    #[error("commit message cannot be empty")]
+    EmptyMessage,
+
+    /// The commit message failed validation, with the report of every rule file
+    #[error("{0}")]
+    MessageRejected(String),
+
+    #[error("unable to record the skip trailer: {0}")]
+    SkipTrailer(GitLogError),
+
+    #[error("{failed} of {total} commit message(s) in `{range}` failed validation")]
+    RangeRejected {
+        failed: usize,
+        total: usize,
+        range: String,
+    },
+
+    #[error("{count} problem(s) found in {name}")]
+    InvalidRuleFile { count: usize, name: String },
+
+    #[error("{count} problem(s) found")]
+    ProblemsFound { count: usize },
+
+    #[error("unable to serialize the {what}: {source}")]
+    Serialize {
+        what: &'static str,
+        source: serde_json::Error,
+    },
+
+    #[error(
+        "no hook manifest at {}, list the hooks to install in it, \
+         e.g. `[hooks] commit-msg = true`",
+        path.display()
+    )]
+    NoManifest { path: PathBuf },
+
+    #[error("{count} hook(s) do not match {}, run `gitru sync`", manifest.display())]
+    NotSynced { count: usize, manifest: PathBuf },
+
+    #[error("{}", sync_failures(.0))]
+    SyncFailed(Vec<(HookKind, HookError)>),
+
+    #[error(
+        "{done} in {succeeded} of {total} repositories, failed in: {}",
+        failed.join(", ")
+    )]
+    RepositoriesFailed {
+        done: String,
+        succeeded: usize,
+        total: usize,
+        failed: Vec<String>,
+    },
+
+    #[error("missing hook name")]
+    MissingHookName,
+
+    #[error("unable to list staged files: {0}")]
+    StagedFiles(GitLogError),
+
+    #[error("invalid glob `{glob}`: {source}")]
+    InvalidGlob {
+        glob: String,
+        source: globset::Error,
+    },
+
+    #[error("{hook}: unable to run {name}: {source}")]
+    CommandSpawn {
+        hook: HookKind,
+        name: String,
+        source: std::io::Error,
+    },
+
+    #[error("{hook}: {name} failed ({status})")]
+    CommandFailed {
+        hook: HookKind,
+        name: String,
+        status: ExitStatus,
+    },
+
+    #[error("{hook}: failed: {}", names.join(", "))]
+    CommandsFailed { hook: HookKind, names: Vec<String> },
+
+    #[error("unexpected pre-push input: `{line}`")]
+    InvalidPushInput { line: String },
+
+    #[error(
+        "push rejected: {} of {total} pushed commit message(s) failed validation: {}\n  {}",
+        shas.len(),
+        shas.join(", "),
+        rebase_hint(rebases)
+    )]
+    PushRejected {
+        shas: Vec<String>,
+        total: usize,
+        /// Where to start the interactive rebase on each rejected ref, as (ref, base)
+        rebases: Vec<(String, String)>,
+    },
+}
+
+impl HookError {
+    /// An I/O error while trying to `action`, e.g. `write commit-msg hook`
+    pub fn io(action: impl Into<String>, source: std::io::Error) -> Self {
+        HookError::Io {
+            action: action.into(),
+            source,
+        }
+    }
+}
+
+fn unknown_hook_help(suggestion: Option<&str>) -> String {
+    match suggestion {
+        Some(suggestion) => format!(", did you mean `{}`?", suggestion),
+        None => {
+            let names: Vec<&str> = HookKind::ALL.iter().map(HookKind::as_str).collect();
+            format!(", available hooks: {}", names.join(", "))
+        }
+    }
+}
+
+fn sync_failures(failures: &[(HookKind, HookError)]) -> String {
+    failures
+        .iter()
+        .map(|(hook, e)| format!("{}: {}", hook, e))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn rebase_hint(rebases: &[(String, String)]) -> String {
+    match rebases {
+        [(_, base)] => format!(
+            "reword them with `git rebase -i {}`, marking them `reword`, then push again",
+            base
+        ),
+        _ => {
+            let commands: Vec<String> = rebases
+                .iter()
+                .map(|(name, base)| format!("\n    {}: `git rebase -i {}`", name, base))
+                .collect();
+            format!(
+                "reword them on each branch, marking them `reword`, then push again:{}",
+                commands.concat()
+            )
+        }
+    }
+}
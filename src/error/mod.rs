@@ -1,5 +1,19 @@
 pub mod body_error;
+pub mod commit_kind_error;
 pub mod commit_msg_error;
+pub mod config_error;
 pub mod footer_error;
 pub mod git_error;
 pub mod header_error;
+pub mod hook_error;
+pub mod skip_error;
+
+/// Extra help line listing further close matches of a misspelled word
+pub(crate) fn other_suggestions(others: &[String]) -> String {
+    if others.is_empty() {
+        return String::new();
+    }
+
+    let list: Vec<String> = others.iter().map(|o| format!("`{}`", o)).collect();
+    format!("\nhelp: other close matches: {}", list.join(", "))
+}
@@ -0,0 +1,26 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum SkipError {
+    #[error("skip keyword matched case-insensitively; validation not skipped")]
+    CaseMismatch,
+
+    #[error("a reason is required to skip validation, e.g. `{word}: hotfix for incident 123`")]
+    ReasonRequired { word: String },
+
+    #[error(
+        "skipping validation is not allowed on branch `{branch}`, allowed branches are {allowed:?}"
+    )]
+    BranchNotAllowed {
+        branch: String,
+        allowed: Vec<String>,
+    },
+
+    #[error(
+        "skipping validation is not allowed for author `{email}`, allowed authors are {allowed:?}"
+    )]
+    AuthorNotAllowed { email: String, allowed: Vec<String> },
+
+    #[error("invalid pattern `{pattern}` in skip_validation: {reason}")]
+    InvalidPattern { pattern: String, reason: String },
+}
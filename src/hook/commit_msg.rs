@@ -1,104 +1,167 @@
-use crate::config::commit_msg_rule::parse_commit_msg_rule;
-use crate::constant::{
-    COMMIT_MSG_HOOK_CONTENT, COMMIT_MSG_RULE_FILE_NAME, COMMIT_MSG_RULE_TEMPLATE,
-};
-use crate::parser::commit_msg::parse_commit_msg;
+use crate::config::commit_msg_rule::load_commit_msg_rule_with_user;
+use crate::config::commit_msg_rule::{NestedRulesPolicy, ParsedCommitMsgRule, SkipValidationRule};
+use crate::config::discovery::{RuleFileOrigin, RuleFormat, discover_rule_file, resolve_rule_file};
+use crate::config::nested::applicable_rule_files;
+use crate::config::overrides::RunOverrides;
+use crate::config::preset::{self, DEFAULT_PRESET};
+use crate::config::user::UserConfig;
+use crate::constant::{COMMIT_MSG_RULE_FILE_NAME, SKIP_TRAILER_KEY};
+use crate::error::git_error::GitKindError;
+use crate::error::hook_error::HookError;
+use crate::hook::pre_push::RangeRuleFiles;
+use crate::parser::commit_kind::CommitKind;
 use crate::parser::get_first_non_empty_line;
 use crate::util::colored_print::{print_error, print_success, print_warning};
-use crate::util::git_path::detect_current_repo;
-use crate::validator::commit_msg::validate_commit_msg;
-use crate::validator::git_status::check_config_status;
+use crate::util::git_log::{list_commits, run_git_checked, staged_changes};
+use crate::util::git_path::{GitKind, detect_current_repo};
+use crate::validator::commit_msg::{ValidationMode, fix_commit_msg, validate_raw_commit_msg};
+use crate::validator::git_status::{ConfigStatus, check_config_status, untracked_config_warning};
+use crate::validator::nested::{rejection_message, validate_with_rule_files};
+use crate::validator::range::{RangeLintReport, short_sha};
+use crate::validator::skip::{
+    SkipContext, SkipRequest, check_skip_policy, match_skip_line, override_skip_request,
+    skip_policy, skip_trailer_value,
+};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+/// Write the rule file of the given preset (`conventional` by default) to the root of `git_kind`.
+pub fn init(git_kind: &GitKind, force: bool, preset: Option<&str>) -> Result<(), HookError> {
+    let preset_name = preset.unwrap_or(DEFAULT_PRESET);
+    let Some(template) = preset::preset(preset_name) else {
+        return Err(HookError::UnknownPreset {
+            name: preset_name.to_string(),
+            available: preset::preset_names(),
+        });
+    };
 
-pub fn init(force: bool) -> Result<(), String> {
-    let git_kind =
-        detect_current_repo().map_err(|e| format!("failed to detect git kind: {}", e))?;
     let path = git_kind.config_path(COMMIT_MSG_RULE_FILE_NAME);
-    if path.exists() && !force {
-        return Err(format!(
-            "commit-msg config file '{}' already exists at {}. Use -f or --force to overwrite.",
-            COMMIT_MSG_RULE_FILE_NAME,
-            path.display()
-        ));
+    if let Some(existing) = discover_rule_file(git_kind.workdir())
+        && !force
+    {
+        return Err(HookError::RuleFileExists {
+            path: existing,
+            file_name: COMMIT_MSG_RULE_FILE_NAME,
+        });
     }
 
-    if let Err(e) = fs::write(&path, COMMIT_MSG_RULE_TEMPLATE) {
-        print_error(&format!("unable to write commit-msg config file: {}", e));
-        std::process::exit(1);
-    }
+    fs::write(&path, template).map_err(|e| HookError::io("write commit-msg config file", e))?;
 
     print_success(&format!(
-        "commit-msg config file '{}' has been initialized at {}",
+        "commit-msg config file '{}' has been initialized at {} (preset: {})",
         COMMIT_MSG_RULE_FILE_NAME,
-        path.display()
+        path.display(),
+        preset_name
     ));
     Ok(())
 }
 
-pub fn install(force: bool) -> Result<(), String> {
-    let git_kind =
-        detect_current_repo().map_err(|e| format!("failed to detect git kind: {}", e))?;
-    let hook_path = git_kind.hook_path("commit-msg");
+/// Result of a commit-msg run that does not block the commit
+#[derive(Debug, Clone, PartialEq)]
+pub enum RunOutcome {
+    /// The message passed validation
+    Passed,
+    /// Validation is turned off by `global.enable_validation = false`
+    Disabled,
+    /// Validation was skipped, with the reason
+    Skipped(String),
+    /// A git-generated commit (merge, revert...) was accepted by the commit_kind policy
+    Accepted(&'static str),
+}
 
-    if hook_path.exists() && !force {
-        print_error(&format!(
-            "commit-msg hook already exists at {}. Use -f or --force to overwrite.",
-            hook_path.display()
-        ));
-        std::process::exit(1);
-    }
+/// Validate the commit message at `msg_path`.
+///
+/// `rule_path` takes precedence over `overrides` (environment variables and git config),
+/// see [`crate::config::overrides`]. Without either, the rule file is discovered in the
+/// repository, see [`crate::config::discovery`]. Non-blocking findings are pushed to `warnings`,
+/// unless `strict` is set, in which case they fail the run.
+pub fn run(
+    msg_path: &PathBuf,
+    rule_path: Option<&PathBuf>,
+    overrides: &RunOverrides,
+    strict: bool,
+    user: Option<&UserConfig>,
+    warnings: &mut Vec<String>,
+) -> Result<RunOutcome, HookError> {
+    // The --rule argument takes precedence over GITRU_RULE / gitru.ruleFile
+    let (rule_path, origin) = resolve_rule_file(rule_path, overrides)?;
+    // Only the repository rule files must be committed; an overridden one may live anywhere
+    let check_status = !matches!(origin, RuleFileOrigin::Override(_));
 
-    if let Err(e) = fs::write(&hook_path, COMMIT_MSG_HOOK_CONTENT.as_str()) {
-        print_error(&format!("unable to write commit-msg hook: {}", e));
-        std::process::exit(1);
-    }
+    // The root rule file controls validation as a whole (enable_validation, skip words,
+    // nested_rules). A monorepo may keep all of its rules in subdirectories instead.
+    let discovered = matches!(origin, RuleFileOrigin::Discovered | RuleFileOrigin::Default);
+    let mut root_rule = if rule_path.exists() || !discovered {
+        Some(load_rule_file(
+            &rule_path,
+            check_status,
+            strict,
+            user,
+            warnings,
+        )?)
+    } else {
+        None
+    };
 
-    #[cfg(target_family = "unix")]
+    // GITRU_SKIP / gitru.skip skip validation under the skip policy of the root rule file,
+    // like the skip words
+    if let Some(skip) = &overrides.skip
+        && let Some(request) = override_skip_request(skip)
     {
-        use std::os::unix::fs::PermissionsExt;
-        fs::set_permissions(&hook_path, fs::Permissions::from_mode(0o755))
-            .map_err(|e| format!("unable to set commit-msg hook permissions: {}", e))?;
+        let policy = skip_policy(root_rule.as_ref().and_then(|r| r.global.as_ref()));
+        check_skip_policy(&request, &policy, &SkipContext::from_git())?;
+        record_skip(msg_path, &request, &policy)?;
+        return Ok(RunOutcome::Skipped(format!("skipped by {}", skip.source)));
     }
 
-    print_success(&format!(
-        "commit-msg hook has been installed at {}",
-        hook_path.display()
-    ));
-    Ok(())
-}
+    let policy = root_rule
+        .as_ref()
+        .and_then(|r| r.global.as_ref())
+        .and_then(|g| g.nested_rules)
+        .unwrap_or_default();
 
-pub fn uninstall() -> Result<(), String> {
-    let git_kind =
-        detect_current_repo().map_err(|e| format!("failed to detect git kind: {}", e))?;
-    let hook_path = git_kind.hook_path("commit-msg");
+    // Rule files in subdirectories, selected by the staged paths
+    let mut root_applies = false;
+    let mut nested = Vec::new();
+    if discovered && policy != NestedRulesPolicy::Off {
+        let git_kind = detect_current_repo()?;
+        let staged = staged_changes().map_err(HookError::StagedFiles)?;
+        let root = root_rule.as_ref().map(|_| rule_path.as_path());
 
-    if hook_path.exists() {
-        if let Err(e) = fs::remove_file(&hook_path) {
-            return Err(format!("unable to remove commit-msg hook: {}", e));
+        for path in applicable_rule_files(git_kind.workdir(), root, &staged).into_keys() {
+            if path == rule_path {
+                root_applies = true;
+            } else {
+                let rule = load_rule_file(&path, check_status, strict, user, warnings)?;
+                nested.push((path, rule));
+            }
         }
-        print_success(&format!(
-            "commit-msg hook has been uninstalled at {}",
-            hook_path.display()
-        ));
-    } else {
-        print_warning("unable to find commit-msg hook");
     }
 
-    Ok(())
-}
+    // Nothing applies: the root rule file is required, report it as missing
+    if root_rule.is_none() && nested.is_empty() {
+        root_rule = Some(load_rule_file(
+            &rule_path,
+            check_status,
+            strict,
+            user,
+            warnings,
+        )?);
+    }
 
-pub fn run(msg_path: &PathBuf, rule_path: &PathBuf) -> Result<(), String> {
-    // Git uses paths relative to the repository root. You should NOT pass an
-    // absolute or full filesystem path; otherwise Git cannot correctly determine
-    // the file's status.
-    let relative_path = COMMIT_MSG_RULE_FILE_NAME;
-    check_config_status(relative_path).map_err(|e| e.to_string())?;
+    let mut targets: Vec<(&Path, &ParsedCommitMsgRule)> = nested
+        .iter()
+        .map(|(path, rule)| (path.as_path(), rule))
+        .collect();
+    if let Some(root) = &root_rule
+        && (root_applies || targets.is_empty())
+    {
+        targets.insert(0, (&rule_path, root));
+    }
 
-    // Parse and validate the commit message rule file
-    let rule_content =
-        fs::read_to_string(rule_path).map_err(|e| format!("cannot read commit message: {}", e))?;
-    let parsed_rule = parse_commit_msg_rule(&rule_content).map_err(|e| e.to_string())?;
+    let Some(parsed_rule) = root_rule.as_ref().or(nested.first().map(|(_, rule)| rule)) else {
+        return Err(HookError::NoRuleFile { path: rule_path });
+    };
 
     // Global validation switch
     let enable_validation = parsed_rule
@@ -109,59 +172,262 @@ pub fn run(msg_path: &PathBuf, rule_path: &PathBuf) -> Result<(), String> {
 
     // Only when the global switch is explicitly set to false, return Ok
     if !enable_validation {
-        return Ok(());
+        return Ok(RunOutcome::Disabled);
     }
 
-    let commit_msg_raw =
-        fs::read_to_string(msg_path).map_err(|e| format!("cannot read commit message: {}", e))?;
+    let mut commit_msg_raw =
+        fs::read_to_string(msg_path).map_err(|e| HookError::io("read commit message", e))?;
 
-    if let Some(first_line) = get_first_non_empty_line(&commit_msg_raw) {
-        // Get skip_validation_words, default is an empty list
-        let skip_words = parsed_rule
-            .global
-            .as_ref()
-            .and_then(|g| g.skip_validation_words.clone())
-            .unwrap_or_else(Vec::new);
+    let Some(first_line) = get_first_non_empty_line(&commit_msg_raw) else {
+        return Err(HookError::EmptyMessage);
+    };
 
-        // Check if the first line matches any skip words
-        if skip_words.iter().any(|w| w == first_line.trim()) {
-            let mut lines: Vec<&str> = commit_msg_raw.lines().collect();
+    // Get skip_validation_words, default is an empty list
+    let skip_words = parsed_rule
+        .global
+        .as_ref()
+        .and_then(|g| g.skip_validation_words.clone())
+        .unwrap_or_else(Vec::new);
 
-            // find index of first non-empty line and remove it
-            if let Some(idx) = lines.iter().position(|l| l.trim() == first_line.trim()) {
-                lines.remove(idx);
-            }
+    // Check if the first line matches any skip words, e.g. `SKIP` or `SKIP: reason`
+    if let Some(request) = match_skip_line(&first_line, &skip_words)? {
+        let policy = skip_policy(parsed_rule.global.as_ref());
+        check_skip_policy(&request, &policy, &SkipContext::from_git())?;
+
+        let mut lines: Vec<&str> = commit_msg_raw.lines().collect();
 
-            // join back new commit message
-            let new_msg = lines.join("\n");
+        // find index of first non-empty line and remove it
+        if let Some(idx) = lines.iter().position(|l| l.trim() == first_line.trim()) {
+            lines.remove(idx);
+        }
 
-            // write back commit message file
-            fs::write(msg_path, new_msg)
-                .map_err(|e| format!("failed to rewrite commit message: {}", e))?;
+        // join back new commit message
+        let new_msg = lines.join("\n");
 
-            print_success("commit message is skipped validation");
-            return Ok(());
-        } else if skip_words
-            .iter()
-            .any(|w| w.eq_ignore_ascii_case(first_line.trim()))
+        // write back commit message file
+        fs::write(msg_path, new_msg).map_err(|e| HookError::io("rewrite commit message", e))?;
+
+        record_skip(msg_path, &request, &policy)?;
+        return Ok(RunOutcome::Skipped(skip_trailer_value(&request)));
+    }
+
+    // Classify (merge, revert, fixup...) and validate commit message against the configured rules.
+    // Rule files are named in errors once subdirectory rule files are involved.
+    let name_rule_files =
+        targets.len() > 1 || targets.first().is_some_and(|(p, _)| *p != rule_path);
+    let auto_fix = user.is_some_and(UserConfig::auto_fix);
+    let mut fixed = false;
+
+    let combined = validate_with_rule_files(&targets, policy, |rule| {
+        let validate = |msg: &str| {
+            validate_raw_commit_msg(msg, rule, ValidationMode::Hook)
+                .and_then(|v| if strict { v.into_strict() } else { Ok(v) })
+        };
+        let mut validated = validate(&commit_msg_raw);
+
+        // `auto_fix` of the user config: replace misspelled types and scopes, then validate again
+        while auto_fix
+            && let Err(e) = &validated
+            && let Some(fix) = fix_commit_msg(&commit_msg_raw, e)
+            && fix.message != commit_msg_raw
         {
-            return Err(
-                "skip keyword matched case-insensitively; validation not skipped".to_string(),
-            );
+            warnings.push(format!("note: auto_fix changed {}", fix.description));
+            commit_msg_raw = fix.message;
+            validated = validate(&commit_msg_raw);
+            fixed = true;
         }
-    } else {
-        return Err("commit message cannot be empty".to_string());
+        validated
+    });
+
+    if fixed {
+        fs::write(msg_path, &commit_msg_raw)
+            .map_err(|e| HookError::io("rewrite commit message", e))?;
+    }
+
+    // Note: The error message here will be printed directly to stderr for the user, do not use debug mode
+    let validated = combined.map_err(|rejections| {
+        HookError::MessageRejected(rejection_message(
+            &rejections,
+            name_rule_files,
+            display_rule_path,
+        ))
+    })?;
+    warnings.extend(
+        validated
+            .warnings
+            .iter()
+            .map(|w| format!("warning: {}", w.error)),
+    );
+
+    Ok(match validated.kind {
+        CommitKind::Regular => RunOutcome::Passed,
+        kind => RunOutcome::Accepted(kind.name()),
+    })
+}
+
+/// Record a skip as a `Validation-Skipped` trailer of the message at `msg_path`, so that the
+/// bypass stays visible in history and range linting, unless the policy turns it off
+fn record_skip(
+    msg_path: &Path,
+    request: &SkipRequest,
+    policy: &SkipValidationRule,
+) -> Result<(), HookError> {
+    if !policy.record_trailer.unwrap_or(true) {
+        return Ok(());
     }
+    let trailer = format!("{}: {}", SKIP_TRAILER_KEY, skip_trailer_value(request));
+    let msg_path = msg_path.to_string_lossy();
+    run_git_checked(&[
+        "interpret-trailers",
+        "--in-place",
+        "--trailer",
+        &trailer,
+        &msg_path,
+    ])
+    .map_err(HookError::SkipTrailer)?;
+    Ok(())
+}
 
-    let msg = parse_commit_msg(&commit_msg_raw);
-    let msg = msg.map_err(|e| e.to_string())?;
+/// Load a rule file of the repository over the user rules and collect its warnings.
+/// With `check_status`, the file must be committed, see [`check_config_status`].
+fn load_rule_file(
+    path: &Path,
+    check_status: bool,
+    strict: bool,
+    user: Option<&UserConfig>,
+    warnings: &mut Vec<String>,
+) -> Result<ParsedCommitMsgRule, HookError> {
+    // Manifests (Cargo.toml, pyproject.toml) are skipped: unrelated unstaged edits are common.
+    if check_status
+        && path.exists()
+        && !matches!(RuleFormat::of(path), RuleFormat::TomlSection(_))
+        && let Some(relative_path) = repo_relative_path(path)?
+    {
+        // Git uses paths relative to the repository root. You should NOT pass an
+        // absolute or full filesystem path; otherwise Git cannot correctly determine
+        // the file's status.
+        let git_kind = detect_current_repo()?;
+        let status = check_config_status(git_kind.workdir(), &relative_path)?;
 
-    // Validate commit message against the configured rules
-    let is_valid = validate_commit_msg(&msg, &parsed_rule);
-    if let Err(e) = is_valid {
-        // Note: The error message here will be printed directly to stderr for the user, do not use debug mode
-        return Err(format!("error: {}", e));
+        if status == ConfigStatus::Untracked {
+            let warning = untracked_config_warning(&relative_path);
+            if strict {
+                return Err(HookError::UntrackedRuleFile { warning });
+            }
+            warnings.push(warning);
+        }
     }
 
-    Ok(())
+    // `extends` entries are resolved relative to the rule file
+    let parsed_rule = load_commit_msg_rule_with_user(path, user)?;
+    warnings.extend(
+        parsed_rule
+            .warnings
+            .iter()
+            .map(|w| format!("warning: {}", w)),
+    );
+    Ok(parsed_rule)
+}
+
+/// A rule file path relative to the repository root when possible, for messages
+fn display_rule_path(path: &Path) -> String {
+    repo_relative_path(path)
+        .ok()
+        .flatten()
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+/// Path of a rule file relative to the repository root, with `/` separators as used by git,
+/// or `None` when it is outside the repository
+pub(crate) fn repo_relative_path(path: &Path) -> Result<Option<String>, GitKindError> {
+    let git_kind = detect_current_repo()?;
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+
+    Ok(path.strip_prefix(git_kind.workdir()).ok().map(|relative| {
+        relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/")
+    }))
+}
+
+/// Validate every commit message of a revision range, e.g. `origin/main..HEAD`.
+/// The rule files are selected like for the pre-push hook, see [`RangeRuleFiles`]: the
+/// root one (`rule_path`, `GITRU_RULE`, `gitru.ruleFile` or discovery, see
+/// [`resolve_rule_file`]) and the ones in subdirectories each commit changes files below,
+/// over the user rules. With `strict`, checks of severity `warning` fail the lint as well.
+pub fn lint(
+    range: &str,
+    rule_path: Option<&PathBuf>,
+    strict: bool,
+    user: Option<&UserConfig>,
+) -> Result<(), HookError> {
+    let overrides = RunOverrides::load(user)?;
+    let (rule_path, origin) = resolve_rule_file(rule_path, &overrides)?;
+    for w in user.map(UserConfig::warnings).unwrap_or_default() {
+        print_warning(&w);
+    }
+
+    let mut rules = RangeRuleFiles::load(rule_path.clone(), &origin, user)?;
+    let commits = list_commits(&[range])?;
+    rules.select(&commits, user)?;
+    if rules.is_empty() {
+        return Err(HookError::NoRuleFile { path: rule_path });
+    }
+
+    let report = rules.lint(&commits, strict);
+    print_lint_report(&report);
+
+    let failed = report.failed_commits();
+    if failed.is_empty() {
+        print_success(&format!(
+            "{} commit message(s) in `{}` passed validation",
+            commits.len(),
+            range
+        ));
+        return Ok(());
+    }
+
+    Err(HookError::RangeRejected {
+        failed: failed.len(),
+        total: commits.len(),
+        range: range.to_string(),
+    })
+}
+
+/// Print the warnings, the skipped commits and the violations of a range lint
+pub(crate) fn print_lint_report(report: &RangeLintReport) {
+    for w in &report.warnings {
+        print_warning(&format!(
+            "{} {}\n  warning: {}",
+            short_sha(&w.sha),
+            w.header,
+            w.error
+        ));
+    }
+
+    for s in &report.skipped {
+        print_warning(&format!(
+            "{} {}\n  validation was skipped: {}",
+            short_sha(&s.sha),
+            s.header,
+            s.reason
+        ));
+    }
+
+    for v in &report.violations {
+        let rule_file = v
+            .rule_file
+            .as_deref()
+            .map(|path| format!(" (rule file: {})", display_rule_path(path)))
+            .unwrap_or_default();
+        print_error(&format!(
+            "{} {}\n  error: {}{}",
+            short_sha(&v.sha),
+            v.header,
+            v.error,
+            rule_file
+        ));
+    }
 }
@@ -0,0 +1,234 @@
+use crate::cli::{ConfigCmd, ShowFormat};
+use crate::config::check::{CheckedRule, check_layers};
+use crate::config::commit_msg_rule::load_commit_msg_rule_with_user;
+use crate::config::discovery::{RuleFormat, repo_rule_file, resolve_rule_file};
+use crate::config::extends::load_layers;
+use crate::config::migrate::{RULE_VERSION, migrate_rule};
+use crate::config::overrides::{Override, RunOverrides};
+use crate::config::schema::json_schema;
+use crate::config::show::{annotated_toml, effective_table, value_sources};
+use crate::config::user::{UserConfig, user_rule_layers};
+use crate::error::hook_error::HookError;
+use crate::util::colored_print::{print_error, print_info, print_success, print_warning};
+use crate::util::git_path::detect_current_repo;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub fn run_config(cmd: &ConfigCmd, user: Option<&UserConfig>) -> Result<(), HookError> {
+    match cmd {
+        ConfigCmd::Check { rule } => check(rule.as_ref(), user),
+        ConfigCmd::Schema => schema(),
+        ConfigCmd::Show { rule, format } => show(rule.as_ref(), *format, user),
+        ConfigCmd::Migrate { rule, dry_run } => migrate(rule.as_ref(), *dry_run),
+    }
+}
+
+/// Path of the rule file: the given one, or the one discovered in the repository,
+/// see [`crate::config::discovery`]
+fn rule_file_path(rule_path: Option<&PathBuf>) -> Result<PathBuf, HookError> {
+    match rule_path {
+        Some(path) => Ok(path.clone()),
+        None => {
+            let git_kind = detect_current_repo()?;
+            Ok(repo_rule_file(&git_kind))
+        }
+    }
+}
+
+/// Check the rule file and everything it extends, over the rules of the user config,
+/// printing every problem found.
+pub fn check(rule_path: Option<&PathBuf>, user: Option<&UserConfig>) -> Result<(), HookError> {
+    let (path, checked) = check_rule_file(&rule_file_path(rule_path)?, user)?;
+    for warning in user.map(UserConfig::warnings).unwrap_or_default() {
+        print_warning(&warning);
+    }
+    for warning in &checked.warnings {
+        print_warning(&warning.to_string());
+    }
+
+    if checked.diagnostics.is_empty() {
+        print_success(&format!("{} is valid", path));
+        return Ok(());
+    }
+
+    for diagnostic in &checked.diagnostics {
+        print_error(&diagnostic.to_string());
+    }
+
+    Err(HookError::InvalidRuleFile {
+        count: checked.diagnostics.len(),
+        name: path,
+    })
+}
+
+/// Check the rule file at `path` over the user rules, returning the name of the checked
+/// rule: the rule file, or the user config when there is no rule file.
+pub(crate) fn check_rule_file(
+    path: &Path,
+    user: Option<&UserConfig>,
+) -> Result<(String, CheckedRule), HookError> {
+    let mut layers = user_rule_layers(user)?;
+    if layers.is_empty() || path.exists() {
+        let content = fs::read_to_string(path)
+            .map_err(|e| HookError::io(format!("read rule file {}", path.display()), e))?;
+        layers.extend(load_layers(&content, Some(path))?);
+    }
+    // without a rule file, the user rules are what the commit-msg hook uses
+    let name = layers
+        .last()
+        .map(|l| l.name.clone())
+        .unwrap_or_else(|| path.display().to_string());
+
+    let checked = check_layers(layers)?;
+    Ok((name, checked))
+}
+
+/// Rewrite the rule file to the current format, or print the result with `dry_run`.
+pub fn migrate(rule_path: Option<&PathBuf>, dry_run: bool) -> Result<(), HookError> {
+    let path = rule_file_path(rule_path)?;
+    let content = fs::read_to_string(&path)
+        .map_err(|e| HookError::io(format!("read rule file {}", path.display()), e))?;
+
+    let migration = migrate_rule(&content, RuleFormat::of(&path), &path.display().to_string())?;
+
+    if dry_run {
+        print!("{}", migration.content);
+        return Ok(());
+    }
+
+    if migration.changes.is_empty() {
+        print_success(&format!(
+            "{} is up to date (rule format {})",
+            path.display(),
+            RULE_VERSION
+        ));
+        return Ok(());
+    }
+
+    fs::write(&path, &migration.content)
+        .map_err(|e| HookError::io(format!("write rule file {}", path.display()), e))?;
+
+    for change in &migration.changes {
+        print_info(change);
+    }
+    print_success(&format!(
+        "migrated {} to rule format {}",
+        path.display(),
+        RULE_VERSION
+    ));
+    Ok(())
+}
+
+/// Print the effective rule the commit-msg hook would use, with the source of each value.
+pub fn show(
+    rule_path: Option<&PathBuf>,
+    format: ShowFormat,
+    user: Option<&UserConfig>,
+) -> Result<(), HookError> {
+    let overrides = RunOverrides::load(user)?;
+    let git_kind = detect_current_repo().ok();
+    let (path, origin) = resolve_rule_file(rule_path, &overrides)?;
+    let rule = load_commit_msg_rule_with_user(&path, user)?;
+    let user_warnings = user.map(UserConfig::warnings).unwrap_or_default();
+
+    // overrides that change how the rule is applied, not the rule itself
+    let settings: Vec<(&str, String, String)> = [
+        setting("skip", &overrides.skip),
+        setting("strict", &overrides.strict),
+        setting("format", &overrides.format),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+
+    match format {
+        ShowFormat::Toml => {
+            match &git_kind {
+                Some(kind) => println!(
+                    "# repository: {} ({})",
+                    kind.name(),
+                    kind.workdir().display()
+                ),
+                None => println!("# repository: none"),
+            }
+            println!("# rule file: {} ({})", path.display(), origin);
+            if let Some(user) = user {
+                println!("# user config: {}", user.path.display());
+            }
+            for (name, value, source) in &settings {
+                println!("# {}: {} ({})", name, value, source);
+            }
+            for warning in &user_warnings {
+                println!("# {}", warning);
+            }
+            for warning in &rule.warnings {
+                println!("# warning: {}", warning.to_string().replace('\n', "\n#"));
+            }
+            println!();
+            print!("{}", annotated_toml(&rule)?);
+        }
+
+        ShowFormat::Json => {
+            let table = effective_table(&rule)?;
+            let sources = value_sources(&rule, &table);
+            let overrides: serde_json::Map<String, serde_json::Value> = settings
+                .into_iter()
+                .map(|(name, value, source)| {
+                    (
+                        name.to_string(),
+                        serde_json::json!({ "value": value, "source": source }),
+                    )
+                })
+                .collect();
+
+            let report = serde_json::json!({
+                "repository": git_kind.as_ref().map(|kind| serde_json::json!({
+                    "kind": kind.name(),
+                    "workdir": kind.workdir().display().to_string(),
+                    "git_dir": kind.git_dir().display().to_string(),
+                })),
+                "rule_file": {
+                    "path": path.display().to_string(),
+                    "origin": origin.to_string(),
+                },
+                "user_config": user.map(|u| u.path.display().to_string()),
+                "overrides": overrides,
+                "rule": table,
+                "sources": sources,
+                "warnings": user_warnings
+                    .into_iter()
+                    .chain(rule.warnings.iter().map(|w| format!("warning: {}", w)))
+                    .collect::<Vec<_>>(),
+            });
+            let json =
+                serde_json::to_string_pretty(&report).map_err(|source| HookError::Serialize {
+                    what: "configuration",
+                    source,
+                })?;
+            println!("{}", json);
+        }
+    }
+
+    Ok(())
+}
+
+/// Name, value and source of an override that is set, for `config show`
+fn setting<'a, T: std::fmt::Display>(
+    name: &'a str,
+    value: &Option<Override<T>>,
+) -> Option<(&'a str, String, String)> {
+    value
+        .as_ref()
+        .map(|o| (name, o.value.to_string(), o.source.to_string()))
+}
+
+/// Print the JSON Schema of the rule file to stdout
+pub fn schema() -> Result<(), HookError> {
+    let schema =
+        serde_json::to_string_pretty(&json_schema()).map_err(|source| HookError::Serialize {
+            what: "schema",
+            source,
+        })?;
+    println!("{}", schema);
+    Ok(())
+}
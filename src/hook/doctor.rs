@@ -0,0 +1,715 @@
+//! `gitru doctor`: diagnose the installation when the commit-msg hook "doesn't run"
+//!
+//! Checks, in the order git goes through them when committing:
+//! 1. the repository type and the hooks directory git uses, including `core.hooksPath`
+//! 2. the commit-msg hook: present, executable, written by gitru, and running a gitru binary
+//!    that still exists, of the version that wrote the hook, as found by the `exec` strategy
+//!    of its marker (see `gitru install --exec`)
+//! 3. the rule file: which one is used, whether it parses and passes `gitru config check`,
+//!    and its git status as the hook sees it
+//!
+//! Every problem comes with a suggested fix.
+
+use crate::config::discovery::{RuleFormat, resolve_rule_file};
+use crate::config::overrides::RunOverrides;
+use crate::config::user::{UserConfig, user_rule_layers};
+use crate::error::git_error::ConfigStatusCheckError;
+use crate::error::hook_error::HookError;
+use crate::hook::commit_msg::repo_relative_path;
+use crate::hook::config::check_rule_file;
+use crate::hook::install::{
+    chained_order, hooks_path_note, installed_exec, local_hook_path, read_hook,
+};
+use crate::hook::kind::{HookExec, HookKind};
+use crate::hook::marker::{HookOwnership, inspect};
+use crate::util::colored_print::{print_error, print_info, print_success, print_warning};
+use crate::util::git_path::detect_current_repo;
+use crate::validator::git_status::{ConfigStatus, check_config_status, untracked_config_warning};
+use std::env;
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Line of the hook template holding the gitru binary the hook runs
+const PROGRAM_EXEC_PREFIX: &str = "VALIDATOR_SCRIPT=\"";
+
+/// How bad a finding is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FindingLevel {
+    Ok,
+    /// Works, but probably not as intended
+    Warning,
+    /// The hook fails or does not validate
+    Error,
+}
+
+/// One result of `gitru doctor`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    pub level: FindingLevel,
+    pub message: String,
+    /// Suggested fix, for warnings and errors
+    pub fix: Option<String>,
+}
+
+impl Finding {
+    fn ok(message: impl Into<String>) -> Self {
+        Self {
+            level: FindingLevel::Ok,
+            message: message.into(),
+            fix: None,
+        }
+    }
+
+    fn warning(message: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            level: FindingLevel::Warning,
+            message: message.into(),
+            fix: Some(fix.into()),
+        }
+    }
+
+    fn error(message: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            level: FindingLevel::Error,
+            message: message.into(),
+            fix: Some(fix.into()),
+        }
+    }
+}
+
+/// Diagnose the installation in the current repository, failing when the hook cannot work.
+pub fn doctor(user: Option<&UserConfig>) -> Result<(), HookError> {
+    let git_kind = detect_current_repo()?;
+    let hooks_dir = git_kind.hooks_dir();
+
+    print_info(&format!(
+        "repository: {} ({})",
+        git_kind.name(),
+        git_kind.workdir().display()
+    ));
+    print_info(&format!(
+        "hooks directory: {}{}",
+        hooks_dir.path.display(),
+        hooks_path_note(&hooks_dir)
+    ));
+
+    let mut findings = Vec::new();
+    if hooks_dir.is_in_worktree(git_kind.workdir()) {
+        findings.push(Finding::warning(
+            format!(
+                "hooks directory {} (core.hooksPath) is inside the working tree, \
+                 the hook is shared with everyone who commits it",
+                hooks_dir.path.display()
+            ),
+            "make sure this is intended, or `git config --unset core.hooksPath`",
+        ));
+    }
+    findings.extend(check_hook(&hooks_dir.path, git_kind.workdir()));
+    findings.extend(check_rule(user, git_kind.workdir()));
+
+    for finding in &findings {
+        report(finding);
+    }
+
+    let errors = findings
+        .iter()
+        .filter(|f| f.level == FindingLevel::Error)
+        .count();
+    if errors > 0 {
+        return Err(HookError::ProblemsFound { count: errors });
+    }
+    print_success("no problems found");
+    Ok(())
+}
+
+fn report(finding: &Finding) {
+    match finding.level {
+        FindingLevel::Ok => print_success(&finding.message),
+        FindingLevel::Warning => print_warning(&finding.message),
+        FindingLevel::Error => print_error(&finding.message),
+    }
+    if let Some(fix) = &finding.fix {
+        println!("    fix: {}", fix);
+    }
+}
+
+/// Findings about the commit-msg hook in `hooks_dir` of the repository at `workdir`
+fn check_hook(hooks_dir: &Path, workdir: &Path) -> Vec<Finding> {
+    let hook_path = hooks_dir.join("commit-msg");
+    let content = match read_hook(&hook_path) {
+        Ok(Some(content)) => content,
+        Ok(None) => {
+            return vec![Finding::error(
+                format!("no commit-msg hook at {}", hook_path.display()),
+                "run `gitru install commit-msg`",
+            )];
+        }
+        Err(e) => {
+            return vec![Finding::error(
+                e.to_string(),
+                "check the permissions of the hooks directory",
+            )];
+        }
+    };
+
+    let mut findings = Vec::new();
+    if !is_executable(&hook_path) {
+        findings.push(Finding::error(
+            format!(
+                "commit-msg hook at {} is not executable, git ignores it",
+                hook_path.display()
+            ),
+            format!("`chmod +x {}`", hook_path.display()),
+        ));
+    }
+
+    let current = env!("CARGO_PKG_VERSION");
+    let expected_version = match inspect(&content) {
+        HookOwnership::Foreign => {
+            findings.push(Finding::warning(
+                format!(
+                    "commit-msg hook at {} was not installed by gitru, \
+                     commit messages are not validated unless it runs gitru",
+                    hook_path.display()
+                ),
+                "keep it and run gitru with `gitru install commit-msg --chain after`, \
+                 or replace it with `gitru install commit-msg --force`",
+            ));
+            return findings;
+        }
+        HookOwnership::Legacy => {
+            findings.push(Finding::warning(
+                "commit-msg hook was written by an older gitru without an ownership marker",
+                "upgrade it with `gitru install commit-msg`",
+            ));
+            current.to_string()
+        }
+        HookOwnership::Gitru { version, modified } => {
+            findings.push(Finding::ok(format!(
+                "commit-msg hook at {} installed by gitru {}",
+                hook_path.display(),
+                version
+            )));
+            if modified {
+                findings.push(Finding::warning(
+                    "commit-msg hook was modified after gitru installed it",
+                    "restore it with `gitru install commit-msg --force`",
+                ));
+            }
+            if version != current {
+                findings.push(Finding::warning(
+                    format!(
+                        "commit-msg hook was written by gitru {}, this is gitru {}",
+                        version, current
+                    ),
+                    "upgrade it with `gitru install commit-msg`",
+                ));
+            }
+            version
+        }
+    };
+
+    let exec = installed_exec(&content).unwrap_or_default();
+    findings.push(check_program(&content, &exec, workdir, &expected_version));
+
+    if let Some(order) = chained_order(&content) {
+        let local_path = local_hook_path(hooks_dir, HookKind::CommitMsg);
+        findings.push(if is_executable(&local_path) {
+            Finding::ok(format!(
+                "{} runs {} gitru",
+                local_path.display(),
+                order.as_str()
+            ))
+        } else {
+            Finding::warning(
+                format!(
+                    "commit-msg hook is chained, but {} is missing or not executable",
+                    local_path.display()
+                ),
+                format!(
+                    "restore the hook or `chmod +x {}`; \
+                     `gitru install commit-msg --force` removes the chain",
+                    local_path.display()
+                ),
+            )
+        });
+    }
+
+    findings
+}
+
+/// Finding about the gitru binary a gitru hook runs, found with its `exec` strategy.
+/// Repository paths are relative to `workdir`.
+fn check_program(
+    content: &str,
+    exec: &HookExec,
+    workdir: &Path,
+    expected_version: &str,
+) -> Finding {
+    let program = match exec {
+        HookExec::Absolute => {
+            let Some(program) = program_exec(content) else {
+                return Finding::warning(
+                    "cannot find the gitru binary in the commit-msg hook",
+                    "rewrite the hook with `gitru install commit-msg --force`",
+                );
+            };
+            if !is_executable(&program) {
+                // the hook falls back to `gitru` on PATH
+                return match find_on_path("gitru") {
+                    Some(fallback) => Finding::warning(
+                        format!(
+                            "gitru binary {} no longer exists, the hook runs {} instead",
+                            program.display(),
+                            fallback.display()
+                        ),
+                        "point the hook at the current binary with `gitru install commit-msg`, \
+                         or let it look gitru up with `gitru install commit-msg --exec path`",
+                    ),
+                    None => Finding::error(
+                        format!(
+                            "gitru binary {} no longer exists and gitru is not on PATH, \
+                             every commit fails",
+                            program.display()
+                        ),
+                        "reinstall gitru (`cargo install gitru`), then run `gitru install commit-msg`",
+                    ),
+                };
+            }
+            program
+        }
+        HookExec::Path => match find_on_path("gitru") {
+            Some(program) => program,
+            None => {
+                return Finding::error(
+                    "gitru is not on PATH (exec: path), every commit fails",
+                    "install gitru (`cargo install gitru`), or choose another strategy \
+                     with `gitru install commit-msg --exec absolute`",
+                );
+            }
+        },
+        HookExec::Repo(path) => {
+            let program = workdir.join(path);
+            if !is_executable(&program) {
+                return Finding::error(
+                    format!(
+                        "{} is missing or not executable (exec: repo), every commit fails",
+                        program.display()
+                    ),
+                    format!(
+                        "add the gitru binary at {} to the repository, or choose another \
+                         strategy with `gitru install commit-msg --exec path`",
+                        path
+                    ),
+                );
+            }
+            program
+        }
+        HookExec::Cargo => return check_cargo(workdir),
+    };
+
+    match program_version(&program) {
+        Some(version) if version == expected_version => Finding::ok(format!(
+            "commit-msg hook runs gitru {} at {} (exec: {})",
+            version,
+            program.display(),
+            exec
+        )),
+        Some(version) => Finding::warning(
+            format!(
+                "commit-msg hook runs gitru {} at {} (exec: {}), but was written by gitru {}",
+                version,
+                program.display(),
+                exec,
+                expected_version
+            ),
+            "rewrite the hook for that binary by running `gitru install commit-msg` with it",
+        ),
+        None => Finding::warning(
+            format!(
+                "cannot get the version of {}, it may not be gitru",
+                program.display()
+            ),
+            "point the hook at the current binary with `gitru install commit-msg`",
+        ),
+    }
+}
+
+/// Finding about running gitru with `cargo run` in `workdir`. The version is only known
+/// once built, so it is not checked.
+fn check_cargo(workdir: &Path) -> Finding {
+    if find_on_path("cargo").is_none() {
+        return Finding::error(
+            "cargo is not on PATH (exec: cargo), every commit fails",
+            "install Rust with rustup, or choose another strategy \
+             with `gitru install commit-msg --exec path`",
+        );
+    }
+    if !workdir.join("Cargo.toml").is_file() {
+        return Finding::error(
+            format!(
+                "no Cargo.toml in {} (exec: cargo), `cargo run --bin gitru` fails",
+                workdir.display()
+            ),
+            "choose another strategy with `gitru install commit-msg --exec path`",
+        );
+    }
+
+    let output = Command::new("cargo")
+        .args(["metadata", "--no-deps", "--format-version", "1"])
+        .current_dir(workdir)
+        .output();
+    let metadata = match output {
+        Ok(output) if output.status.success() => {
+            serde_json::from_slice::<serde_json::Value>(&output.stdout).ok()
+        }
+        _ => None,
+    };
+    let Some(metadata) = metadata else {
+        return Finding::error(
+            format!(
+                "`cargo metadata` fails in {} (exec: cargo), so does `cargo run --bin gitru`",
+                workdir.display()
+            ),
+            "fix the Cargo.toml, `cargo metadata --no-deps` shows the problem",
+        );
+    };
+    if !has_gitru_bin(&metadata) {
+        return Finding::error(
+            format!(
+                "no package in {} has a `gitru` binary (exec: cargo), \
+                 `cargo run --bin gitru` fails",
+                workdir.display()
+            ),
+            "choose another strategy with `gitru install commit-msg --exec path`",
+        );
+    }
+
+    Finding::ok(format!(
+        "commit-msg hook runs gitru with `cargo run --bin gitru` in {} (exec: cargo)",
+        workdir.display()
+    ))
+}
+
+/// Whether the output of `cargo metadata` has a `gitru` bin target
+fn has_gitru_bin(metadata: &serde_json::Value) -> bool {
+    let mut targets = metadata["packages"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|package| package["targets"].as_array())
+        .flatten();
+
+    targets.any(|target| {
+        target["name"] == "gitru"
+            && target["kind"]
+                .as_array()
+                .is_some_and(|kinds| kinds.iter().any(|kind| kind == "bin"))
+    })
+}
+
+/// The binary a hook script runs, as written by `gitru install`
+fn program_exec(content: &str) -> Option<PathBuf> {
+    content.lines().find_map(|line| {
+        let value = line.strip_prefix(PROGRAM_EXEC_PREFIX)?.strip_suffix('"')?;
+        (!value.is_empty() && !value.contains("{{")).then(|| PathBuf::from(value))
+    })
+}
+
+/// Version printed by `<program> --version`, e.g. `gitru 0.2.11`
+fn program_version(program: &Path) -> Option<String> {
+    let output = Command::new(program).arg("--version").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut words = stdout.split_whitespace();
+    match (words.next(), words.next()) {
+        (Some("gitru"), Some(version)) => Some(version.to_string()),
+        _ => None,
+    }
+}
+
+/// `name` on PATH, looked up like the hook does
+fn find_on_path(name: &str) -> Option<PathBuf> {
+    hook_search_dirs(
+        &env::var_os("PATH").unwrap_or_default(),
+        env::var_os("CARGO_HOME"),
+        env::var_os("HOME"),
+    )
+    .into_iter()
+    .map(|dir| dir.join(name))
+    .find(|candidate| is_executable(candidate))
+}
+
+/// Directories the hook searches in order: the hook appends Cargo's bin directory
+/// (`${CARGO_HOME:-$HOME/.cargo}/bin`) to PATH, so it comes last
+fn hook_search_dirs(
+    path: &OsStr,
+    cargo_home: Option<OsString>,
+    home: Option<OsString>,
+) -> Vec<PathBuf> {
+    let cargo_bin = cargo_home
+        .map(PathBuf::from)
+        .or_else(|| home.map(|home| Path::new(&home).join(".cargo")))
+        .map(|cargo_home| cargo_home.join("bin"));
+
+    env::split_paths(path).chain(cargo_bin).collect()
+}
+
+#[cfg(target_family = "unix")]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(target_family = "unix"))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// Findings about the rule file the commit-msg hook uses in the repository at `workdir`
+fn check_rule(user: Option<&UserConfig>, workdir: &Path) -> Vec<Finding> {
+    let overrides = match RunOverrides::load(user) {
+        Ok(overrides) => overrides,
+        Err(e) => {
+            return vec![Finding::error(
+                e.to_string(),
+                "fix the GITRU_* environment variable or gitru.* git config",
+            )];
+        }
+    };
+
+    let mut findings = Vec::new();
+    if let Some(skip) = overrides.skip.as_ref().filter(|s| s.value) {
+        findings.push(Finding::warning(
+            format!("validation is skipped by {}", skip.source),
+            "unset it to validate commit messages again",
+        ));
+    }
+
+    let (path, origin) = match resolve_rule_file(None, &overrides) {
+        // git runs hooks from the root of the working tree, relative paths start there
+        Ok((path, origin)) => (workdir.join(path), origin),
+        Err(e) => {
+            findings.push(Finding::error(
+                format!("failed to detect git kind: {}", e),
+                "run `gitru doctor` inside a git repository",
+            ));
+            return findings;
+        }
+    };
+
+    let has_user_rules = user_rule_layers(user).is_ok_and(|layers| !layers.is_empty());
+    if !path.exists() && !has_user_rules {
+        findings.push(Finding::error(
+            format!("no rule file at {} ({})", path.display(), origin),
+            "create one with `gitru init commit-msg`",
+        ));
+        return findings;
+    }
+    findings.push(Finding::ok(format!(
+        "rule file: {} ({})",
+        path.display(),
+        origin
+    )));
+
+    match check_rule_file(&path, user) {
+        Ok((name, checked)) if checked.diagnostics.is_empty() => {
+            findings.push(Finding::ok(format!("{} is valid", name)));
+            for warning in &checked.warnings {
+                findings.push(Finding::warning(
+                    warning.to_string(),
+                    "update the rule file with `gitru config migrate`",
+                ));
+            }
+            let disabled = checked
+                .rule
+                .as_ref()
+                .and_then(|r| r.global.as_ref())
+                .and_then(|g| g.enable_validation)
+                == Some(false);
+            if disabled {
+                findings.push(Finding::warning(
+                    format!(
+                        "validation is disabled by `global.enable_validation` in {}",
+                        name
+                    ),
+                    "set `enable_validation = true` to validate commit messages",
+                ));
+            }
+        }
+        Ok((name, checked)) => {
+            let problems: Vec<String> = checked.diagnostics.iter().map(|d| d.to_string()).collect();
+            findings.push(Finding::error(
+                format!(
+                    "{} problem(s) found in {}:\n{}",
+                    problems.len(),
+                    name,
+                    problems.join("\n")
+                ),
+                "fix the rule file, `gitru config check` shows the details",
+            ));
+        }
+        Err(e) => findings.push(Finding::error(
+            e.to_string(),
+            "fix the rule file, `gitru config check` shows the details",
+        )),
+    }
+
+    // like the hook: only repository rule files must be committed, manifests are not checked
+    let checks_status = overrides.rule_file.is_none()
+        && path.exists()
+        && !matches!(RuleFormat::of(&path), RuleFormat::TomlSection(_));
+    if checks_status && let Ok(Some(relative_path)) = repo_relative_path(&path) {
+        findings.push(check_rule_status(workdir, &relative_path));
+    }
+
+    findings
+}
+
+/// Finding about the git status of the rule file, as [`check_config_status`] sees it
+fn check_rule_status(workdir: &Path, relative_path: &str) -> Finding {
+    match check_config_status(workdir, relative_path) {
+        Ok(ConfigStatus::Tracked) => Finding::ok(format!("{} is tracked by git", relative_path)),
+        Ok(ConfigStatus::Untracked) => Finding::warning(
+            untracked_config_warning(relative_path)
+                .trim_start_matches("warning: ")
+                .to_string(),
+            format!("`git add {}`", relative_path),
+        ),
+        Err(e @ ConfigStatusCheckError::ConfigNotCommitted { .. }) => Finding::error(
+            e.to_string(),
+            format!(
+                "stage it with `git add {0}`, or discard the changes with `git restore {0}`",
+                relative_path
+            ),
+        ),
+        Err(e) => Finding::warning(e.to_string(), "check `git status`"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constant::hook_content;
+    use crate::hook::marker::mark;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn levels(findings: &[Finding]) -> Vec<FindingLevel> {
+        findings.iter().map(|f| f.level).collect()
+    }
+
+    #[test]
+    fn test_check_hook() {
+        let dir = TempDir::new().unwrap();
+        let hook_path = dir.path().join("commit-msg");
+
+        let missing = check_hook(dir.path(), dir.path());
+        assert_eq!(levels(&missing), [FindingLevel::Error]);
+        assert!(missing[0].fix.as_deref().unwrap().contains("gitru install"));
+
+        fs::write(
+            &hook_path,
+            "#!/bin/sh\necho \"Change-Id: I123\" >> \"$1\"\n",
+        )
+        .unwrap();
+        let foreign = check_hook(dir.path(), dir.path());
+        assert!(
+            foreign
+                .iter()
+                .any(|f| f.message.contains("not installed by gitru"))
+        );
+
+        // a gitru hook running a binary that no longer exists
+        let script = "#!/bin/sh\n# This file is initialized by gitru\n\
+                      VALIDATOR_SCRIPT=\"/nonexistent/gitru\"\nCHAIN_ORDER=\"after\"\n";
+        fs::write(&hook_path, mark(script, &[])).unwrap();
+        let findings = check_hook(dir.path(), dir.path());
+        assert!(
+            findings
+                .iter()
+                .any(|f| f.message.contains("/nonexistent/gitru no longer exists"))
+        );
+        assert!(
+            findings
+                .iter()
+                .any(|f| f.level == FindingLevel::Warning && f.message.contains("chained"))
+        );
+
+        #[cfg(target_family = "unix")]
+        assert!(
+            findings
+                .iter()
+                .any(|f| f.level == FindingLevel::Error && f.message.contains("not executable"))
+        );
+    }
+
+    #[test]
+    fn test_check_program_strategies() {
+        let dir = TempDir::new().unwrap();
+        let exec: HookExec = "repo:tools/gitru".parse().unwrap();
+        let content = hook_content(HookKind::CommitMsg, "", &exec);
+
+        let missing = check_program(&content, &exec, dir.path(), env!("CARGO_PKG_VERSION"));
+        assert_eq!(missing.level, FindingLevel::Error);
+        assert!(missing.message.contains("tools/gitru is missing"));
+
+        let no_manifest = check_program(&content, &HookExec::Cargo, dir.path(), "");
+        assert_eq!(no_manifest.level, FindingLevel::Error);
+    }
+
+    #[test]
+    fn test_has_gitru_bin() {
+        let metadata = |name: &str, kind: &str| {
+            serde_json::json!({
+                "packages": [
+                    { "name": "app", "targets": [{ "name": "app", "kind": ["lib"] }] },
+                    { "name": "tools", "targets": [{ "name": name, "kind": [kind] }] },
+                ]
+            })
+        };
+
+        assert!(has_gitru_bin(&metadata("gitru", "bin")));
+        assert!(!has_gitru_bin(&metadata("gitru", "lib")));
+        assert!(!has_gitru_bin(&metadata("other", "bin")));
+        assert!(!has_gitru_bin(&serde_json::json!({})));
+    }
+
+    #[cfg(target_family = "unix")]
+    #[test]
+    fn test_hook_search_dirs() {
+        let dirs = hook_search_dirs(
+            OsStr::new("/usr/local/bin:/usr/bin"),
+            None,
+            Some("/home/me".into()),
+        );
+        assert_eq!(
+            dirs,
+            [
+                PathBuf::from("/usr/local/bin"),
+                PathBuf::from("/usr/bin"),
+                PathBuf::from("/home/me/.cargo/bin"),
+            ]
+        );
+
+        let dirs = hook_search_dirs(OsStr::new("/usr/bin"), Some("/opt/cargo".into()), None);
+        assert_eq!(dirs.last(), Some(&PathBuf::from("/opt/cargo/bin")));
+    }
+
+    #[test]
+    fn test_program_exec() {
+        assert_eq!(
+            program_exec("VALIDATOR_SCRIPT=\"/usr/bin/gitru\"\n"),
+            Some(PathBuf::from("/usr/bin/gitru"))
+        );
+        assert_eq!(
+            program_exec("VALIDATOR_SCRIPT=\"{{program_exec}}\"\n"),
+            None
+        );
+        assert_eq!(program_exec("#!/bin/sh\n"), None);
+    }
+}
@@ -0,0 +1,314 @@
+//! Installing and removing hook scripts, the same way for every hook
+//!
+//! The script of a hook runs `gitru run <hook>`, see [`hook_content`]. An existing hook that
+//! gitru did not write is kept as `<hook>.local` with `--chain`, and ownership markers protect
+//! foreign and modified hooks, see [`crate::hook::marker`].
+
+use crate::constant::hook_content;
+use crate::error::hook_error::HookError;
+use crate::hook::kind::{ChainOrder, HookExec, HookKind};
+use crate::hook::marker::{HookOwnership, inspect, mark, marker_fields};
+use crate::util::colored_print::{print_info, print_success, print_warning};
+use crate::util::git_path::{GitKind, HooksDir};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The existing hook kept by `install --chain`, next to the gitru hook: `<hook>.local`
+pub fn local_hook_path(hooks_dir: &Path, hook: HookKind) -> PathBuf {
+    hooks_dir.join(format!("{}.local", hook))
+}
+
+/// Options of `gitru install`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InstallOptions {
+    /// Replace a hook that gitru did not write or that was modified
+    pub force: bool,
+    /// Keep an existing hook as `<hook>.local` and run it in this order
+    pub chain: Option<ChainOrder>,
+    /// How the hook finds gitru, the one of the installed hook or the default when `None`
+    pub exec: Option<HookExec>,
+}
+
+/// How a hook is installed in a hooks directory
+#[derive(Debug, Clone, PartialEq)]
+pub struct HookStatus {
+    pub hook: HookKind,
+    pub path: PathBuf,
+    /// `None` when there is no hook
+    pub ownership: Option<HookOwnership>,
+    /// How a gitru hook finds gitru
+    pub exec: Option<HookExec>,
+    /// When the hook chained by a gitru hook runs
+    pub chain: Option<ChainOrder>,
+}
+
+impl HookStatus {
+    /// Short description for `gitru status`
+    pub fn describe(&self) -> String {
+        let version = match &self.ownership {
+            None => return "not installed".to_string(),
+            Some(HookOwnership::Foreign) => return "installed, not by gitru".to_string(),
+            Some(HookOwnership::Legacy) => "an older gitru".to_string(),
+            Some(HookOwnership::Gitru { version, .. }) => format!("gitru {}", version),
+        };
+
+        let mut description = format!(
+            "installed by {} (exec: {}",
+            version,
+            self.exec.clone().unwrap_or_default()
+        );
+        if let Some(chain) = self.chain {
+            description.push_str(&format!(
+                ", runs {}.local {} gitru",
+                self.hook,
+                chain.as_str()
+            ));
+        }
+        description.push(')');
+        if matches!(
+            self.ownership,
+            Some(HookOwnership::Gitru { modified: true, .. })
+        ) {
+            description.push_str(", modified since");
+        }
+        description
+    }
+}
+
+/// Status of `hook` in `hooks_dir`
+pub fn status(hooks_dir: &Path, hook: HookKind) -> Result<HookStatus, HookError> {
+    let path = hooks_dir.join(hook.as_str());
+    let content = read_hook(&path)?;
+    let ownership = content.as_deref().map(inspect);
+    let gitru_content = content
+        .as_deref()
+        .filter(|_| ownership.as_ref().is_some_and(HookOwnership::is_gitru));
+
+    Ok(HookStatus {
+        hook,
+        path,
+        exec: gitru_content.map(|c| installed_exec(c).unwrap_or_default()),
+        chain: gitru_content.and_then(chained_order),
+        ownership,
+    })
+}
+
+/// Install `hook` into the hooks directory of `git_kind`. With `chain`, an existing hook that
+/// gitru did not write is kept as `<hook>.local` and run before or after gitru.
+///
+/// `exec` selects how the hook finds gitru, recorded in its marker line.
+///
+/// Unmodified hooks of any gitru version are upgraded in place; other hooks are only
+/// replaced with `force`, see [`crate::hook::marker`].
+pub fn install(
+    git_kind: &GitKind,
+    hook: HookKind,
+    options: &InstallOptions,
+) -> Result<(), HookError> {
+    let InstallOptions { force, chain, exec } = options.clone();
+    let hooks_dir = git_kind.hooks_dir();
+    let hook_path = hooks_dir.path.join(hook.as_str());
+    let local_path = local_hook_path(&hooks_dir.path, hook);
+    warn_hooks_dir_in_worktree(&hooks_dir, git_kind.workdir());
+
+    let existing = read_hook(&hook_path)?;
+    let ownership = existing.as_deref().map(inspect);
+
+    match &ownership {
+        None => {}
+        Some(HookOwnership::Foreign) if chain.is_some() => {
+            if local_path.exists() {
+                return Err(HookError::LocalHookExists {
+                    hook,
+                    path: local_path,
+                });
+            }
+            fs::rename(&hook_path, &local_path)
+                .map_err(|e| HookError::io(format!("move the existing {} hook", hook), e))?;
+        }
+        Some(HookOwnership::Foreign) if !force => {
+            return Err(HookError::HookExists {
+                hook,
+                path: hook_path,
+            });
+        }
+        Some(HookOwnership::Gitru { modified: true, .. }) if !force => {
+            return Err(HookError::HookModified {
+                hook,
+                path: hook_path,
+            });
+        }
+        Some(_) => {}
+    }
+
+    // reinstalling keeps chaining with the hook moved aside by an earlier install,
+    // and the way it finds gitru
+    let previous = existing
+        .as_deref()
+        .filter(|_| ownership.as_ref().is_some_and(HookOwnership::is_gitru));
+    let chain = chain.or_else(|| previous.and_then(chained_order));
+    let exec = exec
+        .or_else(|| previous.and_then(installed_exec))
+        .unwrap_or_default();
+
+    // a configured core.hooksPath may not exist yet
+    fs::create_dir_all(&hooks_dir.path).map_err(|e| {
+        HookError::io(
+            format!("create hooks directory {}", hooks_dir.path.display()),
+            e,
+        )
+    })?;
+
+    let content = mark(
+        &hook_content(hook, chain.map(|c| c.as_str()).unwrap_or_default(), &exec),
+        &[("exec", &exec.to_string())],
+    );
+    fs::write(&hook_path, content).map_err(|e| HookError::io(format!("write {} hook", hook), e))?;
+
+    #[cfg(target_family = "unix")]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&hook_path, fs::Permissions::from_mode(0o755))
+            .map_err(|e| HookError::io(format!("set {} hook permissions", hook), e))?;
+    }
+
+    match ownership {
+        Some(HookOwnership::Legacy) => print_info(&format!(
+            "upgraded the {} hook written by an older gitru",
+            hook
+        )),
+        Some(HookOwnership::Gitru { version, .. }) if version != env!("CARGO_PKG_VERSION") => {
+            print_info(&format!(
+                "upgraded the {} hook written by gitru {}",
+                hook, version
+            ))
+        }
+        _ => {}
+    }
+    print_success(&format!(
+        "{} hook has been installed at {}{} (exec: {})",
+        hook,
+        hook_path.display(),
+        hooks_path_note(&hooks_dir),
+        exec
+    ));
+    if let HookExec::Repo(path) = &exec
+        && !git_kind.workdir().join(path).is_file()
+    {
+        print_warning(&format!(
+            "{} does not exist yet, commits fail until it is added to the repository",
+            path
+        ));
+    }
+    if let Some(chain) = chain
+        && local_path.exists()
+    {
+        print_info(&format!(
+            "{} runs {} gitru",
+            local_path.display(),
+            chain.as_str()
+        ));
+    }
+    Ok(())
+}
+
+/// Remove `hook` from `git_kind`, putting back the hook it was chained with.
+/// Hooks that gitru did not write, or that were modified since, are only removed with `force`.
+pub fn uninstall(git_kind: &GitKind, hook: HookKind, force: bool) -> Result<(), HookError> {
+    let hooks_dir = git_kind.hooks_dir();
+    let hook_path = hooks_dir.path.join(hook.as_str());
+    let local_path = local_hook_path(&hooks_dir.path, hook);
+    warn_hooks_dir_in_worktree(&hooks_dir, git_kind.workdir());
+
+    let Some(existing) = read_hook(&hook_path)? else {
+        print_warning(&format!(
+            "unable to find {} hook in {}{}",
+            hook,
+            hooks_dir.path.display(),
+            hooks_path_note(&hooks_dir)
+        ));
+        return Ok(());
+    };
+
+    let ownership = inspect(&existing);
+    if !ownership.is_pristine() && !force {
+        let reason = match ownership {
+            HookOwnership::Foreign => "was not installed by gitru",
+            HookOwnership::Legacy => {
+                "has no gitru marker (written by an older gitru? `gitru install` upgrades it)"
+            }
+            HookOwnership::Gitru { .. } => "was modified after gitru installed it",
+        };
+        return Err(HookError::HookNotRemovable {
+            hook,
+            path: hook_path,
+            reason,
+        });
+    }
+
+    fs::remove_file(&hook_path).map_err(|e| HookError::io(format!("remove {} hook", hook), e))?;
+    print_success(&format!(
+        "{} hook has been uninstalled at {}{}",
+        hook,
+        hook_path.display(),
+        hooks_path_note(&hooks_dir)
+    ));
+
+    // a rename keeps the original content and permissions
+    if ownership.is_gitru() && chained_order(&existing).is_some() && local_path.exists() {
+        fs::rename(&local_path, &hook_path)
+            .map_err(|e| HookError::io(format!("restore the original {} hook", hook), e))?;
+        print_success(&format!(
+            "the original {} hook has been restored from {}",
+            hook,
+            local_path.display()
+        ));
+    }
+
+    Ok(())
+}
+
+/// Content of the hook at `path`, `None` when there is none. Hooks may be binaries,
+/// invalid UTF-8 is replaced.
+pub(crate) fn read_hook(path: &Path) -> Result<Option<String>, HookError> {
+    match fs::read(path) {
+        Ok(bytes) => Ok(Some(String::from_utf8_lossy(&bytes).into_owned())),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(HookError::io(format!("read hook {}", path.display()), e)),
+    }
+}
+
+/// The `CHAIN_ORDER` of a gitru hook script, `None` when it is not chained
+pub(crate) fn chained_order(content: &str) -> Option<ChainOrder> {
+    content.lines().find_map(|line| {
+        let value = line.strip_prefix("CHAIN_ORDER=\"")?.strip_suffix('"')?;
+        ChainOrder::parse(value)
+    })
+}
+
+/// How a gitru hook script finds gitru, from its marker line. `None` for hooks written
+/// before `--exec`, which behave like [`HookExec::Absolute`].
+pub(crate) fn installed_exec(content: &str) -> Option<HookExec> {
+    marker_fields(content)?.get("exec")?.parse().ok()
+}
+
+/// ` (core.hooksPath = <value>)` when the hooks directory is configured
+pub(crate) fn hooks_path_note(hooks_dir: &HooksDir) -> String {
+    hooks_dir
+        .hooks_path
+        .as_ref()
+        .map(|value| format!(" (core.hooksPath = {})", value))
+        .unwrap_or_default()
+}
+
+/// Hooks installed into a directory of the working tree are committed and shared,
+/// or left as untracked changes
+fn warn_hooks_dir_in_worktree(hooks_dir: &HooksDir, workdir: &Path) {
+    if hooks_dir.is_in_worktree(workdir) {
+        print_warning(&format!(
+            "hooks directory {} (core.hooksPath) is inside the working tree: \
+             changes to its hooks apply to everyone who commits them",
+            hooks_dir.path.display()
+        ));
+    }
+}
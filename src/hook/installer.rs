@@ -0,0 +1,175 @@
+//! The lifecycle of each hook: init, install, uninstall and status
+//!
+//! Every hook is installed the same way, see [`crate::hook::install`]; they differ in what
+//! `gitru init` sets up for them. Get the installer of a hook with [`installer`].
+
+use crate::config::discovery::discover_rule_file;
+use crate::config::manifest::HOOK_MANIFEST_PATH;
+use crate::error::hook_error::HookError;
+use crate::hook::commit_msg;
+use crate::hook::install::{self, HookStatus, InstallOptions};
+use crate::hook::kind::HookKind;
+use crate::util::colored_print::print_info;
+use crate::util::git_path::GitKind;
+
+/// Options of `gitru init`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InitOptions {
+    /// Overwrite the configuration that exists already
+    pub force: bool,
+    /// Rule preset of the rule file, `conventional` by default
+    pub preset: Option<String>,
+}
+
+/// Sets up, installs and removes one hook in a repository
+pub trait HookInstaller {
+    /// The hook this installer handles
+    fn kind(&self) -> HookKind;
+
+    /// Write the configuration the hook needs to the repository
+    fn init(&self, git_kind: &GitKind, options: &InitOptions) -> Result<(), HookError>;
+
+    /// Write the hook script into the hooks directory
+    fn install(&self, git_kind: &GitKind, options: &InstallOptions) -> Result<(), HookError> {
+        install::install(git_kind, self.kind(), options)
+    }
+
+    /// Remove the hook script, putting back the hook it was chained with
+    fn uninstall(&self, git_kind: &GitKind, force: bool) -> Result<(), HookError> {
+        install::uninstall(git_kind, self.kind(), force)
+    }
+
+    /// How the hook is installed
+    fn status(&self, git_kind: &GitKind) -> Result<HookStatus, HookError> {
+        install::status(&git_kind.hooks_dir().path, self.kind())
+    }
+}
+
+/// commit-msg: validates the message with the rule file, which init writes
+pub struct CommitMsgInstaller;
+
+impl HookInstaller for CommitMsgInstaller {
+    fn kind(&self) -> HookKind {
+        HookKind::CommitMsg
+    }
+
+    fn init(&self, git_kind: &GitKind, options: &InitOptions) -> Result<(), HookError> {
+        commit_msg::init(git_kind, options.force, options.preset.as_deref())
+    }
+}
+
+/// pre-push: validates the pushed commits with the rule file of commit-msg
+pub struct PrePushInstaller;
+
+impl HookInstaller for PrePushInstaller {
+    fn kind(&self) -> HookKind {
+        HookKind::PrePush
+    }
+
+    /// Write the rule file unless the repository has one, which pre-push shares with commit-msg
+    fn init(&self, git_kind: &GitKind, options: &InitOptions) -> Result<(), HookError> {
+        if let Some(existing) = discover_rule_file(git_kind.workdir())
+            && !options.force
+        {
+            print_info(&format!(
+                "pre-push validates commits with the rule file at {}",
+                existing.display()
+            ));
+            return Ok(());
+        }
+        commit_msg::init(git_kind, options.force, options.preset.as_deref())
+    }
+}
+
+/// Any other hook: runs the commands listed in the hook manifest
+pub struct CommandHookInstaller(pub HookKind);
+
+impl HookInstaller for CommandHookInstaller {
+    fn kind(&self) -> HookKind {
+        self.0
+    }
+
+    fn init(&self, _git_kind: &GitKind, _options: &InitOptions) -> Result<(), HookError> {
+        print_info(&format!(
+            "{} has no rule file, list its commands under `[hooks.{}]` in {}",
+            self.0, self.0, HOOK_MANIFEST_PATH
+        ));
+        Ok(())
+    }
+}
+
+/// The installer of `hook`
+pub fn installer(hook: HookKind) -> Box<dyn HookInstaller> {
+    match hook {
+        HookKind::CommitMsg => Box::new(CommitMsgInstaller),
+        HookKind::PrePush => Box::new(PrePushInstaller),
+        other => Box::new(CommandHookInstaller(other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hook::kind::HookExec;
+    use crate::hook::marker::HookOwnership;
+    use crate::util::git_path::detect_git_kind;
+    use std::fs;
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    #[test]
+    #[ignore]
+    fn test_installer_lifecycle() {
+        let dir = TempDir::new().unwrap();
+        assert!(
+            Command::new("git")
+                .arg("init")
+                .arg(dir.path())
+                .output()
+                .unwrap()
+                .status
+                .success()
+        );
+        let git_kind = detect_git_kind(dir.path()).unwrap();
+        let commit_msg = installer(HookKind::CommitMsg);
+
+        commit_msg.init(&git_kind, &InitOptions::default()).unwrap();
+        assert!(matches!(
+            commit_msg.init(&git_kind, &InitOptions::default()),
+            Err(HookError::RuleFileExists { .. })
+        ));
+        // pre-push shares the rule file
+        installer(HookKind::PrePush)
+            .init(&git_kind, &InitOptions::default())
+            .unwrap();
+
+        assert_eq!(commit_msg.status(&git_kind).unwrap().ownership, None);
+        let options = InstallOptions {
+            exec: Some(HookExec::Path),
+            ..Default::default()
+        };
+        commit_msg.install(&git_kind, &options).unwrap();
+        let status = commit_msg.status(&git_kind).unwrap();
+        assert!(
+            status
+                .ownership
+                .as_ref()
+                .is_some_and(HookOwnership::is_pristine)
+        );
+        assert_eq!(status.exec, Some(HookExec::Path));
+        commit_msg.uninstall(&git_kind, false).unwrap();
+
+        fs::write(&status.path, "#!/bin/sh\nexit 0\n").unwrap();
+        assert!(matches!(
+            commit_msg.install(&git_kind, &options),
+            Err(HookError::HookExists {
+                hook: HookKind::CommitMsg,
+                ..
+            })
+        ));
+        assert!(matches!(
+            commit_msg.uninstall(&git_kind, false),
+            Err(HookError::HookNotRemovable { .. })
+        ));
+    }
+}
@@ -0,0 +1,248 @@
+//! The hooks gitru installs and how their scripts run: [`HookKind`], [`ChainOrder`] and
+//! [`HookExec`], shared by the command line, the hook manifest and the hook scripts
+
+use crate::error::hook_error::HookError;
+use crate::util::similarity::{SpellcheckOptions, suggest};
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+/// A client-side git hook gitru can install and run, in the order git runs them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, ValueEnum)]
+pub enum HookKind {
+    PreCommit,
+    PrepareCommitMsg,
+    /// Validates the commit message
+    CommitMsg,
+    PostCommit,
+    PreRebase,
+    PostCheckout,
+    PostMerge,
+    /// Validates the message of every pushed commit
+    PrePush,
+}
+
+impl HookKind {
+    pub const ALL: [HookKind; 8] = [
+        HookKind::PreCommit,
+        HookKind::PrepareCommitMsg,
+        HookKind::CommitMsg,
+        HookKind::PostCommit,
+        HookKind::PreRebase,
+        HookKind::PostCheckout,
+        HookKind::PostMerge,
+        HookKind::PrePush,
+    ];
+
+    /// File name of the hook in the hooks directory
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HookKind::PreCommit => "pre-commit",
+            HookKind::PrepareCommitMsg => "prepare-commit-msg",
+            HookKind::CommitMsg => "commit-msg",
+            HookKind::PostCommit => "post-commit",
+            HookKind::PreRebase => "pre-rebase",
+            HookKind::PostCheckout => "post-checkout",
+            HookKind::PostMerge => "post-merge",
+            HookKind::PrePush => "pre-push",
+        }
+    }
+
+    /// git writes input to the hook on stdin
+    pub fn reads_stdin(&self) -> bool {
+        *self == HookKind::PrePush
+    }
+
+    /// Commands of the hook may edit the commit message, so they run one after another
+    pub fn edits_message(&self) -> bool {
+        matches!(self, HookKind::PrepareCommitMsg | HookKind::CommitMsg)
+    }
+
+    /// The hook runs while a commit is being created, with the files of the commit staged.
+    /// Only its commands can use `glob` and `{files}`.
+    pub fn has_staged_files(&self) -> bool {
+        matches!(
+            self,
+            HookKind::PreCommit | HookKind::PrepareCommitMsg | HookKind::CommitMsg
+        )
+    }
+}
+
+impl fmt::Display for HookKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for HookKind {
+    type Err = HookError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        if let Some(kind) = HookKind::ALL.into_iter().find(|k| k.as_str() == value) {
+            return Ok(kind);
+        }
+
+        let names: Vec<String> = HookKind::ALL.iter().map(|k| k.to_string()).collect();
+        let options = SpellcheckOptions::with_threshold(HOOK_SUGGESTION_THRESHOLD);
+        Err(HookError::UnknownHook {
+            name: value.to_string(),
+            suggestion: suggest(value, &names, &options)
+                .into_iter()
+                .next()
+                .map(|s| s.candidate),
+        })
+    }
+}
+
+/// Minimum similarity for a misspelled hook name to get a suggestion
+const HOOK_SUGGESTION_THRESHOLD: f64 = 0.6;
+
+/// When a chained hook runs relative to gitru, see `install --chain`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChainOrder {
+    Before,
+    After,
+}
+
+impl ChainOrder {
+    /// Value of `CHAIN_ORDER` in the hook script
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChainOrder::Before => "before",
+            ChainOrder::After => "after",
+        }
+    }
+
+    /// Parse the `CHAIN_ORDER` value of a hook script
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "before" => Some(ChainOrder::Before),
+            "after" => Some(ChainOrder::After),
+            _ => None,
+        }
+    }
+}
+
+/// How a hook script finds gitru, see `install --exec`. Displayed and parsed as the
+/// strategy name, `repo:<path>` for a repository binary.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum HookExec {
+    /// The binary that installed the hook, falling back to `gitru` on `PATH` when it moved
+    #[default]
+    Absolute,
+    /// `gitru` on `PATH`, including Cargo's bin directory
+    Path,
+    /// A binary kept in the repository, relative to its root, e.g. `./tools/gitru`
+    Repo(String),
+    /// `cargo run --bin gitru` in the repository, for workspaces that build gitru
+    Cargo,
+}
+
+impl HookExec {
+    /// Binary of [`HookExec::Repo`] when no path is given
+    pub const DEFAULT_REPO_PATH: &'static str = "./tools/gitru";
+
+    /// Value of `EXEC_STRATEGY` in the hook script
+    pub fn strategy(&self) -> &'static str {
+        match self {
+            HookExec::Absolute => "absolute",
+            HookExec::Path => "path",
+            HookExec::Repo(_) => "repo",
+            HookExec::Cargo => "cargo",
+        }
+    }
+}
+
+impl fmt::Display for HookExec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HookExec::Repo(path) => write!(f, "repo:{}", path),
+            _ => f.write_str(self.strategy()),
+        }
+    }
+}
+
+impl FromStr for HookExec {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.split_once(':') {
+            None if value == "absolute" => Ok(HookExec::Absolute),
+            None if value == "path" => Ok(HookExec::Path),
+            None if value == "cargo" => Ok(HookExec::Cargo),
+            None if value == "repo" => Ok(HookExec::Repo(Self::DEFAULT_REPO_PATH.to_string())),
+            Some(("repo", path)) => {
+                // recorded in the marker line, whose fields are separated by whitespace
+                if path.is_empty() || path.contains(char::is_whitespace) || path.contains('"') {
+                    return Err(format!("invalid repository path `{}`", path));
+                }
+                if Path::new(path).is_absolute() {
+                    return Err(format!(
+                        "`{}` is absolute, repo paths are relative to the repository root",
+                        path
+                    ));
+                }
+                Ok(HookExec::Repo(if path.contains('/') {
+                    path.to_string()
+                } else {
+                    format!("./{}", path)
+                }))
+            }
+            _ => Err(format!(
+                "unknown strategy `{}`, expected absolute, path, repo[:<path>] or cargo",
+                value
+            )),
+        }
+    }
+}
+
+impl TryFrom<String> for HookExec {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hook_exec() {
+        assert_eq!("path".parse(), Ok(HookExec::Path));
+        assert_eq!(
+            "repo".parse(),
+            Ok(HookExec::Repo(HookExec::DEFAULT_REPO_PATH.to_string()))
+        );
+        assert_eq!(
+            "repo:bin/gitru".parse::<HookExec>().map(|e| e.to_string()),
+            Ok("repo:bin/gitru".to_string())
+        );
+        assert_eq!(
+            "repo:gitru".parse(),
+            Ok(HookExec::Repo("./gitru".to_string()))
+        );
+        assert!("repo:/usr/bin/gitru".parse::<HookExec>().is_err());
+        assert!("repo:my tools/gitru".parse::<HookExec>().is_err());
+        assert!("nix".parse::<HookExec>().is_err());
+    }
+
+    #[test]
+    fn test_parse_hook_kind() {
+        for kind in HookKind::ALL {
+            assert_eq!(kind.as_str().parse::<HookKind>().unwrap(), kind);
+            // the names of clap and of git agree
+            assert_eq!(kind.to_possible_value().unwrap().get_name(), kind.as_str());
+        }
+
+        let err = "comit-msg".parse::<HookKind>().unwrap_err();
+        assert!(err.to_string().contains("did you mean `commit-msg`?"));
+        let err = "post-receive".parse::<HookKind>().unwrap_err();
+        assert!(err.to_string().contains("available hooks: pre-commit,"));
+    }
+}
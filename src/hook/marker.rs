@@ -0,0 +1,162 @@
+//! Ownership marker of the hook scripts written by gitru
+//!
+//! Every generated hook carries a marker line after the shebang:
+//!
+//! ```sh
+//! # gitru-hook: version=0.2.11 hash=9f2c4e1a7b3d5f60
+//! ```
+//!
+//! `hash` covers the script without the marker line, so a hook edited by hand no longer
+//! matches it. `install` upgrades hooks of other gitru versions and `uninstall` only deletes
+//! unmodified gitru hooks unless forced.
+
+use std::collections::BTreeMap;
+
+/// Start of the marker line
+const MARKER_PREFIX: &str = "# gitru-hook:";
+
+/// Line of the hook template of gitru versions without a marker
+const LEGACY_NOTE: &str = "This file is initialized by gitru";
+
+/// Who wrote a hook script
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HookOwnership {
+    /// Not written by gitru, e.g. a Gerrit or husky hook
+    Foreign,
+    /// Written by a gitru version without markers, modifications cannot be detected
+    Legacy,
+    /// Written by gitru `version`, `modified` by hand since
+    Gitru { version: String, modified: bool },
+}
+
+impl HookOwnership {
+    /// Whether gitru wrote the hook, possibly an older version
+    pub fn is_gitru(&self) -> bool {
+        !matches!(self, HookOwnership::Foreign)
+    }
+
+    /// Whether the hook can be replaced or removed without `--force`
+    pub fn is_pristine(&self) -> bool {
+        matches!(
+            self,
+            HookOwnership::Gitru {
+                modified: false,
+                ..
+            }
+        )
+    }
+}
+
+/// Fields of the marker line, e.g. `version`
+pub type MarkerFields = BTreeMap<String, String>;
+
+/// Add the marker line to a generated hook script, after the shebang.
+/// `fields` are recorded in addition to `version` and `hash`.
+pub fn mark(content: &str, fields: &[(&str, &str)]) -> String {
+    let mut marker = format!("{} version={}", MARKER_PREFIX, env!("CARGO_PKG_VERSION"));
+    for (key, value) in fields {
+        marker.push_str(&format!(" {}={}", key, value));
+    }
+    marker.push_str(&format!(" hash={}", content_hash(content)));
+
+    match content.split_once('\n') {
+        Some((shebang, rest)) if shebang.starts_with("#!") => {
+            format!("{}\n{}\n{}", shebang, marker, rest)
+        }
+        _ => format!("{}\n{}", marker, content),
+    }
+}
+
+/// The fields of the marker line of a hook script, `None` without one
+pub fn marker_fields(content: &str) -> Option<MarkerFields> {
+    let line = content
+        .lines()
+        .find_map(|line| line.strip_prefix(MARKER_PREFIX))?;
+
+    Some(
+        line.split_whitespace()
+            .filter_map(|field| field.split_once('='))
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect(),
+    )
+}
+
+/// Who wrote a hook script
+pub fn inspect(content: &str) -> HookOwnership {
+    let Some(fields) = marker_fields(content) else {
+        return if content.contains(LEGACY_NOTE) {
+            HookOwnership::Legacy
+        } else {
+            HookOwnership::Foreign
+        };
+    };
+
+    let unmarked: String = content
+        .split_inclusive('\n')
+        .filter(|line| !line.starts_with(MARKER_PREFIX))
+        .collect();
+
+    HookOwnership::Gitru {
+        version: fields.get("version").cloned().unwrap_or_default(),
+        modified: fields.get("hash") != Some(&content_hash(&unmarked)),
+    }
+}
+
+/// FNV-1a 64 of `content` in hex. Stable across Rust versions and platforms, unlike
+/// `std::hash`; it detects edits, it is not meant to resist tampering.
+fn content_hash(content: &str) -> String {
+    let hash = content
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+        });
+    format!("{:016x}", hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCRIPT: &str =
+        "#!/bin/sh\n# This file is initialized by gitru\ngitru run commit-msg --msg \"$1\"\n";
+
+    #[test]
+    fn test_mark_and_inspect() {
+        let marked = mark(SCRIPT, &[("exec", "path")]);
+        assert!(marked.starts_with("#!/bin/sh\n# gitru-hook: version="));
+
+        let fields = marker_fields(&marked).unwrap();
+        assert_eq!(fields["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(fields["exec"], "path");
+        assert_eq!(
+            inspect(&marked),
+            HookOwnership::Gitru {
+                version: env!("CARGO_PKG_VERSION").to_string(),
+                modified: false
+            }
+        );
+
+        let edited = marked.replace("--msg", "--strict --msg");
+        assert!(matches!(
+            inspect(&edited),
+            HookOwnership::Gitru { modified: true, .. }
+        ));
+    }
+
+    #[test]
+    fn test_unmarked_hooks() {
+        assert_eq!(inspect(SCRIPT), HookOwnership::Legacy);
+        assert_eq!(
+            inspect("#!/bin/sh\necho \"Change-Id: I123\" >> \"$1\"\n"),
+            HookOwnership::Foreign
+        );
+        assert!(!HookOwnership::Legacy.is_pristine());
+        assert!(HookOwnership::Legacy.is_gitru());
+    }
+
+    #[test]
+    fn test_content_hash_is_stable() {
+        assert_eq!(content_hash(""), "cbf29ce484222325");
+        assert_eq!(content_hash("a"), "af63dc4c8601ec8c");
+    }
+}
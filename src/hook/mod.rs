@@ -1,67 +1,270 @@
 use crate::cli::RunCmd;
-use crate::util::colored_print::{print_error, print_success};
+use crate::config::overrides::{OutputFormat, RunOverrides};
+use crate::config::user::UserConfig;
+use crate::error::hook_error::HookError;
+use crate::hook::commit_msg::RunOutcome;
+use crate::hook::install::InstallOptions;
+use crate::hook::installer::{InitOptions, installer};
+use crate::hook::kind::{ChainOrder, HookExec, HookKind};
+use crate::util::colored_print::{print_error, print_info, print_success, print_warning};
+use crate::util::git_path::{GitKind, detect_current_repo, detect_recursive};
+use serde::Serialize;
+use std::io::Read;
+use std::path::{Path, PathBuf};
 
 pub mod commit_msg;
+pub mod config;
+pub mod doctor;
+pub mod install;
+pub mod installer;
+pub mod kind;
+pub mod marker;
+pub mod pre_push;
+pub mod runner;
+pub mod sync;
 
-pub fn init(hook: &str, force: bool) -> Result<(), String> {
-    // write files to the current project root based on the hook type
-    match hook {
-        "commit-msg" => {
-            commit_msg::init(force)?;
-        }
-        _ => {
-            return Err(
-                std::io::Error::new(std::io::ErrorKind::InvalidInput, "unknown hook").to_string(),
-            );
+/// Write the configuration `hook` needs to the current repository, see [`installer`]
+pub fn init(hook: HookKind, force: bool, preset: Option<&str>) -> Result<(), HookError> {
+    let git_kind = detect_current_repo()?;
+    let options = InitOptions {
+        force,
+        preset: preset.map(str::to_string),
+    };
+    installer(hook).init(&git_kind, &options)
+}
+
+pub fn install(
+    hook: HookKind,
+    force: bool,
+    chain: Option<ChainOrder>,
+    exec: Option<&HookExec>,
+    recursive: bool,
+) -> Result<(), HookError> {
+    let options = InstallOptions {
+        force,
+        chain,
+        exec: exec.cloned(),
+    };
+    let installer = installer(hook);
+    in_repositories(&format!("{} hook installed", hook), recursive, |git_kind| {
+        installer.install(git_kind, &options)
+    })
+}
+
+pub fn uninstall(hook: HookKind, force: bool, recursive: bool) -> Result<(), HookError> {
+    let installer = installer(hook);
+    in_repositories(
+        &format!("{} hook uninstalled", hook),
+        recursive,
+        |git_kind| installer.uninstall(git_kind, force),
+    )
+}
+
+/// Print how every hook is installed in the current repository
+pub fn status() -> Result<(), HookError> {
+    let git_kind = detect_current_repo()?;
+    print_info(&format!(
+        "hooks directory: {}",
+        git_kind.hooks_dir().path.display()
+    ));
+    for hook in HookKind::ALL {
+        let status = installer(hook).status(&git_kind)?;
+        match &status.ownership {
+            Some(ownership) if ownership.is_pristine() => {
+                print_success(&format!("{}: {}", hook, status.describe()))
+            }
+            Some(_) => print_warning(&format!("{}: {}", hook, status.describe())),
+            None => print_info(&format!("{}: {}", hook, status.describe())),
         }
     }
-
     Ok(())
 }
 
-pub fn install(hook: &str, force: bool) -> Result<(), String> {
-    match hook {
-        "commit-msg" => {
-            commit_msg::install(force)?;
+/// Install, update and remove the hooks of the current repository to match its hook manifest,
+/// see [`sync::sync`]
+pub fn sync(force: bool) -> Result<(), HookError> {
+    let git_kind = detect_current_repo()?;
+    sync::sync(&git_kind, force)
+}
+
+/// Check that the hooks of the current repository match its hook manifest
+pub fn check_installed() -> Result<(), HookError> {
+    let git_kind = detect_current_repo()?;
+    sync::check_installed(&git_kind)
+}
+
+/// Run `action` on the current repository, or with `recursive` on the repository with its
+/// worktrees and submodules (see [`detect_recursive`]), once per hooks directory, then print
+/// a summary prefixed with `done`.
+fn in_repositories(
+    done: &str,
+    recursive: bool,
+    mut action: impl FnMut(&GitKind) -> Result<(), HookError>,
+) -> Result<(), HookError> {
+    if !recursive {
+        let git_kind = detect_current_repo()?;
+        return action(&git_kind);
+    }
+
+    let repos = detect_recursive(".")?;
+    // worktrees share the hooks directory of their main repository
+    let mut done_dirs: Vec<(PathBuf, &Path)> = Vec::new();
+    let mut failed = Vec::new();
+    for git_kind in &repos {
+        let workdir = git_kind.workdir();
+        let hooks_dir = git_kind.hooks_dir().path;
+        if let Some((_, first)) = done_dirs.iter().find(|(dir, _)| *dir == hooks_dir) {
+            print_info(&format!(
+                "{} {}: shares the hooks directory of {}",
+                git_kind.name(),
+                workdir.display(),
+                first.display()
+            ));
+            continue;
         }
-        _ => {
-            return Err(
-                std::io::Error::new(std::io::ErrorKind::InvalidInput, "unknown hook").to_string(),
-            );
+
+        print_info(&format!("{} {}:", git_kind.name(), workdir.display()));
+        match action(git_kind) {
+            Ok(()) => done_dirs.push((hooks_dir, workdir)),
+            Err(e) => {
+                print_error(&e.to_string());
+                failed.push(workdir.display().to_string());
+            }
         }
     }
 
-    Ok(())
+    let total = done_dirs.len() + failed.len();
+    if failed.is_empty() {
+        print_success(&format!(
+            "{} in {} of {} repositories",
+            done,
+            done_dirs.len(),
+            total
+        ));
+        Ok(())
+    } else {
+        Err(HookError::RepositoriesFailed {
+            done: done.to_string(),
+            succeeded: done_dirs.len(),
+            total,
+            failed,
+        })
+    }
 }
 
-pub fn uninstall(hook: &str) -> Result<(), String> {
+/// Run `hook` with the arguments git passed to it. `Ok(false)` when it blocks the git operation,
+/// after reporting why.
+pub fn run_hook(hook: &RunCmd, user: Option<&UserConfig>) -> Result<bool, HookError> {
     match hook {
-        "commit-msg" => {
-            commit_msg::uninstall()?;
+        RunCmd::CommitMsg { msg, rule, strict } => {
+            let overrides = RunOverrides::load(user)?;
+            let strict = overrides.strict_or(*strict);
+            let mut warnings = user.map(UserConfig::warnings).unwrap_or_default();
+
+            let result =
+                commit_msg::run(msg, rule.as_ref(), &overrides, strict, user, &mut warnings);
+            report_run("commit-msg", &result, &warnings, overrides.output_format());
+
+            if result.is_err() {
+                return Ok(false);
+            }
+
+            // the commands of the manifest run once the message is valid
+            runner::run_commands(HookKind::CommitMsg, &[msg.display().to_string()])?;
+            Ok(true)
+        }
+        RunCmd::PrePush {
+            remote,
+            url,
+            rule,
+            strict,
+        } => {
+            let overrides = RunOverrides::load(user)?;
+            let strict = overrides.strict_or(*strict);
+
+            // git writes the pushed refs once, both the validation and the commands need them
+            let mut input = String::new();
+            std::io::stdin()
+                .read_to_string(&mut input)
+                .map_err(|e| HookError::io("read the input of pre-push", e))?;
+
+            pre_push::validate(
+                &input,
+                remote.as_deref(),
+                rule.as_ref(),
+                &overrides,
+                strict,
+                user,
+            )?;
+            let args: Vec<String> = remote.iter().chain(url).cloned().collect();
+            runner::run_commands_with_input(HookKind::PrePush, &args, &input)?;
+            Ok(true)
         }
-        _ => {
-            return Err(
-                std::io::Error::new(std::io::ErrorKind::InvalidInput, "unknown hook").to_string(),
-            );
+        RunCmd::Hook(args) => {
+            let Some((hook, args)) = args.split_first() else {
+                return Err(HookError::MissingHookName);
+            };
+            let hook: HookKind = hook.parse()?;
+            runner::run_commands(hook, args)?;
+            Ok(true)
         }
     }
+}
 
-    Ok(())
+/// Machine-readable hook result, printed with `GITRU_FORMAT=json`
+#[derive(Serialize)]
+struct RunReport<'a> {
+    hook: &'a str,
+    status: &'a str,
+    message: Option<String>,
+    warnings: &'a [String],
 }
 
-pub fn run_hook(hook: &RunCmd) -> Result<(), String> {
-    match hook {
-        RunCmd::CommitMsg { msg, rule } => {
-            if let Err(e) = commit_msg::run(msg, rule) {
-                print_error(&e);
-                // status code 1 means fail
-                std::process::exit(1);
+/// Print the result of a hook run in the requested format.
+fn report_run(
+    hook: &str,
+    result: &Result<RunOutcome, HookError>,
+    warnings: &[String],
+    format: OutputFormat,
+) {
+    match format {
+        OutputFormat::Text => {
+            for w in warnings {
+                print_warning(w);
             }
 
-            // status code 0 means success
-            print_success("commit-msg validation passed");
-            std::process::exit(0);
+            match result {
+                Ok(RunOutcome::Passed) => print_success(&format!("{} validation passed", hook)),
+                Ok(RunOutcome::Disabled) => print_info(&format!("{} validation is disabled", hook)),
+                Ok(RunOutcome::Skipped(reason)) => {
+                    print_success(&format!("commit message is skipped validation: {}", reason))
+                }
+                Ok(RunOutcome::Accepted(kind)) => {
+                    print_success(&format!("{} commit accepted by commit_kind policy", kind))
+                }
+                Err(e) => print_error(&e.to_string()),
+            }
+        }
+
+        OutputFormat::Json => {
+            let (status, message) = match result {
+                Ok(RunOutcome::Passed) => ("passed", None),
+                Ok(RunOutcome::Disabled) => ("disabled", None),
+                Ok(RunOutcome::Skipped(reason)) => ("skipped", Some(reason.clone())),
+                Ok(RunOutcome::Accepted(kind)) => ("accepted", Some(kind.to_string())),
+                Err(e) => ("failed", Some(e.to_string())),
+            };
+
+            let report = RunReport {
+                hook,
+                status,
+                message,
+                warnings,
+            };
+            match serde_json::to_string(&report) {
+                Ok(json) => println!("{}", json),
+                Err(e) => print_error(&format!("failed to serialize report: {}", e)),
+            }
         }
-        _ => Err(format!("unsupported hook: {:?}", hook)),
     }
 }
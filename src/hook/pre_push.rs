@@ -0,0 +1,537 @@
+//! `gitru run pre-push`: validate the message of every commit a push sends
+//!
+//! git writes one line per pushed ref to the hook: `<local ref> <local sha> <remote ref>
+//! <remote sha>`. The new commits of a ref are the ones reachable from the local sha but not
+//! from the remote sha, or, when the remote sha is unknown locally (new branches, force pushes
+//! over commits never fetched), not from any ref of the remote. Deleted refs send nothing.
+//!
+//! Each commit is validated with the rule files commit-msg would have used for the paths it
+//! changes, so that `--no-verify`, rebases with `exec` and commits made by other tools cannot
+//! bypass the rules.
+
+use crate::config::commit_msg_rule::{
+    GlobalRule, NestedRulesPolicy, ParsedCommitMsgRule, load_commit_msg_rule_with_user,
+};
+use crate::config::discovery::{RuleFileOrigin, resolve_rule_file};
+use crate::config::nested::applicable_rule_files;
+use crate::config::overrides::RunOverrides;
+use crate::config::user::UserConfig;
+use crate::error::hook_error::HookError;
+use crate::hook::commit_msg::print_lint_report;
+use crate::util::colored_print::{print_info, print_success, print_warning};
+use crate::util::git_log::{CommitInfo, commit_paths, list_commits, run_git_checked};
+use crate::util::git_path::detect_current_repo;
+use crate::validator::range::{RangeLintReport, lint_commits_with_rule_files, short_sha};
+use crate::validator::skip::{SkipContext, check_skip_policy, override_skip_request, skip_policy};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// A ref being pushed, as written by git to the pre-push hook
+#[derive(Debug, Clone, PartialEq)]
+pub struct PushUpdate {
+    pub local_ref: String,
+    pub local_sha: String,
+    pub remote_ref: String,
+    pub remote_sha: String,
+}
+
+impl PushUpdate {
+    /// The push deletes the remote ref
+    pub fn is_delete(&self) -> bool {
+        is_null_sha(&self.local_sha)
+    }
+
+    /// The remote ref does not exist yet
+    pub fn is_new(&self) -> bool {
+        is_null_sha(&self.remote_sha)
+    }
+}
+
+/// The all-zero sha git uses for a missing side of an update
+fn is_null_sha(sha: &str) -> bool {
+    !sha.is_empty() && sha.bytes().all(|b| b == b'0')
+}
+
+/// Parse the input of the pre-push hook, one update per line
+pub fn parse_push_updates(input: &str) -> Result<Vec<PushUpdate>, HookError> {
+    input
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(parse_push_update)
+        .collect()
+}
+
+fn parse_push_update(line: &str) -> Result<PushUpdate, HookError> {
+    match line.split_whitespace().collect::<Vec<_>>()[..] {
+        [local_ref, local_sha, remote_ref, remote_sha] => Ok(PushUpdate {
+            local_ref: local_ref.to_string(),
+            local_sha: local_sha.to_string(),
+            remote_ref: remote_ref.to_string(),
+            remote_sha: remote_sha.to_string(),
+        }),
+        _ => Err(HookError::InvalidPushInput {
+            line: line.to_string(),
+        }),
+    }
+}
+
+/// `git log` arguments selecting the new commits of `update`, `None` for a deletion.
+/// `remote_known`: the remote sha exists locally; `exclude`: the refs of the remote.
+fn update_range(update: &PushUpdate, remote_known: bool, exclude: &str) -> Option<Vec<String>> {
+    if update.is_delete() {
+        return None;
+    }
+    if !update.is_new() && remote_known {
+        return Some(vec![format!("{}..{}", update.remote_sha, update.local_sha)]);
+    }
+    Some(vec![
+        update.local_sha.clone(),
+        "--not".to_string(),
+        exclude.to_string(),
+    ])
+}
+
+/// The new commits of a pushed ref, children before their parents
+#[derive(Debug, Clone, PartialEq)]
+struct PushedRef {
+    local_ref: String,
+    shas: Vec<String>,
+}
+
+/// The commits sent by `updates`, each once, newest first per ref, and the new commits of
+/// each ref. `remote` is the name or URL of the remote passed to the hook.
+fn pushed_commits(
+    updates: &[PushUpdate],
+    remote: Option<&str>,
+) -> Result<(Vec<CommitInfo>, Vec<PushedRef>), HookError> {
+    // a push to a URL has no remote-tracking refs, exclude the commits of every remote then
+    let remotes = run_git_checked(&["remote"])?;
+    let exclude = match remote {
+        Some(remote) if remotes.lines().any(|r| r == remote) => format!("--remotes={}", remote),
+        _ => "--remotes".to_string(),
+    };
+
+    let mut seen = HashSet::new();
+    let mut commits = Vec::new();
+    let mut refs = Vec::new();
+    for update in updates {
+        let remote_known = !update.is_new()
+            && run_git_checked(&[
+                "cat-file",
+                "-e",
+                &format!("{}^{{commit}}", update.remote_sha),
+            ])
+            .is_ok();
+        let Some(range) = update_range(update, remote_known, &exclude) else {
+            continue;
+        };
+
+        // parents after their children, so the oldest offender of a ref is its last one
+        let mut args = vec!["--topo-order"];
+        args.extend(range.iter().map(String::as_str));
+        let mut shas = Vec::new();
+        for commit in list_commits(&args)? {
+            shas.push(commit.sha.clone());
+            if seen.insert(commit.sha.clone()) {
+                commits.push(commit);
+            }
+        }
+        refs.push(PushedRef {
+            local_ref: update.local_ref.clone(),
+            shas,
+        });
+    }
+    Ok((commits, refs))
+}
+
+/// Where to reword the `failed` commits of each pushed ref: from the parent of its oldest
+/// failing commit, or from the root when that commit has no parent. `has_parent` tells
+/// whether a commit has a parent.
+fn rebase_bases(
+    refs: &[PushedRef],
+    failed: &[&str],
+    has_parent: impl Fn(&str) -> bool,
+) -> Vec<(String, String)> {
+    refs.iter()
+        .filter_map(|pushed| {
+            let oldest = pushed
+                .shas
+                .iter()
+                .rev()
+                .find(|sha| failed.contains(&sha.as_str()))?;
+            let base = if has_parent(oldest) {
+                format!("{}^", short_sha(oldest))
+            } else {
+                "--root".to_string()
+            };
+            let name = pushed
+                .local_ref
+                .strip_prefix("refs/heads/")
+                .unwrap_or(&pushed.local_ref);
+            Some((name.to_string(), base))
+        })
+        .collect()
+}
+
+/// Validate the messages of the commits sent by the push described by `input`.
+///
+/// The rule files are selected like for the commit-msg hook, see [`resolve_rule_file`] and
+/// [`crate::config::nested`], over the paths each commit changes; without one, nothing is
+/// validated. With `strict`, checks of severity `warning` block the push too.
+pub fn validate(
+    input: &str,
+    remote: Option<&str>,
+    rule_path: Option<&PathBuf>,
+    overrides: &RunOverrides,
+    strict: bool,
+    user: Option<&UserConfig>,
+) -> Result<(), HookError> {
+    let (rule_path, origin) = resolve_rule_file(rule_path, overrides)?;
+    for w in user.map(UserConfig::warnings).unwrap_or_default() {
+        print_warning(&w);
+    }
+
+    let mut rules = RangeRuleFiles::load(rule_path, &origin, user)?;
+    let root_global = rules.root_global();
+
+    // GITRU_SKIP / gitru.skip are held to the skip policy of the root rule file, like in the
+    // commit-msg hook; the pushed commits exist already, no trailer is recorded
+    if let Some(skip) = &overrides.skip
+        && let Some(request) = override_skip_request(skip)
+    {
+        check_skip_policy(
+            &request,
+            &skip_policy(root_global),
+            &SkipContext::from_git(),
+        )?;
+        print_info(&format!("pre-push validation skipped by {}", skip.source));
+        return Ok(());
+    }
+    if root_global.and_then(|g| g.enable_validation) == Some(false) {
+        print_info("pre-push validation is disabled");
+        return Ok(());
+    }
+
+    let updates = parse_push_updates(input)?;
+    let (commits, refs) = pushed_commits(&updates, remote)?;
+    if commits.is_empty() {
+        return Ok(());
+    }
+
+    rules.select(&commits, user)?;
+    if rules.is_empty() {
+        print_info("no rule file found, commit messages are not validated on push");
+        return Ok(());
+    }
+
+    let report = rules.lint(&commits, strict);
+    print_lint_report(&report);
+
+    let failed = report.failed_commits();
+    if failed.is_empty() {
+        print_success(&format!(
+            "{} pushed commit message(s) passed validation",
+            commits.len()
+        ));
+        return Ok(());
+    }
+
+    let rebases = rebase_bases(&refs, &failed, |sha| {
+        run_git_checked(&["rev-parse", "--verify", "--quiet", &format!("{}^", sha)]).is_ok()
+    });
+    Err(HookError::PushRejected {
+        shas: failed
+            .into_iter()
+            .map(|sha| short_sha(sha).to_string())
+            .collect(),
+        total: commits.len(),
+        rebases,
+    })
+}
+
+/// Rule files validating a range of commits: the root rule file, and the rule files each
+/// commit selects by the paths it changes, as the commit-msg hook does for staged paths
+pub(crate) struct RangeRuleFiles {
+    rule_path: PathBuf,
+    /// The root rule file was looked up in the repository rather than given explicitly
+    discovered: bool,
+    rule_files: HashMap<PathBuf, ParsedCommitMsgRule>,
+    /// Rule files of each commit by sha
+    selected: HashMap<String, Vec<PathBuf>>,
+}
+
+impl RangeRuleFiles {
+    /// Load the root rule file `rule_path`, found as `origin`. As for commit-msg, a
+    /// discovered one may be missing: a monorepo may keep all of its rules in subdirectories.
+    pub(crate) fn load(
+        rule_path: PathBuf,
+        origin: &RuleFileOrigin,
+        user: Option<&UserConfig>,
+    ) -> Result<Self, HookError> {
+        let discovered = matches!(origin, RuleFileOrigin::Discovered | RuleFileOrigin::Default);
+        let mut rule_files = HashMap::new();
+        if rule_path.exists() || !discovered {
+            rule_files.insert(rule_path.clone(), load_rule_file(&rule_path, user)?);
+        }
+        Ok(Self {
+            rule_path,
+            discovered,
+            rule_files,
+            selected: HashMap::new(),
+        })
+    }
+
+    /// `[global]` of the root rule file, which controls validation as a whole
+    pub(crate) fn root_global(&self) -> Option<&GlobalRule> {
+        self.rule_files
+            .get(&self.rule_path)
+            .and_then(|rule| rule.global.as_ref())
+    }
+
+    /// Select the rule files of each of `commits`, loading the ones not loaded yet
+    pub(crate) fn select(
+        &mut self,
+        commits: &[CommitInfo],
+        user: Option<&UserConfig>,
+    ) -> Result<(), HookError> {
+        let policy = self
+            .root_global()
+            .and_then(|g| g.nested_rules)
+            .unwrap_or_default();
+        let has_root = self.rule_files.contains_key(&self.rule_path);
+
+        if !self.discovered || policy == NestedRulesPolicy::Off {
+            if has_root {
+                for commit in commits {
+                    self.selected
+                        .insert(commit.sha.clone(), vec![self.rule_path.clone()]);
+                }
+            }
+            return Ok(());
+        }
+
+        let git_kind = detect_current_repo()?;
+        for commit in commits {
+            let changed = commit_paths(&commit.sha)?;
+            let paths = commit_rule_files(git_kind.workdir(), &self.rule_path, has_root, &changed);
+            for path in &paths {
+                if !self.rule_files.contains_key(path) {
+                    let rule = load_rule_file(path, user)?;
+                    self.rule_files.insert(path.clone(), rule);
+                }
+            }
+            self.selected.insert(commit.sha.clone(), paths);
+        }
+        Ok(())
+    }
+
+    /// No rule file was found, neither at the root nor for any commit
+    pub(crate) fn is_empty(&self) -> bool {
+        self.rule_files.is_empty()
+    }
+
+    /// Lint `commits` with their selected rule files
+    pub(crate) fn lint(&self, commits: &[CommitInfo], strict: bool) -> RangeLintReport {
+        let root = self
+            .rule_files
+            .get_key_value(&self.rule_path)
+            .map(|(path, rule)| (path.as_path(), rule));
+        lint_commits_with_rule_files(commits, root, strict, |commit| {
+            self.selected
+                .get(&commit.sha)
+                .into_iter()
+                .flatten()
+                .filter_map(|path| self.rule_files.get_key_value(path))
+                .map(|(path, rule)| (path.as_path(), rule))
+                .collect()
+        })
+    }
+}
+
+/// Rule files of a commit changing the paths `changed`: the subdirectory rule files that
+/// apply, after the root rule file `rule_path` when it applies as well or nothing else does
+fn commit_rule_files(
+    workdir: &Path,
+    rule_path: &Path,
+    has_root: bool,
+    changed: &[String],
+) -> Vec<PathBuf> {
+    let root = has_root.then_some(rule_path);
+    let mut root_applies = false;
+    let mut paths = Vec::new();
+    for path in applicable_rule_files(workdir, root, changed).into_keys() {
+        if path == rule_path {
+            root_applies = true;
+        } else {
+            paths.push(path);
+        }
+    }
+    if has_root && (root_applies || paths.is_empty()) {
+        paths.insert(0, rule_path.to_path_buf());
+    }
+    paths
+}
+
+/// Load a rule file over the user rules, printing its warnings
+fn load_rule_file(
+    path: &Path,
+    user: Option<&UserConfig>,
+) -> Result<ParsedCommitMsgRule, HookError> {
+    let parsed_rule = load_commit_msg_rule_with_user(path, user)?;
+    for w in &parsed_rule.warnings {
+        print_warning(&format!("warning: {}", w));
+    }
+    Ok(parsed_rule)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constant::COMMIT_MSG_RULE_FILE_NAME;
+    use std::fs;
+    use tempfile::TempDir;
+
+    const NULL_SHA: &str = "0000000000000000000000000000000000000000";
+
+    #[test]
+    fn test_parse_push_updates() {
+        let input = format!(
+            "refs/heads/main 1111 refs/heads/main 2222\n\n\
+             (delete) {} refs/heads/old 3333\n",
+            NULL_SHA
+        );
+        let updates = parse_push_updates(&input).unwrap();
+        assert_eq!(updates.len(), 2);
+        assert_eq!(updates[0].local_sha, "1111");
+        assert!(!updates[0].is_new());
+        assert!(updates[1].is_delete());
+
+        assert!(parse_push_updates("refs/heads/main 1111\n").is_err());
+    }
+
+    #[test]
+    fn test_update_range() {
+        let update = |local: &str, remote: &str| PushUpdate {
+            local_ref: "refs/heads/main".to_string(),
+            local_sha: local.to_string(),
+            remote_ref: "refs/heads/main".to_string(),
+            remote_sha: remote.to_string(),
+        };
+        let exclude = "--remotes=origin";
+
+        assert_eq!(
+            update_range(&update("1111", "2222"), true, exclude),
+            Some(vec!["2222..1111".to_string()])
+        );
+        // new branch
+        assert_eq!(
+            update_range(&update("1111", NULL_SHA), false, exclude),
+            Some(vec![
+                "1111".to_string(),
+                "--not".to_string(),
+                exclude.to_string()
+            ])
+        );
+        // force push over commits that were never fetched
+        assert_eq!(
+            update_range(&update("1111", "2222"), false, exclude),
+            update_range(&update("1111", NULL_SHA), false, exclude)
+        );
+        assert_eq!(update_range(&update(NULL_SHA, "2222"), true, exclude), None);
+    }
+
+    #[test]
+    fn test_nested_rule_file_rejects_pushed_commit() {
+        let dir = TempDir::new().unwrap();
+        let workdir = dir.path();
+        let root_path = workdir.join(COMMIT_MSG_RULE_FILE_NAME);
+        let api_path = workdir.join("api").join(COMMIT_MSG_RULE_FILE_NAME);
+        fs::create_dir(workdir.join("api")).unwrap();
+        fs::write(
+            &root_path,
+            "[header.type]\nallowed_types = [\"feat\", \"fix\"]\n",
+        )
+        .unwrap();
+        fs::write(&api_path, "[header.type]\nallowed_types = [\"feat\"]\n").unwrap();
+
+        let commit = |sha: &str, message: &str| CommitInfo {
+            sha: sha.to_string(),
+            author_email: "dev@example.com".to_string(),
+            message: message.to_string(),
+        };
+        let commits = [
+            commit("aaaa", "fix: handle empty bodies"),
+            commit("bbbb", "fix: typo in the readme"),
+        ];
+        let changed = HashMap::from([
+            ("aaaa", vec!["api/handler.rs".to_string()]),
+            ("bbbb", vec!["README.md".to_string()]),
+        ]);
+
+        let mut rule_files = HashMap::new();
+        for path in [&root_path, &api_path] {
+            rule_files.insert(path.clone(), load_rule_file(path, None).unwrap());
+        }
+        let root = rule_files
+            .get_key_value(&root_path)
+            .map(|(path, rule)| (path.as_path(), rule));
+        let report = lint_commits_with_rule_files(&commits, root, false, |commit| {
+            commit_rule_files(workdir, &root_path, true, &changed[commit.sha.as_str()])
+                .iter()
+                .filter_map(|path| rule_files.get_key_value(path))
+                .map(|(path, rule)| (path.as_path(), rule))
+                .collect()
+        });
+
+        // only the commit touching api/ is rejected, by the rule file of api/
+        assert_eq!(report.failed_commits(), vec!["aaaa"]);
+        assert_eq!(report.violations[0].rule_file.as_ref(), Some(&api_path));
+    }
+
+    #[test]
+    fn test_missing_root_rule_file() {
+        let dir = TempDir::new().unwrap();
+        let missing = dir.path().join(COMMIT_MSG_RULE_FILE_NAME);
+
+        // a monorepo may keep all of its rules in subdirectories
+        let rules = RangeRuleFiles::load(missing.clone(), &RuleFileOrigin::Default, None).unwrap();
+        assert!(rules.is_empty());
+        assert!(rules.root_global().is_none());
+
+        // a rule file given explicitly must exist
+        assert!(RangeRuleFiles::load(missing, &RuleFileOrigin::Argument, None).is_err());
+    }
+
+    #[test]
+    fn test_rebase_bases_per_ref() {
+        let pushed = |local_ref: &str, shas: &[&str]| PushedRef {
+            local_ref: local_ref.to_string(),
+            shas: shas.iter().map(|s| s.to_string()).collect(),
+        };
+        // `main` pushes c2 over c1, `feature` pushes f2 over f1, a root commit
+        let refs = [
+            pushed("refs/heads/main", &["c2c2c2c2c2c2", "c1c1c1c1c1c1"]),
+            pushed("refs/heads/feature", &["f2f2f2f2f2f2", "f1f1f1f1f1f1"]),
+            pushed("refs/heads/docs", &["d1d1d1d1d1d1"]),
+        ];
+        let failed = ["c2c2c2c2c2c2", "c1c1c1c1c1c1", "f1f1f1f1f1f1"];
+
+        let rebases = rebase_bases(&refs, &failed, |sha| sha != "f1f1f1f1f1f1");
+        assert_eq!(
+            rebases,
+            [
+                ("main".to_string(), "c1c1c1c1c1^".to_string()),
+                ("feature".to_string(), "--root".to_string()),
+            ]
+        );
+
+        let message = HookError::PushRejected {
+            shas: vec!["c2c2c2c2c2".to_string()],
+            total: 5,
+            rebases,
+        }
+        .to_string();
+        assert!(message.contains("main: `git rebase -i c1c1c1c1c1^`"));
+        assert!(message.contains("feature: `git rebase -i --root`"));
+    }
+}
@@ -0,0 +1,300 @@
+//! `gitru run <hook>`: run the commands the hook manifest lists for a hook
+//!
+//! Every command runs with `sh -c` from the repository root, with the arguments git passed to
+//! the hook as `"$@"`, the input git wrote to the hook on stdin and `GITRU_HOOK` set to the
+//! hook name. Commands run one after another and stop at the first failure, or with
+//! `parallel = true` all at once, with their output printed in order when they are done.
+//! See [`crate::config::manifest`] for the format.
+
+use crate::config::manifest::{FILES_PLACEHOLDER, HookCommand, HookManifest};
+use crate::error::hook_error::HookError;
+use crate::hook::kind::HookKind;
+use crate::util::colored_print::{print_error, print_info, print_success};
+use crate::util::git_log::staged_paths;
+use crate::util::git_path::detect_current_repo;
+use globset::{Glob, GlobSetBuilder};
+use std::io::{Read, Write};
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+
+/// Run the commands of `hook` in the current repository with the hook arguments `args`,
+/// passing them the input of the hook
+pub fn run_commands(hook: HookKind, args: &[String]) -> Result<(), HookError> {
+    let mut input = String::new();
+    if hook.reads_stdin() {
+        std::io::stdin()
+            .read_to_string(&mut input)
+            .map_err(|e| HookError::io(format!("read the input of {}", hook), e))?;
+    }
+    run_commands_with_input(hook, args, &input)
+}
+
+/// Run the commands of `hook` with the hook arguments `args` and `input` on stdin, for hooks
+/// that read the input of git themselves
+pub fn run_commands_with_input(
+    hook: HookKind,
+    args: &[String],
+    input: &str,
+) -> Result<(), HookError> {
+    let git_kind = detect_current_repo()?;
+    let workdir = git_kind.workdir();
+    let manifest = HookManifest::load(workdir)?;
+    let Some(options) = manifest.as_ref().and_then(|m| m.options(hook)) else {
+        return Ok(());
+    };
+    if options.commands.is_empty() {
+        return Ok(());
+    }
+
+    let needs_files = options
+        .commands
+        .iter()
+        .any(|c| !c.glob.is_empty() || c.run.contains(FILES_PLACEHOLDER));
+    let staged = if needs_files {
+        staged_paths().map_err(HookError::StagedFiles)?
+    } else {
+        Vec::new()
+    };
+
+    let mut commands = Vec::new();
+    for command in &options.commands {
+        match command_line(command, &staged)? {
+            Some(line) => commands.push((command.display_name(), line)),
+            None => print_info(&format!(
+                "{}: {} skipped, no staged file to run on",
+                hook,
+                command.display_name()
+            )),
+        }
+    }
+
+    if options.parallel {
+        run_parallel(hook, &commands, args, input, workdir)
+    } else {
+        run_sequential(hook, &commands, args, input, workdir)
+    }
+}
+
+/// The shell command of `command` with `{files}` replaced by the staged files matching its
+/// globs, `None` when it has globs or `{files}` and no staged file to run on
+fn command_line(command: &HookCommand, staged: &[String]) -> Result<Option<String>, HookError> {
+    let files: Vec<&String> = if command.glob.is_empty() {
+        staged.iter().collect()
+    } else {
+        let mut builder = GlobSetBuilder::new();
+        for glob in &command.glob {
+            builder.add(Glob::new(glob).map_err(|source| HookError::InvalidGlob {
+                glob: glob.clone(),
+                source,
+            })?);
+        }
+        let globs = builder.build().map_err(|source| HookError::InvalidGlob {
+            glob: command.glob.join(", "),
+            source,
+        })?;
+        staged.iter().filter(|p| globs.is_match(p)).collect()
+    };
+    let uses_files = !command.glob.is_empty() || command.run.contains(FILES_PLACEHOLDER);
+    if uses_files && files.is_empty() {
+        return Ok(None);
+    }
+
+    let quoted: Vec<String> = files.iter().map(|f| shell_quote(f)).collect();
+    Ok(Some(
+        command.run.replace(FILES_PLACEHOLDER, &quoted.join(" ")),
+    ))
+}
+
+/// `value` quoted for `sh`
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// `sh -c <line> <hook> <args>...`, so that the hook arguments are `"$@"`
+fn shell_command(hook: HookKind, line: &str, args: &[String], workdir: &Path) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c")
+        .arg(line)
+        .arg(hook.as_str())
+        .args(args)
+        .current_dir(workdir)
+        .env("GITRU_HOOK", hook.as_str());
+    cmd
+}
+
+/// Run `command` with `stdin` as its input, returning its exit status or captured output
+fn spawn_with_stdin(mut command: Command, stdin: &str, capture: bool) -> std::io::Result<Output> {
+    command.stdin(Stdio::piped());
+    if capture {
+        command.stdout(Stdio::piped()).stderr(Stdio::piped());
+    }
+    let mut child = command.spawn()?;
+    if let Some(mut input) = child.stdin.take() {
+        // a command that does not read its input closes the pipe early
+        let _ = input.write_all(stdin.as_bytes());
+    }
+    child.wait_with_output()
+}
+
+/// Run the commands one after another, stopping at the first failure
+fn run_sequential(
+    hook: HookKind,
+    commands: &[(&str, String)],
+    args: &[String],
+    stdin: &str,
+    workdir: &Path,
+) -> Result<(), HookError> {
+    for (name, line) in commands {
+        print_info(&format!("{}: {}", hook, name));
+        let output = spawn_with_stdin(shell_command(hook, line, args, workdir), stdin, false)
+            .map_err(|source| HookError::CommandSpawn {
+                hook,
+                name: name.to_string(),
+                source,
+            })?;
+        if !output.status.success() {
+            return Err(HookError::CommandFailed {
+                hook,
+                name: name.to_string(),
+                status: output.status,
+            });
+        }
+    }
+
+    if !commands.is_empty() {
+        print_success(&format!("{}: {} command(s) passed", hook, commands.len()));
+    }
+    Ok(())
+}
+
+/// Run the commands at the same time, then print their output in order
+fn run_parallel(
+    hook: HookKind,
+    commands: &[(&str, String)],
+    args: &[String],
+    stdin: &str,
+    workdir: &Path,
+) -> Result<(), HookError> {
+    let outputs: Vec<std::io::Result<Output>> = std::thread::scope(|scope| {
+        let handles: Vec<_> = commands
+            .iter()
+            .map(|(_, line)| {
+                let command = shell_command(hook, line, args, workdir);
+                scope.spawn(move || spawn_with_stdin(command, stdin, true))
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|_| Err(std::io::Error::other("command thread panicked")))
+            })
+            .collect()
+    });
+
+    let mut failed = Vec::new();
+    for ((name, _), output) in commands.iter().zip(outputs) {
+        print_info(&format!("{}: {}", hook, name));
+        match output {
+            Ok(output) => {
+                let _ = std::io::stdout().write_all(&output.stdout);
+                let _ = std::io::stderr().write_all(&output.stderr);
+                if !output.status.success() {
+                    let e = HookError::CommandFailed {
+                        hook,
+                        name: name.to_string(),
+                        status: output.status,
+                    };
+                    print_error(&e.to_string());
+                    failed.push(name.to_string());
+                }
+            }
+            Err(source) => {
+                let e = HookError::CommandSpawn {
+                    hook,
+                    name: name.to_string(),
+                    source,
+                };
+                print_error(&e.to_string());
+                failed.push(name.to_string());
+            }
+        }
+    }
+
+    if failed.is_empty() {
+        if !commands.is_empty() {
+            print_success(&format!("{}: {} command(s) passed", hook, commands.len()));
+        }
+        Ok(())
+    } else {
+        Err(HookError::CommandsFailed {
+            hook,
+            names: failed,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn command(run: &str, glob: &[&str]) -> HookCommand {
+        HookCommand {
+            name: None,
+            run: run.to_string(),
+            glob: glob.iter().map(|g| g.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_command_line() {
+        let staged = vec!["src/main.rs".to_string(), "it's.md".to_string()];
+
+        assert_eq!(
+            command_line(&command("rustfmt {files}", &["*.rs"]), &staged).unwrap(),
+            Some("rustfmt 'src/main.rs'".to_string())
+        );
+        assert_eq!(
+            command_line(&command("typos {files}", &[]), &staged).unwrap(),
+            Some(r"typos 'src/main.rs' 'it'\''s.md'".to_string())
+        );
+        assert_eq!(
+            command_line(&command("eslint", &["*.js"]), &staged).unwrap(),
+            None
+        );
+        // nothing staged, e.g. `git commit --amend` of the message only
+        assert_eq!(
+            command_line(&command("typos {files}", &[]), &[]).unwrap(),
+            None
+        );
+        assert_eq!(
+            command_line(&command("cargo test", &[]), &[]).unwrap(),
+            Some("cargo test".to_string())
+        );
+    }
+
+    #[cfg(target_family = "unix")]
+    #[test]
+    fn test_run_commands_with_hook_input() {
+        let dir = TempDir::new().unwrap();
+        let args = vec!["origin".to_string()];
+        let commands = [
+            (
+                "args",
+                "test \"$1\" = origin && test \"$GITRU_HOOK\" = pre-push".to_string(),
+            ),
+            ("stdin", "grep -q refs/heads/main".to_string()),
+        ];
+        let stdin = "refs/heads/main 1 refs/heads/main 0\n";
+
+        assert!(run_sequential(HookKind::PrePush, &commands, &args, stdin, dir.path()).is_ok());
+        assert!(run_parallel(HookKind::PrePush, &commands, &args, stdin, dir.path()).is_ok());
+
+        let failing = [("stdin", "grep -q refs/heads/main".to_string())];
+        let err = run_parallel(HookKind::PrePush, &failing, &args, "", dir.path()).unwrap_err();
+        assert!(err.to_string().contains("failed: stdin"));
+        assert!(run_sequential(HookKind::PrePush, &failing, &args, "", dir.path()).is_err());
+    }
+}
@@ -0,0 +1,243 @@
+//! `gitru sync` and `gitru check-installed`: reconcile the installed hooks with the hook
+//! manifest of the repository, see [`crate::config::manifest`].
+
+use crate::config::manifest::{HOOK_MANIFEST_PATH, HookManifest, HookOptions};
+use crate::error::hook_error::HookError;
+use crate::hook::install::{InstallOptions, status};
+use crate::hook::installer::installer;
+use crate::hook::kind::HookKind;
+use crate::hook::marker::HookOwnership;
+use crate::util::colored_print::{print_info, print_success, print_warning};
+use crate::util::git_path::GitKind;
+use std::path::Path;
+
+/// How an installed hook compares to the manifest
+#[derive(Debug, Clone, PartialEq)]
+pub enum HookState {
+    UpToDate,
+    /// Listed in the manifest, not installed
+    Missing,
+    /// Listed in the manifest, installed differently, with the reason
+    Outdated(String),
+    /// Installed by gitru, no longer listed in the manifest
+    Unlisted,
+}
+
+impl HookState {
+    /// Short description for the output of sync and check
+    fn describe(&self) -> String {
+        match self {
+            HookState::UpToDate => "up to date".to_string(),
+            HookState::Missing => "missing".to_string(),
+            HookState::Outdated(reason) => format!("outdated, {}", reason),
+            HookState::Unlisted => "installed but no longer listed in the manifest".to_string(),
+        }
+    }
+}
+
+/// State of `hook` in `hooks_dir`, given its options in the manifest (`None` when it is not
+/// listed). Hooks that gitru did not write are only a concern when the manifest lists them.
+pub fn hook_state(
+    hooks_dir: &Path,
+    hook: HookKind,
+    wanted: Option<&HookOptions>,
+) -> Result<HookState, HookError> {
+    let status = status(hooks_dir, hook)?;
+    let (wanted, ownership) = match (wanted, status.ownership) {
+        (Some(wanted), Some(ownership)) => (wanted, ownership),
+        (Some(_), None) => return Ok(HookState::Missing),
+        (None, Some(ownership)) if ownership.is_gitru() => return Ok(HookState::Unlisted),
+        (None, _) => return Ok(HookState::UpToDate),
+    };
+
+    let current = env!("CARGO_PKG_VERSION");
+    let outdated = |reason: String| Ok(HookState::Outdated(reason));
+    match ownership {
+        HookOwnership::Foreign => return outdated("not installed by gitru".to_string()),
+        HookOwnership::Legacy => return outdated("written by an older gitru".to_string()),
+        HookOwnership::Gitru { modified: true, .. } => {
+            return outdated("modified after installation".to_string());
+        }
+        HookOwnership::Gitru { version, .. } if version != current => {
+            return outdated(format!("written by gitru {}", version));
+        }
+        HookOwnership::Gitru { .. } => {}
+    }
+
+    let exec = status.exec.unwrap_or_default();
+    if let Some(wanted_exec) = &wanted.exec
+        && *wanted_exec != exec
+    {
+        return outdated(format!(
+            "exec is {}, the manifest wants {}",
+            exec, wanted_exec
+        ));
+    }
+    let chain = status.chain;
+    if wanted.chain.is_some() && wanted.chain != chain {
+        return outdated(format!(
+            "chain is {}, the manifest wants {}",
+            chain.map_or("none", |c| c.as_str()),
+            wanted.chain.map_or("none", |c| c.as_str())
+        ));
+    }
+
+    Ok(HookState::UpToDate)
+}
+
+/// The manifest of `git_kind`, required by sync and check
+fn load_manifest(git_kind: &GitKind) -> Result<HookManifest, HookError> {
+    let manifest =
+        HookManifest::load(git_kind.workdir())?.ok_or_else(|| HookError::NoManifest {
+            path: git_kind.workdir().join(HOOK_MANIFEST_PATH),
+        })?;
+
+    for hook in manifest.hooks.keys() {
+        if hook.parse::<HookKind>().is_err() {
+            print_warning(&format!(
+                "{}: `{}` cannot be installed by this version of gitru, skipped",
+                manifest.path.display(),
+                hook
+            ));
+        }
+    }
+    Ok(manifest)
+}
+
+/// The state of every installable hook of `git_kind`
+fn hook_states(
+    git_kind: &GitKind,
+    manifest: &HookManifest,
+) -> Result<Vec<(HookKind, HookState)>, HookError> {
+    let hooks_dir = git_kind.hooks_dir().path;
+    HookKind::ALL
+        .into_iter()
+        .map(|hook| Ok((hook, hook_state(&hooks_dir, hook, manifest.options(hook))?)))
+        .collect()
+}
+
+/// Install, update and remove hooks of `git_kind` to match its manifest. With `force`,
+/// modified and foreign hooks are replaced or removed as well.
+pub fn sync(git_kind: &GitKind, force: bool) -> Result<(), HookError> {
+    let manifest = load_manifest(git_kind)?;
+
+    let mut failed = Vec::new();
+    for (hook, state) in hook_states(git_kind, &manifest)? {
+        if state == HookState::UpToDate {
+            print_success(&format!("{}: up to date", hook));
+            continue;
+        }
+
+        print_info(&format!("{}: {}", hook, state.describe()));
+        let installer = installer(hook);
+        let result = match manifest.options(hook) {
+            Some(options) => installer.install(
+                git_kind,
+                &InstallOptions {
+                    force,
+                    chain: options.chain,
+                    exec: options.exec.clone(),
+                },
+            ),
+            None => installer.uninstall(git_kind, force),
+        };
+        if let Err(e) = result {
+            failed.push((hook, e));
+        }
+    }
+
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        Err(HookError::SyncFailed(failed))
+    }
+}
+
+/// Report the hooks of `git_kind` that do not match its manifest, failing when any does not.
+pub fn check_installed(git_kind: &GitKind) -> Result<(), HookError> {
+    let manifest = load_manifest(git_kind)?;
+
+    let mut mismatches = 0;
+    for (hook, state) in hook_states(git_kind, &manifest)? {
+        if state == HookState::UpToDate {
+            print_success(&format!("{}: up to date", hook));
+        } else {
+            print_warning(&format!("{}: {}", hook, state.describe()));
+            mismatches += 1;
+        }
+    }
+
+    if mismatches == 0 {
+        return Ok(());
+    }
+    Err(HookError::NotSynced {
+        count: mismatches,
+        manifest: manifest.path,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constant::hook_content;
+    use crate::hook::kind::{ChainOrder, HookExec};
+    use crate::hook::marker::mark;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_hook_state() {
+        let dir = TempDir::new().unwrap();
+        let hook_path = dir.path().join("commit-msg");
+        let wanted = HookOptions::default();
+
+        assert_eq!(
+            hook_state(dir.path(), HookKind::CommitMsg, Some(&wanted)).unwrap(),
+            HookState::Missing
+        );
+        assert_eq!(
+            hook_state(dir.path(), HookKind::CommitMsg, None).unwrap(),
+            HookState::UpToDate
+        );
+
+        let exec = HookExec::Path;
+        fs::write(
+            &hook_path,
+            mark(
+                &hook_content(HookKind::CommitMsg, "", &exec),
+                &[("exec", &exec.to_string())],
+            ),
+        )
+        .unwrap();
+        assert_eq!(
+            hook_state(dir.path(), HookKind::CommitMsg, Some(&wanted)).unwrap(),
+            HookState::UpToDate
+        );
+        assert_eq!(
+            hook_state(dir.path(), HookKind::CommitMsg, None).unwrap(),
+            HookState::Unlisted
+        );
+        assert!(matches!(
+            hook_state(
+                dir.path(),
+                HookKind::CommitMsg,
+                Some(&HookOptions {
+                    chain: Some(ChainOrder::After),
+                    ..Default::default()
+                })
+            ),
+            Ok(HookState::Outdated(reason)) if reason.contains("chain is none")
+        ));
+
+        // foreign hooks are left alone unless the manifest wants the hook
+        fs::write(&hook_path, "#!/bin/sh\nexit 0\n").unwrap();
+        assert_eq!(
+            hook_state(dir.path(), HookKind::CommitMsg, None).unwrap(),
+            HookState::UpToDate
+        );
+        assert!(matches!(
+            hook_state(dir.path(), HookKind::CommitMsg, Some(&wanted)),
+            Ok(HookState::Outdated(_))
+        ));
+    }
+}
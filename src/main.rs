@@ -1,53 +1,151 @@
-use clap::Parser;
+use clap::{CommandFactory, Parser};
 use gitru::cli::{Cli, Commands};
+use gitru::config::user::UserConfig;
 use gitru::hook::{self, run_hook};
 use gitru::util::colored_console::init_console;
-use gitru::util::colored_print::print_error;
+use gitru::util::colored_print::{print_error, print_warning, set_color_choice};
 
 fn main() {
     init_console();
 
     let cli = Cli::parse();
 
+    // the user config applies to every repository, see `gitru::config::user`
+    let user = match UserConfig::load() {
+        Ok(user) => user,
+        // `gitru config` reports the configuration, a broken one is an error there
+        Err(e) if matches!(cli.command, Commands::Config { .. }) => {
+            print_error(&e.to_string());
+            std::process::exit(1);
+        }
+        // a broken user config must not block commits in every repository
+        Err(e) => {
+            print_warning(&format!("ignoring the user config: {}", e));
+            None
+        }
+    };
+    if let Some(color) = user.as_ref().and_then(|u| u.preferences.color) {
+        set_color_choice(color);
+    }
+
     match cli.command {
-        Commands::II { hook, force } => {
-            if let Err(e) = hook::init(&hook, force) {
-                print_error(&e);
+        Commands::II {
+            hook,
+            force,
+            preset,
+            chain,
+            exec,
+        } => {
+            if let Err(e) = hook::init(hook, force, preset.as_deref()) {
+                print_error(&e.to_string());
                 std::process::exit(1);
             }
 
-            if let Err(e) = hook::install(&hook, force) {
-                print_error(&e);
+            if let Err(e) = hook::install(hook, force, chain, exec.as_ref(), false) {
+                print_error(&e.to_string());
+                std::process::exit(1);
+            }
+        }
+
+        Commands::Init {
+            hook,
+            force,
+            preset,
+        } => {
+            if let Err(e) = hook::init(hook, force, preset.as_deref()) {
+                print_error(&e.to_string());
                 std::process::exit(1);
             }
         }
 
-        Commands::Init { hook, force } => {
-            if let Err(e) = hook::init(&hook, force) {
-                print_error(&e);
+        Commands::Install {
+            hook,
+            all: _,
+            force,
+            chain,
+            exec,
+            recursive,
+        } => {
+            // without a hook, `--all` is set
+            let result = match hook {
+                Some(hook) => hook::install(hook, force, chain, exec.as_ref(), recursive),
+                None => hook::sync(force),
+            };
+            if let Err(e) = result {
+                print_error(&e.to_string());
                 std::process::exit(1);
             }
         }
 
-        Commands::Install { hook, force } => {
-            if let Err(e) = hook::install(&hook, force) {
-                print_error(&e);
+        Commands::Sync { force } => {
+            if let Err(e) = hook::sync(force) {
+                print_error(&e.to_string());
                 std::process::exit(1);
             }
         }
 
-        Commands::Uninstall { hook } => {
-            if let Err(e) = hook::uninstall(&hook) {
-                print_error(&e);
+        Commands::CheckInstalled => {
+            if let Err(e) = hook::check_installed() {
+                print_error(&e.to_string());
                 std::process::exit(1);
             }
         }
 
-        Commands::Run { hook } => {
-            if let Err(err) = run_hook(&hook) {
-                print_error(&err);
+        Commands::Status => {
+            if let Err(e) = hook::status() {
+                print_error(&e.to_string());
                 std::process::exit(1);
             }
         }
+
+        Commands::Uninstall {
+            hook,
+            force,
+            recursive,
+        } => {
+            if let Err(e) = hook::uninstall(hook, force, recursive) {
+                print_error(&e.to_string());
+                std::process::exit(1);
+            }
+        }
+
+        Commands::Lint {
+            range,
+            rule,
+            strict,
+        } => {
+            if let Err(e) = hook::commit_msg::lint(&range, rule.as_ref(), strict, user.as_ref()) {
+                print_error(&e.to_string());
+                std::process::exit(1);
+            }
+        }
+
+        Commands::Config { cmd } => {
+            if let Err(e) = hook::config::run_config(&cmd, user.as_ref()) {
+                print_error(&e.to_string());
+                std::process::exit(1);
+            }
+        }
+
+        Commands::Doctor => {
+            if let Err(e) = hook::doctor::doctor(user.as_ref()) {
+                print_error(&e.to_string());
+                std::process::exit(1);
+            }
+        }
+
+        Commands::Completions { shell } => {
+            clap_complete::generate(shell, &mut Cli::command(), "gitru", &mut std::io::stdout());
+        }
+
+        Commands::Run { hook } => match run_hook(&hook, user.as_ref()) {
+            Ok(true) => {}
+            // the hook reported why it blocks the git operation
+            Ok(false) => std::process::exit(1),
+            Err(err) => {
+                print_error(&err.to_string());
+                std::process::exit(1);
+            }
+        },
     }
 }
@@ -0,0 +1,192 @@
+use regex::Regex;
+use std::sync::LazyLock;
+
+/// Kind of commit, classified from the header (and body for reverts).
+///
+/// Git itself generates several messages that do not follow Conventional Commits,
+/// e.g. `Merge branch 'x'`, `Revert "feat: ..."` or `fixup! ...`. They are classified
+/// here so that they can be handled by policy instead of failing header parsing.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CommitKind {
+    /// A regular commit, validated against the header/body/footer rules
+    Regular,
+
+    /// `Merge branch 'x'`, `Merge pull request #1 from ...`, etc.
+    Merge,
+
+    /// `Revert "feat: add parser"` followed by `This reverts commit <sha>.`
+    Revert {
+        /// The quoted header of the reverted commit
+        original_header: String,
+        /// The sha from the `This reverts commit <sha>` line, if present
+        reverted_sha: Option<String>,
+    },
+
+    /// `fixup! feat: add parser`
+    Fixup { target: String },
+
+    /// `squash! feat: add parser`
+    Squash { target: String },
+
+    /// `amend! feat: add parser`
+    Amend { target: String },
+}
+
+impl CommitKind {
+    /// Short name used in messages
+    pub fn name(&self) -> &'static str {
+        match self {
+            CommitKind::Regular => "regular",
+            CommitKind::Merge => "merge",
+            CommitKind::Revert { .. } => "revert",
+            CommitKind::Fixup { .. } => "fixup",
+            CommitKind::Squash { .. } => "squash",
+            CommitKind::Amend { .. } => "amend",
+        }
+    }
+
+    /// Whether this is an autosquash marker commit (`fixup!`, `squash!`, `amend!`)
+    /// that is expected to disappear after `git rebase --autosquash`.
+    pub fn is_autosquash(&self) -> bool {
+        matches!(
+            self,
+            CommitKind::Fixup { .. } | CommitKind::Squash { .. } | CommitKind::Amend { .. }
+        )
+    }
+}
+
+/// Classify a commit message by its first non-empty line.
+///
+/// `header` is the first non-empty line, `content` is the whole (preprocessed) message.
+pub fn classify_commit_kind(header: &str, content: &str) -> CommitKind {
+    let header = header.trim();
+
+    // Autosquash markers: git requires the marker to be followed by a single space
+    if let Some(target) = header.strip_prefix("fixup! ") {
+        return CommitKind::Fixup {
+            target: target.trim().to_string(),
+        };
+    }
+    if let Some(target) = header.strip_prefix("squash! ") {
+        return CommitKind::Squash {
+            target: target.trim().to_string(),
+        };
+    }
+    if let Some(target) = header.strip_prefix("amend! ") {
+        return CommitKind::Amend {
+            target: target.trim().to_string(),
+        };
+    }
+
+    #[allow(clippy::expect_used)]
+    static MERGE_RE: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(
+            r"^Merge (?:branch|branches|remote-tracking branch|remote-tracking branches|tag|tags|commit|commits|pull request|'|`)",
+        )
+        .expect("regex is valid")
+    });
+
+    if MERGE_RE.is_match(header) {
+        return CommitKind::Merge;
+    }
+
+    if let Some(original_header) = extract_reverted_header(header) {
+        return CommitKind::Revert {
+            original_header,
+            reverted_sha: find_reverted_sha(content),
+        };
+    }
+
+    CommitKind::Regular
+}
+
+/// Extract the quoted header of `Revert "..."`.
+/// Nested reverts (`Revert "Revert "feat: x""`) are unwrapped down to the innermost header.
+fn extract_reverted_header(header: &str) -> Option<String> {
+    let mut inner = header
+        .strip_prefix("Revert \"")?
+        .strip_suffix('"')?
+        .to_string();
+
+    while let Some(next) = inner
+        .strip_prefix("Revert \"")
+        .and_then(|s| s.strip_suffix('"'))
+    {
+        inner = next.to_string();
+    }
+
+    Some(inner)
+}
+
+/// Find the sha in a `This reverts commit <sha>.` line.
+fn find_reverted_sha(content: &str) -> Option<String> {
+    #[allow(clippy::expect_used)]
+    static REVERTS_RE: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r"(?m)^\s*This reverts commit (?P<sha>[0-9a-fA-F]{7,64})\b")
+            .expect("regex is valid")
+    });
+
+    REVERTS_RE
+        .captures(content)
+        .and_then(|caps| caps.name("sha"))
+        .map(|m| m.as_str().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_merge() {
+        for header in [
+            "Merge branch 'dev'",
+            "Merge branch 'dev' into main",
+            "Merge remote-tracking branch 'origin/main'",
+            "Merge pull request #42 from user/feature",
+            "Merge tag 'v1.0.0'",
+        ] {
+            assert_eq!(classify_commit_kind(header, header), CommitKind::Merge);
+        }
+    }
+
+    #[test]
+    fn test_classify_revert() {
+        let msg = "Revert \"feat(parser): add parser\"\n\nThis reverts commit 1a2b3c4d5e6f.\n";
+        let kind = classify_commit_kind("Revert \"feat(parser): add parser\"", msg);
+        assert_eq!(
+            kind,
+            CommitKind::Revert {
+                original_header: "feat(parser): add parser".to_string(),
+                reverted_sha: Some("1a2b3c4d5e6f".to_string()),
+            }
+        );
+
+        let nested = "Revert \"Revert \"fix: boundary\"\"";
+        match classify_commit_kind(nested, nested) {
+            CommitKind::Revert {
+                original_header,
+                reverted_sha,
+            } => {
+                assert_eq!(original_header, "fix: boundary");
+                assert!(reverted_sha.is_none());
+            }
+            other => panic!("unexpected kind: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_classify_autosquash() {
+        assert_eq!(
+            classify_commit_kind("fixup! feat: add parser", ""),
+            CommitKind::Fixup {
+                target: "feat: add parser".to_string()
+            }
+        );
+        assert!(classify_commit_kind("squash! feat: add parser", "").is_autosquash());
+        assert!(classify_commit_kind("amend! feat: add parser", "").is_autosquash());
+        assert_eq!(
+            classify_commit_kind("feat: add parser", ""),
+            CommitKind::Regular
+        );
+    }
+}
@@ -1,8 +1,11 @@
-use crate::config::commit_msg_rule::get_default_path_parsed_commit_msg_rule;
+use crate::config::commit_msg_rule::{
+    ParsedCommitMsgRule, SpellcheckTarget, get_default_path_parsed_commit_msg_rule,
+};
 use crate::error::commit_msg_error::CommitMsgError;
 use crate::error::footer_error::FooterError;
-use crate::error::footer_error::FooterError::FooterKeywordTypoError;
-use crate::parser::footer::{detect_footer_keyword_typo, is_footer_line, looks_like_footer};
+use crate::parser::footer::{
+    FooterKeywordTypo, detect_footer_keyword_typo, is_footer_line, looks_like_footer,
+};
 use crate::parser::header::{ParsedHeader, parse_header};
 use crate::parser::preprocess_lines;
 
@@ -14,6 +17,10 @@ pub struct ParsedCommitMessage {
 
     pub blank_lines_before_body: usize,
     pub blank_lines_before_footer: usize,
+
+    /// A body line that looks like a misspelled footer keyword (only when there is no footer),
+    /// together with the spellcheck threshold. Reported by the validator.
+    pub footer_keyword_typo: Option<(FooterKeywordTypo, f64)>,
 }
 
 /// Remove leading and trailing empty lines from slice, but keep single empty lines between paragraphs
@@ -29,6 +36,16 @@ fn trim_first_and_last_empty_lines(lines: &[String]) -> Vec<String> {
 }
 
 pub fn parse_commit_msg(content: &str) -> Result<ParsedCommitMessage, CommitMsgError> {
+    let parsed_commit_msg_rule = get_default_path_parsed_commit_msg_rule()?;
+    parse_commit_msg_with_rule(content, &parsed_commit_msg_rule)
+}
+
+/// Same as [`parse_commit_msg`], but with an already loaded rule instead of the
+/// rule file at the default path of the current repository.
+pub fn parse_commit_msg_with_rule(
+    content: &str,
+    parsed_commit_msg_rule: &ParsedCommitMsgRule,
+) -> Result<ParsedCommitMessage, CommitMsgError> {
     // 1. Preprocess
     let lines = preprocess_lines(content);
 
@@ -46,7 +63,6 @@ pub fn parse_commit_msg(content: &str) -> Result<ParsedCommitMessage, CommitMsgE
     };
 
     // 3. Find the starting position of the footer (scan after header)
-    let parsed_commit_msg_rule = &get_default_path_parsed_commit_msg_rule()?;
     let mut footer_start: Option<usize> = None;
     let mut i = idx + 1;
     while i < lines.len() {
@@ -121,7 +137,8 @@ pub fn parse_commit_msg(content: &str) -> Result<ParsedCommitMessage, CommitMsgE
     // 7. Validate header against rules
     let parsed_header = parsed_header.map_err(CommitMsgError::Header)?;
 
-    // 8. Validate body against rules
+    // 8. Detect misspelled footer keywords in the body, validated later according to severity
+    let mut footer_keyword_typo = None;
     if footer.is_none() && body.is_some() {
         let footer_cfg = parsed_commit_msg_rule
             .footer
@@ -134,7 +151,8 @@ pub fn parse_commit_msg(content: &str) -> Result<ParsedCommitMessage, CommitMsgE
             .unwrap_or_default();
 
         if spell_cfg.enable {
-            let threshold = spell_cfg.threshold;
+            let options =
+                parsed_commit_msg_rule.spellcheck_options(SpellcheckTarget::FooterKeyword);
             let key_words = &footer_cfg.start_key_words;
 
             let mut footer_block: Vec<&String> = Vec::new();
@@ -154,16 +172,10 @@ pub fn parse_commit_msg(content: &str) -> Result<ParsedCommitMessage, CommitMsgE
             }
 
             // The footer_block now contains the “final contiguous block of footer‑like lines”
-            for line in footer_block {
-                if let Some(typo) = detect_footer_keyword_typo(line, threshold, key_words) {
-                    return Err(CommitMsgError::Footer(FooterKeywordTypoError {
-                        wrong: typo.wrong,
-                        correct: typo.correct,
-                        similarity: typo.similarity,
-                        threshold,
-                    }));
-                }
-            }
+            footer_keyword_typo = footer_block
+                .into_iter()
+                .find_map(|line| detect_footer_keyword_typo(line, &options, key_words))
+                .map(|typo| (typo, options.threshold));
         }
     }
 
@@ -173,5 +185,6 @@ pub fn parse_commit_msg(content: &str) -> Result<ParsedCommitMessage, CommitMsgE
         footer,
         blank_lines_before_body,
         blank_lines_before_footer,
+        footer_keyword_typo,
     })
 }
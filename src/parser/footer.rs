@@ -1,5 +1,5 @@
-use crate::config::commit_msg_rule::ParsedCommitMsgRule;
-use strsim::normalized_levenshtein;
+use crate::config::commit_msg_rule::{ParsedCommitMsgRule, SpellcheckTarget};
+use crate::util::similarity::{SpellcheckOptions, similarity, suggest};
 
 /// Determine whether a line is the starting line of the footer (simple heuristic)
 pub fn is_footer_line(line: &str, rule: &ParsedCommitMsgRule) -> bool {
@@ -13,12 +13,11 @@ pub fn is_footer_line(line: &str, rule: &ParsedCommitMsgRule) -> bool {
         None => return false,
     };
 
-    let spell_cfg = match &footer_cfg.start_key_words_spellcheck {
-        Some(s) => s,
-        None => return false,
-    };
+    if footer_cfg.start_key_words_spellcheck.is_none() {
+        return false;
+    }
 
-    let threshold = spell_cfg.threshold;
+    let options = rule.spellcheck_options(SpellcheckTarget::FooterKeyword);
 
     let (keyword, _) = match trimmed.split_once(':') {
         Some(v) => v,
@@ -30,14 +29,14 @@ pub fn is_footer_line(line: &str, rule: &ParsedCommitMsgRule) -> bool {
     footer_cfg
         .start_key_words
         .iter()
-        .any(|k| strsim::normalized_levenshtein(keyword, k) >= threshold)
+        .any(|k| similarity(keyword, k, &options) >= options.threshold)
 }
 
 pub fn looks_like_footer(line: &str) -> bool {
     line.contains(':') || line.contains('#')
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct FooterKeywordTypo {
     /// User actually wrote the keyword (misspelled)
     pub wrong: String,
@@ -47,36 +46,28 @@ pub struct FooterKeywordTypo {
 
     /// Similarity between the two keywords (0.0 ~ 1.0)
     pub similarity: f64,
+
+    /// Other keywords close enough to be suggested
+    pub others: Vec<String>,
 }
 
 pub fn detect_footer_keyword_typo(
     line: &str,
-    threshold: f64,
+    options: &SpellcheckOptions,
     key_words: &[String],
 ) -> Option<FooterKeywordTypo> {
     let (keyword, _rest) = line.split_once(':')?;
     let keyword = keyword.trim();
 
-    let mut best_match = None;
-    let mut best_score = 0.0;
-
-    for valid in key_words {
-        let score = normalized_levenshtein(keyword, valid.as_str());
-        if score > best_score {
-            best_score = score;
-            best_match = Some(valid.clone());
-        }
-    }
+    let mut suggestions = suggest(keyword, key_words, options).into_iter();
+    let best = suggestions.next()?;
 
-    if best_score >= threshold {
-        best_match.map(|correct| FooterKeywordTypo {
-            wrong: keyword.to_string(),
-            correct,
-            similarity: best_score,
-        })
-    } else {
-        None
-    }
+    Some(FooterKeywordTypo {
+        wrong: keyword.to_string(),
+        correct: best.candidate,
+        similarity: best.similarity,
+        others: suggestions.map(|s| s.candidate).collect(),
+    })
 }
 
 #[cfg(test)]
@@ -85,7 +76,7 @@ mod tests {
     #[test]
     fn test_detect_footer_keyword_typo() {
         let line = "BAEAKING CHANGE: foo bar";
-        let threshold = 0.8;
+        let threshold = &SpellcheckOptions::with_threshold(0.8);
 
         let key_words = vec![
             "BREAKING CHANGE".to_string(),
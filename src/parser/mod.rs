@@ -1,7 +1,8 @@
 // Responsible for parsing commit messages
 
+pub mod commit_kind;
 pub mod commit_msg;
-mod footer;
+pub mod footer;
 pub mod header;
 
 /// Preprocess commit message: remove comment lines and empty lines
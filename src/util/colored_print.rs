@@ -1,7 +1,49 @@
+use serde::Deserialize;
+use std::sync::atomic::{AtomicU8, Ordering};
 use supports_color::Stream;
 
+/// When output is colored, `preferences.color` of the user config
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorChoice {
+    /// Color terminals only, as detected by `supports-color` (honors `NO_COLOR`)
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+static COLOR_CHOICE: AtomicU8 = AtomicU8::new(ColorChoice::Auto as u8);
+
+/// Set when output is colored, for the rest of the process
+pub fn set_color_choice(choice: ColorChoice) {
+    COLOR_CHOICE.store(choice as u8, Ordering::Relaxed);
+}
+
+/// Colors available on a stream
+struct Colors {
+    has_256: bool,
+    has_16m: bool,
+}
+
+fn colors(stream: Stream) -> Option<Colors> {
+    let detected = supports_color::on(stream).map(|level| Colors {
+        has_256: level.has_256,
+        has_16m: level.has_16m,
+    });
+
+    match COLOR_CHOICE.load(Ordering::Relaxed) {
+        c if c == ColorChoice::Never as u8 => None,
+        c if c == ColorChoice::Always as u8 => detected.or(Some(Colors {
+            has_256: false,
+            has_16m: false,
+        })),
+        _ => detected,
+    }
+}
+
 pub fn print_error(msg: &str) {
-    if let Some(support) = supports_color::on(Stream::Stderr) {
+    if let Some(support) = colors(Stream::Stderr) {
         if support.has_16m {
             // True color (RGB)
             eprintln!("\x1b[38;2;255;0;0m{}\x1b[0m", msg);
@@ -18,7 +60,7 @@ pub fn print_error(msg: &str) {
 }
 
 pub fn print_success(msg: &str) {
-    if let Some(support) = supports_color::on(Stream::Stdout) {
+    if let Some(support) = colors(Stream::Stdout) {
         if support.has_16m {
             println!("\x1b[38;2;0;255;0m{}\x1b[0m", msg);
         } else if support.has_256 {
@@ -32,7 +74,7 @@ pub fn print_success(msg: &str) {
 }
 
 pub fn print_warning(msg: &str) {
-    if let Some(support) = supports_color::on(Stream::Stdout) {
+    if let Some(support) = colors(Stream::Stdout) {
         if support.has_16m {
             // True color (RGB) yellow
             println!("\x1b[38;2;255;255;0m{}\x1b[0m", msg);
@@ -49,7 +91,7 @@ pub fn print_warning(msg: &str) {
 }
 
 pub fn print_info(msg: &str) {
-    if let Some(support) = supports_color::on(Stream::Stdout) {
+    if let Some(support) = colors(Stream::Stdout) {
         if support.has_16m {
             // True color (RGB) blue
             println!("\x1b[38;2;0;0;255m{}\x1b[0m", msg);
@@ -66,7 +108,7 @@ pub fn print_info(msg: &str) {
 }
 
 pub fn color_red(s: &str) -> String {
-    if let Some(support) = supports_color::on(Stream::Stdout) {
+    if let Some(support) = colors(Stream::Stdout) {
         if support.has_16m {
             format!("\x1b[38;2;255;0;0m{}\x1b[0m", s)
         } else if support.has_256 {
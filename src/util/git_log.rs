@@ -0,0 +1,119 @@
+use crate::error::git_error::GitLogError;
+use std::process::Command;
+
+/// A commit read from `git log`
+#[derive(Debug, Clone)]
+pub struct CommitInfo {
+    pub sha: String,
+    pub author_email: String,
+    pub message: String,
+}
+
+/// Separates the fields of `git log -z` output, the only byte git does not allow in a commit
+/// message. Each commit has [`LOG_FIELDS`] fields: the sha, the author email and the message.
+const FIELD_SEPARATOR: char = '\u{0}';
+const LOG_FIELDS: usize = 3;
+
+/// Run a git command and return its stdout, failing on a non-zero exit status.
+pub fn run_git_checked(args: &[&str]) -> Result<String, GitLogError> {
+    let output = Command::new("git").args(args).output()?;
+
+    if !output.status.success() {
+        return Err(GitLogError::CommandFailed {
+            args: args.join(" "),
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        });
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Paths staged for the next commit, relative to the repository root. Deleted files are left
+/// out, commands cannot run on them.
+pub fn staged_paths() -> Result<Vec<String>, GitLogError> {
+    let out = run_git_checked(&[
+        "diff",
+        "--cached",
+        "--name-only",
+        "--diff-filter=ACMR",
+        "-z",
+    ])?;
+    Ok(split_paths(&out))
+}
+
+/// Paths changed by the next commit, deleted files included, relative to the repository root
+pub fn staged_changes() -> Result<Vec<String>, GitLogError> {
+    let out = run_git_checked(&["diff", "--cached", "--name-only", "-z"])?;
+    Ok(split_paths(&out))
+}
+
+/// Paths changed by the commit `sha`, relative to the repository root. A merge is compared
+/// with its first parent, a root commit with the empty tree.
+pub fn commit_paths(sha: &str) -> Result<Vec<String>, GitLogError> {
+    let parent = format!("{}^", sha);
+    let out = if run_git_checked(&["rev-parse", "--verify", "--quiet", &parent]).is_ok() {
+        run_git_checked(&["diff-tree", "-r", "--name-only", "-z", &parent, sha])?
+    } else {
+        run_git_checked(&[
+            "diff-tree",
+            "-r",
+            "--name-only",
+            "-z",
+            "--no-commit-id",
+            "--root",
+            sha,
+        ])?
+    };
+    Ok(split_paths(&out))
+}
+
+/// Paths of `-z` output
+fn split_paths(out: &str) -> Vec<String> {
+    out.split('\0')
+        .filter(|p| !p.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// List the commits of a revision range (anything `git log` accepts, e.g. `origin/main..HEAD`),
+/// newest first.
+pub fn list_commits(range: &[&str]) -> Result<Vec<CommitInfo>, GitLogError> {
+    let mut args = vec!["log", "-z", "--format=%H%x00%ae%x00%B"];
+    args.extend_from_slice(range);
+    args.push("--");
+
+    let out = run_git_checked(&args)?;
+    Ok(parse_log_output(&out))
+}
+
+fn parse_log_output(out: &str) -> Vec<CommitInfo> {
+    // `-z` separates commits with NUL too, the last one is followed by one
+    let fields: Vec<&str> = out.split(FIELD_SEPARATOR).collect();
+    fields
+        .chunks_exact(LOG_FIELDS)
+        .map(|record| CommitInfo {
+            sha: record[0].to_string(),
+            author_email: record[1].to_string(),
+            message: record[2].trim_end().to_string(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_log_output() {
+        // any byte but NUL may appear in a message
+        let out = "aaa\u{0}a@example.com\u{0}feat: a\n\nbody \u{1e} x\n\u{0}bbb\u{0}b@example.com\u{0}fix: b\n\u{0}";
+        let commits = parse_log_output(out);
+
+        assert_eq!(commits.len(), 2);
+        assert_eq!(commits[0].sha, "aaa");
+        assert_eq!(commits[0].author_email, "a@example.com");
+        assert_eq!(commits[0].message, "feat: a\n\nbody \u{1e} x");
+        assert_eq!(commits[1].sha, "bbb");
+        assert_eq!(commits[1].message, "fix: b");
+    }
+}
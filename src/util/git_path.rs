@@ -23,6 +23,9 @@
 //! Provided functionality:
 //! - `detect_git_kind(path)` detects whether the repository is a normal repo,
 //!   worktree, or submodule by inspecting the `.git` file/directory.
+//! - `detect_recursive(path)` lists the repository with its worktrees and submodules.
+//! - `GitKind::hooks_dir()` returns the hooks directory git actually uses: `core.hooksPath`
+//!   when set, the type-specific default above otherwise.
 //! - `GitKind::hook_path(hook_name)` returns the actual hook installation path.
 //! - `GitKind::config_path(repo, file_name)` returns the configuration file path
 //!   located in the working directory root.
@@ -33,8 +36,35 @@
 //! - Ensure hook and config paths behave consistently across all repo types.
 
 use crate::error::git_error::GitKindError;
+use crate::util::git_log::run_git_checked;
 use std::fs;
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
+
+/// The directory git runs hooks from
+#[derive(Debug, Clone, PartialEq)]
+pub struct HooksDir {
+    pub path: PathBuf,
+    /// Value of `core.hooksPath`, when set
+    pub hooks_path: Option<String>,
+}
+
+impl HooksDir {
+    /// Whether `core.hooksPath` points into the working tree of `workdir` (outside `.git`),
+    /// where installed hooks show up as changes to commit
+    pub fn is_in_worktree(&self, workdir: &Path) -> bool {
+        if self.hooks_path.is_none() {
+            return false;
+        }
+
+        let path = self
+            .path
+            .canonicalize()
+            .unwrap_or_else(|_| self.path.clone());
+        path.strip_prefix(workdir).is_ok_and(|relative| {
+            relative.components().next() != Some(Component::Normal(".git".as_ref()))
+        })
+    }
+}
 
 pub enum GitKind {
     NormalRepo {
@@ -54,6 +84,24 @@ pub enum GitKind {
 }
 
 impl GitKind {
+    /// Short name of the repository type: `normal`, `worktree` or `submodule`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            GitKind::NormalRepo { .. } => "normal",
+            GitKind::Worktree { .. } => "worktree",
+            GitKind::Submodule { .. } => "submodule",
+        }
+    }
+
+    /// Returns the repository's own gitdir.
+    pub fn git_dir(&self) -> &Path {
+        match self {
+            GitKind::NormalRepo { git_dir, .. } => git_dir,
+            GitKind::Worktree { git_dir, .. } => git_dir,
+            GitKind::Submodule { git_dir, .. } => git_dir,
+        }
+    }
+
     /// Returns the working directory of this repository.
     pub fn workdir(&self) -> &Path {
         match self {
@@ -63,7 +111,7 @@ impl GitKind {
         }
     }
 
-    /// Returns the root directory where Git hooks are installed,
+    /// Returns the default root directory of Git hooks, without `core.hooksPath`,
     /// following Git’s actual behavior for normal repos, worktrees, and submodules.
     pub fn hooks_root(&self) -> PathBuf {
         match self {
@@ -73,10 +121,31 @@ impl GitKind {
         }
     }
 
+    /// Returns the hooks directory git uses: `core.hooksPath` when set in any configuration
+    /// git reads (repository, global or system), [`Self::hooks_root`] otherwise.
+    pub fn hooks_dir(&self) -> HooksDir {
+        self.hooks_dir_with(core_hooks_path(self.workdir()))
+    }
+
+    /// Like [`Self::hooks_dir`], with the given `core.hooksPath` value. A relative value is
+    /// resolved against the working directory root, where git runs hooks.
+    pub fn hooks_dir_with(&self, hooks_path: Option<String>) -> HooksDir {
+        match hooks_path.filter(|v| !v.is_empty()) {
+            Some(value) => HooksDir {
+                path: self.workdir().join(&value),
+                hooks_path: Some(value),
+            },
+            None => HooksDir {
+                path: self.hooks_root(),
+                hooks_path: None,
+            },
+        }
+    }
+
     /// Returns the full installation path of a specific Git hook.
-    /// The hook location depends on the repository type.
+    /// The hook location depends on the repository type and `core.hooksPath`.
     pub fn hook_path(&self, hook_name: &str) -> PathBuf {
-        self.hooks_root().join(hook_name)
+        self.hooks_dir().path.join(hook_name)
     }
 
     /// Returns the installation path of a configuration file.
@@ -116,7 +185,8 @@ pub fn detect_git_kind(path: impl AsRef<Path>) -> Result<GitKind, GitKindError>
     let commondir_path = gitdir.join("commondir");
     if commondir_path.exists() {
         let commondir_raw = fs::read_to_string(&commondir_path)?.trim().to_string();
-        let main_git_dir = resolve_gitdir(&gitdir, &commondir_raw)?.canonicalize()?;
+        // unlike the `.git` file, `commondir` is relative to the gitdir itself
+        let main_git_dir = gitdir.join(commondir_raw).canonicalize()?;
 
         return Ok(GitKind::Worktree {
             git_dir: gitdir.clone(),
@@ -147,6 +217,96 @@ pub fn detect_git_kind(path: impl AsRef<Path>) -> Result<GitKind, GitKindError>
     })
 }
 
+/// The repository at `path` with its worktrees and submodules, recursively: every working
+/// tree listed by `git worktree list` and every initialized submodule of `.gitmodules`, each
+/// once, starting with the repository at `path`. Uninitialized submodules, missing worktrees
+/// and bare repositories are left out.
+pub fn detect_recursive(path: impl AsRef<Path>) -> Result<Vec<GitKind>, GitKindError> {
+    let mut repos: Vec<GitKind> = Vec::new();
+    let mut pending = vec![detect_git_kind(path)?.workdir().to_path_buf()];
+
+    while let Some(workdir) = pending.pop() {
+        if repos.iter().any(|r| r.workdir() == workdir) {
+            continue;
+        }
+        let Ok(kind) = detect_git_kind(&workdir) else {
+            continue;
+        };
+        // a worktree or submodule path that is not a repository on its own
+        if kind.workdir() != workdir {
+            continue;
+        }
+
+        let mut related = submodule_workdirs(&workdir);
+        related.extend(worktree_workdirs(&workdir));
+        // depth first, in listing order
+        pending.extend(related.into_iter().rev());
+        repos.push(kind);
+    }
+
+    Ok(repos)
+}
+
+/// Working directories of the initialized submodules declared in `.gitmodules` of `workdir`
+fn submodule_workdirs(workdir: &Path) -> Vec<PathBuf> {
+    if !workdir.join(".gitmodules").is_file() {
+        return Vec::new();
+    }
+
+    let workdir_arg = workdir.to_string_lossy();
+    // exits with 1 when no submodule is declared
+    let out = run_git_checked(&[
+        "-C",
+        &workdir_arg,
+        "config",
+        "--file",
+        ".gitmodules",
+        "--get-regexp",
+        r"^submodule\..*\.path$",
+    ])
+    .unwrap_or_default();
+
+    out.lines()
+        .filter_map(|line| line.split_once(' '))
+        .map(|(_, path)| workdir.join(path.trim()))
+        .filter(|path| path.join(".git").exists())
+        .filter_map(|path| path.canonicalize().ok())
+        .collect()
+}
+
+/// Working directories of all worktrees of the repository at `workdir`, including the main one
+fn worktree_workdirs(workdir: &Path) -> Vec<PathBuf> {
+    let workdir_arg = workdir.to_string_lossy();
+    let out = run_git_checked(&["-C", &workdir_arg, "worktree", "list", "--porcelain"])
+        .unwrap_or_default();
+
+    // entries are separated by blank lines, a bare main repository has no working tree
+    out.split("\n\n")
+        .filter(|entry| !entry.lines().any(|line| line == "bare"))
+        .filter_map(|entry| {
+            entry
+                .lines()
+                .find_map(|line| line.strip_prefix("worktree "))
+        })
+        .filter_map(|path| PathBuf::from(path).canonicalize().ok())
+        .collect()
+}
+
+/// `core.hooksPath` of the repository at `workdir`, as resolved by git (`~/` expanded)
+fn core_hooks_path(workdir: &Path) -> Option<String> {
+    let workdir = workdir.to_string_lossy();
+    run_git_checked(&[
+        "-C",
+        &workdir,
+        "config",
+        "--type=path",
+        "--get",
+        "core.hooksPath",
+    ])
+    .ok()
+    .map(|v| v.trim().to_string())
+}
+
 fn find_workdir(start: impl AsRef<Path>) -> Result<PathBuf, GitKindError> {
     let mut dir = start.as_ref().canonicalize()?;
 
@@ -175,10 +335,10 @@ fn resolve_gitdir(base: &Path, gitdir: &str) -> Result<PathBuf, GitKindError> {
 
 #[cfg(test)]
 mod tests {
-    use crate::util::git_path::{GitKind, detect_git_kind};
+    use crate::util::git_path::{GitKind, detect_git_kind, detect_recursive};
 
     use std::fs;
-    use std::path::Path;
+    use std::path::{Path, PathBuf};
     use std::process::Command;
     use tempfile::TempDir;
 
@@ -261,7 +421,8 @@ mod tests {
                 println!("main_git_dir: {:?}", main_git_dir);
                 let canonical = main_git_dir.canonicalize().unwrap();
                 println!("canonical: {:?}", canonical);
-                assert!(canonical.join(".git").join("HEAD").exists());
+                assert!(canonical.ends_with(".git"));
+                assert!(canonical.join("HEAD").exists());
             }
             _ => panic!("should detect Worktree"),
         }
@@ -413,4 +574,69 @@ mod tests {
             _ => panic!("should detect Submodule"),
         }
     }
+
+    #[test]
+    #[ignore]
+    fn test_detect_recursive() {
+        let super_repo = TempDir::new().unwrap();
+        init_repo_with_master(super_repo.path());
+
+        let sub_src = super_repo.path().join("sub_src");
+        fs::create_dir(&sub_src).unwrap();
+        init_repo_with_master(&sub_src);
+        run(Command::new("git")
+            .current_dir(super_repo.path())
+            .arg("-c")
+            .arg("protocol.file.allow=always")
+            .arg("submodule")
+            .arg("add")
+            .arg("./sub_src")
+            .arg("sub"));
+
+        let wt = super_repo.path().join("wt");
+        run(Command::new("git")
+            .current_dir(super_repo.path())
+            .arg("worktree")
+            .arg("add")
+            .arg("-b")
+            .arg("wt-branch")
+            .arg(&wt));
+
+        let repos = detect_recursive(&wt).unwrap();
+        let names: Vec<&str> = repos.iter().map(GitKind::name).collect();
+        assert_eq!(names, ["worktree", "normal", "submodule"]);
+
+        // a worktree runs the hooks of its main repository
+        assert_eq!(repos[0].hooks_root(), repos[1].hooks_root());
+        assert_ne!(repos[1].hooks_root(), repos[2].hooks_root());
+    }
+
+    #[test]
+    fn test_hooks_dir_with_core_hooks_path() {
+        let dir = TempDir::new().unwrap();
+        let workdir = dir.path().canonicalize().unwrap();
+        let kind = GitKind::NormalRepo {
+            git_dir: workdir.join(".git"),
+            workdir: workdir.clone(),
+        };
+
+        let default = kind.hooks_dir_with(None);
+        assert_eq!(default.path, workdir.join(".git").join("hooks"));
+        assert!(!default.is_in_worktree(&workdir));
+
+        // relative to the working directory root, and shared with everyone when tracked
+        let husky = kind.hooks_dir_with(Some(".husky".to_string()));
+        assert_eq!(husky.path, workdir.join(".husky"));
+        assert!(husky.is_in_worktree(&workdir));
+
+        assert!(
+            !kind
+                .hooks_dir_with(Some(".git/custom-hooks".to_string()))
+                .is_in_worktree(&workdir)
+        );
+
+        let shared = kind.hooks_dir_with(Some("/opt/git-hooks".to_string()));
+        assert_eq!(shared.path, PathBuf::from("/opt/git-hooks"));
+        assert!(!shared.is_in_worktree(&workdir));
+    }
 }
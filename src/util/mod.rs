@@ -1,3 +1,5 @@
 pub mod colored_console;
 pub mod colored_print;
+pub mod git_log;
 pub mod git_path;
+pub mod similarity;
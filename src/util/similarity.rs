@@ -0,0 +1,180 @@
+//! String similarity used for typo detection of types, scopes and footer keywords
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Algorithm used to score the similarity of two words (0.0 ~ 1.0)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SimilarityAlgorithm {
+    /// Normalized Levenshtein distance
+    #[default]
+    Levenshtein,
+    /// Normalized Damerau-Levenshtein distance, transpositions (`fxi` → `fix`) count as one edit
+    Damerau,
+    /// Jaro-Winkler similarity, favors words sharing a common prefix
+    JaroWinkler,
+    /// Levenshtein distance where substituting a neighboring key on a QWERTY keyboard
+    /// costs half an edit (`fwat` → `feat`)
+    Keyboard,
+}
+
+/// Resolved spellcheck settings for one kind of word (type, scope or footer keyword)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpellcheckOptions {
+    pub algorithm: SimilarityAlgorithm,
+    /// Compare case-insensitively, so that `Feat` suggests the canonical `feat`
+    pub case_insensitive: bool,
+    /// Minimum similarity for a candidate to be suggested
+    pub threshold: f64,
+    /// Maximum number of suggestions
+    pub max_suggestions: usize,
+}
+
+impl SpellcheckOptions {
+    /// Default options with the given threshold
+    pub fn with_threshold(threshold: f64) -> Self {
+        Self {
+            algorithm: SimilarityAlgorithm::default(),
+            case_insensitive: true,
+            threshold,
+            max_suggestions: 3,
+        }
+    }
+}
+
+/// A candidate close to a misspelled word
+#[derive(Debug, Clone, PartialEq)]
+pub struct Suggestion {
+    pub candidate: String,
+    pub similarity: f64,
+}
+
+/// Similarity of two words according to the options (0.0 ~ 1.0)
+pub fn similarity(a: &str, b: &str, options: &SpellcheckOptions) -> f64 {
+    let (a, b) = if options.case_insensitive {
+        (a.to_lowercase(), b.to_lowercase())
+    } else {
+        (a.to_string(), b.to_string())
+    };
+
+    match options.algorithm {
+        SimilarityAlgorithm::Levenshtein => strsim::normalized_levenshtein(&a, &b),
+        SimilarityAlgorithm::Damerau => strsim::normalized_damerau_levenshtein(&a, &b),
+        SimilarityAlgorithm::JaroWinkler => strsim::jaro_winkler(&a, &b),
+        SimilarityAlgorithm::Keyboard => keyboard_similarity(&a, &b),
+    }
+}
+
+/// Candidates whose similarity to `word` reaches the threshold, best first.
+pub fn suggest(word: &str, candidates: &[String], options: &SpellcheckOptions) -> Vec<Suggestion> {
+    let mut suggestions: Vec<Suggestion> = candidates
+        .iter()
+        .map(|c| Suggestion {
+            candidate: c.clone(),
+            similarity: similarity(word, c, options),
+        })
+        .filter(|s| s.similarity >= options.threshold)
+        .collect();
+
+    suggestions.sort_by(|a, b| b.similarity.total_cmp(&a.similarity));
+    suggestions.truncate(options.max_suggestions.max(1));
+    suggestions
+}
+
+const KEYBOARD_ROWS: [&str; 4] = ["1234567890-=", "qwertyuiop[]", "asdfghjkl;'", "zxcvbnm,./"];
+
+/// Position of a key on a QWERTY keyboard as (row, column)
+fn key_position(c: char) -> Option<(i32, i32)> {
+    let c = c.to_ascii_lowercase();
+    KEYBOARD_ROWS.iter().enumerate().find_map(|(row, keys)| {
+        keys.chars()
+            .position(|k| k == c)
+            .map(|col| (row as i32, col as i32))
+    })
+}
+
+fn is_adjacent_key(a: char, b: char) -> bool {
+    match (key_position(a), key_position(b)) {
+        (Some((ra, ca)), Some((rb, cb))) => (ra - rb).abs() <= 1 && (ca - cb).abs() <= 1,
+        _ => false,
+    }
+}
+
+/// Normalized Levenshtein similarity where adjacent-key substitutions cost 0.5
+fn keyboard_similarity(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let max_len = a.len().max(b.len());
+    if max_len == 0 {
+        return 1.0;
+    }
+
+    let mut prev: Vec<f64> = (0..=b.len()).map(|j| j as f64).collect();
+    for (i, &ca) in a.iter().enumerate() {
+        let mut cur = vec![(i + 1) as f64; b.len() + 1];
+        for (j, &cb) in b.iter().enumerate() {
+            let substitution = if ca == cb {
+                0.0
+            } else if is_adjacent_key(ca, cb) {
+                0.5
+            } else {
+                1.0
+            };
+            cur[j + 1] = (prev[j] + substitution)
+                .min(prev[j + 1] + 1.0)
+                .min(cur[j] + 1.0);
+        }
+        prev = cur;
+    }
+
+    1.0 - prev[b.len()] / max_len as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidates() -> Vec<String> {
+        ["feat", "fix", "docs", "refactor", "test"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_case_insensitive_suggests_canonical_casing() {
+        let options = SpellcheckOptions::with_threshold(0.8);
+        let suggestions = suggest("Feat", &candidates(), &options);
+
+        assert_eq!(suggestions[0].candidate, "feat");
+        assert_eq!(suggestions[0].similarity, 1.0);
+    }
+
+    #[test]
+    fn test_algorithms() {
+        let mut options = SpellcheckOptions::with_threshold(0.0);
+
+        // transposition: one edit for Damerau, two for Levenshtein
+        let levenshtein = similarity("fxi", "fix", &options);
+        options.algorithm = SimilarityAlgorithm::Damerau;
+        assert!(similarity("fxi", "fix", &options) > levenshtein);
+
+        // `w` is next to `e` on the keyboard, `p` is not
+        options.algorithm = SimilarityAlgorithm::Keyboard;
+        assert!(similarity("fwat", "feat", &options) > similarity("fpat", "feat", &options));
+
+        options.algorithm = SimilarityAlgorithm::JaroWinkler;
+        assert!(similarity("refactr", "refactor", &options) > 0.9);
+    }
+
+    #[test]
+    fn test_multiple_suggestions() {
+        let mut options = SpellcheckOptions::with_threshold(0.5);
+        options.max_suggestions = 2;
+        let suggestions = suggest("fex", &candidates(), &options);
+
+        assert_eq!(suggestions.len(), 2);
+        assert!(suggestions[0].similarity >= suggestions[1].similarity);
+    }
+}
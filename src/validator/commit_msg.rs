@@ -1,41 +1,271 @@
-use crate::config::commit_msg_rule::ParsedCommitMsgRule;
+use crate::config::commit_msg_rule::{
+    CommitKindRule, ParsedCommitMsgRule, RuleCheck, Severity, SpellcheckTarget,
+};
 use crate::error::body_error::BodyError;
 use crate::error::body_error::BodyError::{BodyLineLengthInvalid, EmptyBody, TrailingWhitespace};
+use crate::error::commit_kind_error::CommitKindError;
 use crate::error::commit_msg_error::CommitMsgError;
 use crate::error::footer_error::FooterError;
 use crate::error::footer_error::FooterError::{
     FooterKeywordTypoError, FooterLineLengthInvalid, FooterStartKeywordInvalid,
-    FooterTrailingWhitespace,
+    FooterTrailingWhitespace, MissingRequiredKeyword,
 };
 use crate::error::header_error::HeaderError::{
-    EmptyAllowedScopes, EmptyAllowedTypes, EmptyScope, EmptySubject, InvalidSubjectLength,
-    NotAllowedScope, NotAllowedType, SpaceAfterColonNotMatch, SubjectEndsWithPeriod, TypeTypo,
+    EmptyAllowedScopes, EmptyAllowedTypes, EmptyHeader, EmptyScope, EmptySubject,
+    InvalidSubjectLength, NotAllowedScope, NotAllowedType, ScopeTypo, SpaceAfterColonNotMatch,
+    SubjectEndsWithPeriod, TypeTypo,
 };
-use crate::parser::commit_msg::ParsedCommitMessage;
-use crate::parser::header::ParsedHeader;
-use strsim::normalized_levenshtein;
+use crate::parser::commit_kind::{CommitKind, classify_commit_kind};
+use crate::parser::commit_msg::{ParsedCommitMessage, parse_commit_msg_with_rule};
+use crate::parser::get_first_non_empty_line;
+use crate::parser::header::{ParsedHeader, parse_header};
+use crate::util::similarity::{SpellcheckOptions, Suggestion, suggest};
+
+/// Where a commit message is being validated.
+/// Some commit kinds are accepted locally but rejected before merging.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidationMode {
+    /// The commit-msg hook, validating a commit that is being created
+    Hook,
+    /// Range linting (`gitru lint`), validating existing commits, usually in CI
+    Range,
+}
+
+/// A failed check whose severity is `warning`
+#[derive(Debug)]
+pub struct RuleWarning {
+    pub check: RuleCheck,
+    pub error: CommitMsgError,
+}
+
+/// A commit message that passed validation
+#[derive(Debug)]
+pub struct Validated {
+    pub kind: CommitKind,
+    pub warnings: Vec<RuleWarning>,
+}
+
+impl Validated {
+    /// Promote warnings to errors: fail with the first warning, if any.
+    pub fn into_strict(mut self) -> Result<Self, CommitMsgError> {
+        if self.warnings.is_empty() {
+            Ok(self)
+        } else {
+            Err(self.warnings.remove(0).error)
+        }
+    }
+}
+
+/// Classify, parse and validate a raw commit message.
+///
+/// Returns the detected [`CommitKind`] and the failed checks of severity `warning` on success.
+pub fn validate_raw_commit_msg(
+    raw: &str,
+    rule: &ParsedCommitMsgRule,
+    mode: ValidationMode,
+) -> Result<Validated, CommitMsgError> {
+    let header = get_first_non_empty_line(raw).ok_or(CommitMsgError::Header(EmptyHeader))?;
+    let kind = classify_commit_kind(&header, raw);
+    let mut warnings = Vec::new();
+
+    validate_commit_kind(&kind, &header, rule, mode, &mut warnings)?;
+
+    if kind == CommitKind::Regular {
+        let msg = parse_commit_msg_with_rule(raw, rule)?;
+        warnings.extend(validate_commit_msg(&msg, rule)?);
+    }
+
+    Ok(Validated { kind, warnings })
+}
+
+/// Apply the `[commit_kind]` policy to git-generated commits.
+/// Regular commits always pass here; they are validated by [`validate_commit_msg`].
+pub fn validate_commit_kind(
+    kind: &CommitKind,
+    header: &str,
+    rule: &ParsedCommitMsgRule,
+    mode: ValidationMode,
+    warnings: &mut Vec<RuleWarning>,
+) -> Result<(), CommitMsgError> {
+    let default_cfg = CommitKindRule::default();
+    let cfg = rule.commit_kind.as_ref().unwrap_or(&default_cfg);
+
+    match kind {
+        CommitKind::Regular => Ok(()),
+
+        CommitKind::Merge => {
+            if cfg.allow_merge.unwrap_or(true) {
+                Ok(())
+            } else {
+                Err(CommitMsgError::CommitKind(
+                    CommitKindError::MergeForbidden {
+                        header: header.trim().to_string(),
+                    },
+                ))
+            }
+        }
+
+        CommitKind::Revert {
+            original_header,
+            reverted_sha,
+        } => {
+            if cfg.require_revert_reference.unwrap_or(true) && reverted_sha.is_none() {
+                return Err(CommitMsgError::CommitKind(
+                    CommitKindError::MissingRevertReference,
+                ));
+            }
+
+            validate_header_str(original_header, rule, warnings).map_err(|e| {
+                CommitMsgError::CommitKind(CommitKindError::InvalidRevertedHeader {
+                    header: original_header.clone(),
+                    reason: e.to_string(),
+                })
+            })
+        }
+
+        CommitKind::Fixup { .. } | CommitKind::Squash { .. } | CommitKind::Amend { .. } => {
+            let allowed = match mode {
+                ValidationMode::Hook => cfg.allow_autosquash.unwrap_or(true),
+                ValidationMode::Range => cfg.allow_autosquash_in_range.unwrap_or(false),
+            };
+
+            if allowed {
+                return Ok(());
+            }
+
+            let marker = header.trim().split(' ').next().unwrap_or_default();
+            Err(CommitMsgError::CommitKind(
+                CommitKindError::AutosquashForbidden {
+                    marker: marker.to_string(),
+                    header: header.trim().to_string(),
+                },
+            ))
+        }
+    }
+}
+
+/// Parse a single header line and validate its type, scope and subject.
+fn validate_header_str(
+    header: &str,
+    rule: &ParsedCommitMsgRule,
+    warnings: &mut Vec<RuleWarning>,
+) -> Result<(), CommitMsgError> {
+    let parsed = parse_header(header)?;
+    validate_header(&parsed, rule, warnings)
+}
 
+/// Validate a parsed commit message against the rules.
+///
+/// Every check is run; failed checks are handled according to their configured severity.
+/// Returns the failed checks of severity `warning`, or the first failed check of severity `error`.
 pub fn validate_commit_msg(
     parsed_commit_msg: &ParsedCommitMessage,
     parsed_commit_msg_rule: &ParsedCommitMsgRule,
-) -> Result<bool, CommitMsgError> {
-    let header = &parsed_commit_msg.header;
+) -> Result<Vec<RuleWarning>, CommitMsgError> {
+    let rule = parsed_commit_msg_rule;
+    let mut warnings = Vec::new();
+
+    // 1. validate type, scope and subject
+    validate_header(&parsed_commit_msg.header, rule, &mut warnings)?;
+
+    // 2. validate body
+    for result in [
+        validate_body_required(parsed_commit_msg, rule),
+        validate_body_blank_lines(parsed_commit_msg, rule),
+        validate_body_trailing_whitespace(parsed_commit_msg, rule),
+        validate_body_line_length(parsed_commit_msg, rule),
+    ] {
+        apply_severity(result, rule, &mut warnings)?;
+    }
+
+    // 3. validate footer
+    for result in [
+        validate_misspelled_footer(parsed_commit_msg),
+        validate_footer_blank_lines(parsed_commit_msg, rule),
+        validate_footer_keyword(parsed_commit_msg, rule),
+        validate_footer_line_length(parsed_commit_msg, rule),
+        validate_footer_trailing_whitespace(parsed_commit_msg, rule),
+        validate_footer_required_keywords(parsed_commit_msg, rule),
+    ] {
+        apply_severity(result, rule, &mut warnings)?;
+    }
+
+    Ok(warnings)
+}
 
-    // 1. validate type
-    validate_type(header, parsed_commit_msg_rule)?;
+fn validate_header(
+    header: &ParsedHeader,
+    rule: &ParsedCommitMsgRule,
+    warnings: &mut Vec<RuleWarning>,
+) -> Result<(), CommitMsgError> {
+    for result in [
+        validate_type(header, rule),
+        validate_scope(header, rule),
+        validate_subject_spaces(header, rule),
+        validate_subject_period(header, rule),
+        validate_subject_length(header, rule),
+    ] {
+        apply_severity(result, rule, warnings)?;
+    }
+
+    Ok(())
+}
 
-    // 2. validate scope
-    validate_scope(header, parsed_commit_msg_rule)?;
+/// The check that produced an error, `None` for errors that cannot be downgraded
+/// (e.g. invalid configuration such as an empty `allowed_types`).
+pub fn rule_check_of(error: &CommitMsgError) -> Option<RuleCheck> {
+    let check = match error {
+        CommitMsgError::Header(e) => match e {
+            NotAllowedType { .. } | TypeTypo { .. } => RuleCheck::Type,
+            EmptyScope | NotAllowedScope { .. } | ScopeTypo { .. } => RuleCheck::Scope,
+            SpaceAfterColonNotMatch { .. } => RuleCheck::SubjectSpaces,
+            SubjectEndsWithPeriod => RuleCheck::SubjectPeriod,
+            InvalidSubjectLength { .. } => RuleCheck::SubjectLength,
+            _ => return None,
+        },
+        CommitMsgError::Body(e) => match e {
+            EmptyBody => RuleCheck::BodyRequired,
+            BodyError::BlankLinesBeforeBodyNotEnough { .. } => RuleCheck::BodyBlankLines,
+            TrailingWhitespace { .. } => RuleCheck::BodyTrailingWhitespace,
+            BodyLineLengthInvalid { .. } => RuleCheck::BodyLineLength,
+        },
+        CommitMsgError::Footer(e) => match e {
+            FooterError::BlankLinesBeforeFooterNotEnough { .. } => RuleCheck::FooterBlankLines,
+            FooterStartKeywordInvalid { .. } => RuleCheck::FooterKeyword,
+            FooterKeywordTypoError { .. } => RuleCheck::FooterKeywordTypo,
+            FooterLineLengthInvalid { .. } => RuleCheck::FooterLineLength,
+            FooterTrailingWhitespace { .. } => RuleCheck::FooterTrailingWhitespace,
+            MissingRequiredKeyword { .. } => RuleCheck::FooterRequiredKeyword,
+            _ => return None,
+        },
+        _ => return None,
+    };
+
+    Some(check)
+}
 
-    // 3. validate subject
-    validate_subject(header, parsed_commit_msg_rule)?;
+/// Turn a failed check into an error, a warning or nothing, according to its severity.
+fn apply_severity(
+    result: Result<(), CommitMsgError>,
+    rule: &ParsedCommitMsgRule,
+    warnings: &mut Vec<RuleWarning>,
+) -> Result<(), CommitMsgError> {
+    let Err(error) = result else {
+        return Ok(());
+    };
 
-    // 4. validate body
-    validate_body(parsed_commit_msg, parsed_commit_msg_rule)?;
+    let Some(check) = rule_check_of(&error) else {
+        return Err(error);
+    };
 
-    // 5. validate footer
-    validate_footer(parsed_commit_msg, parsed_commit_msg_rule)?;
-    Ok(true)
+    match rule.severity_of(check) {
+        Severity::Error => Err(error),
+        Severity::Warning => {
+            warnings.push(RuleWarning { check, error });
+            Ok(())
+        }
+        Severity::Off => Ok(()),
+    }
 }
 
 fn validate_type(header: &ParsedHeader, rule: &ParsedCommitMsgRule) -> Result<(), CommitMsgError> {
@@ -45,15 +275,14 @@ fn validate_type(header: &ParsedHeader, rule: &ParsedCommitMsgRule) -> Result<()
         }
 
         if !allowed.contains(&header.r#type) {
-            let threshold = 0.8;
+            let options = rule.spellcheck_options(SpellcheckTarget::Type);
 
-            if let Some((correct, similarity)) =
-                detect_type_typo(&header.r#type, allowed, threshold)
-            {
+            if let Some((best, others)) = detect_typo(&header.r#type, allowed, &options) {
                 return Err(CommitMsgError::Header(TypeTypo {
                     wrong: header.r#type.clone(),
-                    correct,
-                    similarity,
+                    correct: best.candidate,
+                    similarity: best.similarity,
+                    others,
                     allowed_types: allowed.clone(),
                 }));
             }
@@ -68,23 +297,18 @@ fn validate_type(header: &ParsedHeader, rule: &ParsedCommitMsgRule) -> Result<()
     Ok(())
 }
 
-pub fn detect_type_typo(wrong: &str, allowed: &[String], threshold: f64) -> Option<(String, f64)> {
-    let mut best = None;
-    let mut best_score = 0.0;
-
-    for valid in allowed {
-        let score = normalized_levenshtein(wrong, valid);
-        if score > best_score {
-            best_score = score;
-            best = Some(valid.clone());
-        }
-    }
-
-    if best_score >= threshold {
-        best.map(|b| (b, best_score))
-    } else {
-        None
-    }
+/// Find the closest candidates to a misspelled word.
+/// Returns the best suggestion and the other close candidates, if any reaches the threshold.
+pub fn detect_typo(
+    wrong: &str,
+    allowed: &[String],
+    options: &SpellcheckOptions,
+) -> Option<(Suggestion, Vec<String>)> {
+    let mut suggestions = suggest(wrong, allowed, options).into_iter();
+    let best = suggestions.next()?;
+    let others = suggestions.map(|s| s.candidate).collect();
+
+    Some((best, others))
 }
 
 fn validate_scope(header: &ParsedHeader, rule: &ParsedCommitMsgRule) -> Result<(), CommitMsgError> {
@@ -112,10 +336,7 @@ fn validate_scope(header: &ParsedHeader, rule: &ParsedCommitMsgRule) -> Result<(
 
         // 1.3 required + provided + allowed_scopes exists → check membership
         if !allowed_scopes.contains(scope_value) {
-            return Err(CommitMsgError::Header(NotAllowedScope {
-                scope: scope_value.clone(),
-                allowed_scopes: allowed_scopes.clone(),
-            }));
+            return Err(not_allowed_scope(scope_value, allowed_scopes, rule));
         }
 
         return Ok(());
@@ -139,24 +360,44 @@ fn validate_scope(header: &ParsedHeader, rule: &ParsedCommitMsgRule) -> Result<(
 
     // 2.4 optional + provided + allowed_scopes exists → check membership
     if !allowed_scopes.contains(scope_value) {
-        return Err(CommitMsgError::Header(NotAllowedScope {
-            scope: scope_value.clone(),
-            allowed_scopes: allowed_scopes.clone(),
-        }));
+        return Err(not_allowed_scope(scope_value, allowed_scopes, rule));
     }
 
     Ok(())
 }
 
-fn validate_subject(
+/// Error for a scope outside `allowed_scopes`, with suggestions when it looks like a typo
+fn not_allowed_scope(
+    scope: &str,
+    allowed_scopes: &[String],
+    rule: &ParsedCommitMsgRule,
+) -> CommitMsgError {
+    let options = rule.spellcheck_options(SpellcheckTarget::Scope);
+
+    if let Some((best, others)) = detect_typo(scope, allowed_scopes, &options) {
+        return CommitMsgError::Header(ScopeTypo {
+            wrong: scope.to_string(),
+            correct: best.candidate,
+            similarity: best.similarity,
+            others,
+            allowed_scopes: allowed_scopes.to_vec(),
+        });
+    }
+
+    CommitMsgError::Header(NotAllowedScope {
+        scope: scope.to_string(),
+        allowed_scopes: allowed_scopes.to_vec(),
+    })
+}
+
+fn validate_subject_spaces(
     header: &ParsedHeader,
     rule: &ParsedCommitMsgRule,
 ) -> Result<(), CommitMsgError> {
     let subject_cfg = &rule.header.subject;
-    let subject = &header.subject;
 
     //  validate subject is not empty
-    if subject.is_empty() {
+    if header.subject.is_empty() {
         return Err(CommitMsgError::Header(EmptySubject));
     }
 
@@ -170,6 +411,15 @@ fn validate_subject(
         }));
     }
 
+    Ok(())
+}
+
+fn validate_subject_period(
+    header: &ParsedHeader,
+    rule: &ParsedCommitMsgRule,
+) -> Result<(), CommitMsgError> {
+    let subject = &header.subject;
+
     // validate subject does not end with period
     if rule.header.subject.forbid_trailing_period
         && (subject.ends_with('.') || subject.ends_with('。'))
@@ -177,8 +427,17 @@ fn validate_subject(
         return Err(CommitMsgError::Header(SubjectEndsWithPeriod));
     }
 
+    Ok(())
+}
+
+fn validate_subject_length(
+    header: &ParsedHeader,
+    rule: &ParsedCommitMsgRule,
+) -> Result<(), CommitMsgError> {
+    let subject_cfg = &rule.header.subject;
+
     // validate subject length
-    let subject_len = subject.chars().count();
+    let subject_len = header.subject.chars().count();
     if subject_len < subject_cfg.min_length || subject_len > subject_cfg.max_length {
         return Err(CommitMsgError::Header(InvalidSubjectLength {
             min: subject_cfg.min_length,
@@ -190,7 +449,7 @@ fn validate_subject(
     Ok(())
 }
 
-pub fn validate_body(
+pub fn validate_body_required(
     parsed: &ParsedCommitMessage,
     rule: &ParsedCommitMsgRule,
 ) -> Result<(), CommitMsgError> {
@@ -203,8 +462,15 @@ pub fn validate_body(
         return Err(CommitMsgError::Body(EmptyBody));
     }
 
+    Ok(())
+}
+
+pub fn validate_body_blank_lines(
+    parsed: &ParsedCommitMessage,
+    rule: &ParsedCommitMsgRule,
+) -> Result<(), CommitMsgError> {
     // body is empty and not required → pass
-    let Some(body) = parsed.body.as_deref() else {
+    let (Some(body_rule), Some(_body)) = (&rule.body, parsed.body.as_deref()) else {
         return Ok(());
     };
 
@@ -218,6 +484,17 @@ pub fn validate_body(
         ));
     }
 
+    Ok(())
+}
+
+pub fn validate_body_trailing_whitespace(
+    parsed: &ParsedCommitMessage,
+    rule: &ParsedCommitMsgRule,
+) -> Result<(), CommitMsgError> {
+    let (Some(body_rule), Some(body)) = (&rule.body, parsed.body.as_deref()) else {
+        return Ok(());
+    };
+
     // validate trailing whitespace
     if body_rule.forbid_trailing_whitespace {
         for (i, line) in body.lines().enumerate() {
@@ -229,6 +506,17 @@ pub fn validate_body(
         }
     }
 
+    Ok(())
+}
+
+pub fn validate_body_line_length(
+    parsed: &ParsedCommitMessage,
+    rule: &ParsedCommitMsgRule,
+) -> Result<(), CommitMsgError> {
+    let (Some(body_rule), Some(body)) = (&rule.body, parsed.body.as_deref()) else {
+        return Ok(());
+    };
+
     // validate line length
     for (i, line) in body.lines().enumerate() {
         let trimmed = line.trim();
@@ -253,16 +541,27 @@ pub fn validate_body(
     Ok(())
 }
 
-pub fn validate_footer(
+/// A body line that looks like a misspelled footer keyword, detected while parsing
+fn validate_misspelled_footer(parsed: &ParsedCommitMessage) -> Result<(), CommitMsgError> {
+    if let Some((typo, threshold)) = &parsed.footer_keyword_typo {
+        return Err(CommitMsgError::Footer(FooterKeywordTypoError {
+            wrong: typo.wrong.clone(),
+            correct: typo.correct.clone(),
+            similarity: typo.similarity,
+            threshold: *threshold,
+            others: typo.others.clone(),
+        }));
+    }
+
+    Ok(())
+}
+
+pub fn validate_footer_blank_lines(
     parsed: &ParsedCommitMessage,
     rule: &ParsedCommitMsgRule,
 ) -> Result<(), CommitMsgError> {
-    let Some(footer_rule) = &rule.footer else {
-        return Ok(()); // no footer rule → pass
-    };
-
-    // footer is empty and not required → pass
-    let Some(_footer) = parsed.footer.as_deref() else {
+    // no footer rule, or footer is empty and not required → pass
+    let (Some(footer_rule), Some(_footer)) = (&rule.footer, parsed.footer.as_deref()) else {
         return Ok(());
     };
 
@@ -276,11 +575,20 @@ pub fn validate_footer(
         ));
     }
 
+    Ok(())
+}
+
+pub fn validate_footer_keyword(
+    parsed: &ParsedCommitMessage,
+    rule: &ParsedCommitMsgRule,
+) -> Result<(), CommitMsgError> {
+    let (Some(footer_rule), Some(footer_text)) = (&rule.footer, parsed.footer.as_deref()) else {
+        return Ok(());
+    };
+
     // validate footer start keyword
     // --- Smart footer keyword validation (supports typo detection) ---
     if !footer_rule.start_key_words.is_empty() {
-        let footer_text = parsed.footer.as_deref().ok_or(FooterError::MissingFooter)?;
-
         let first_line = footer_text.lines().next().unwrap_or("").trim();
 
         // Extract keyword before colon
@@ -296,63 +604,71 @@ pub fn validate_footer(
 
         let keyword = keyword.trim();
 
+        // Exact match → completely correct
+        if footer_rule.start_key_words.iter().any(|k| k == keyword) {
+            return Ok(());
+        }
+
         // Load spellcheck config
-        #[allow(clippy::expect_used)]
-        let spell_cfg = footer_rule
-            .start_key_words_spellcheck
-            .as_ref()
-            .expect("start_key_words_spellcheck must exist when start_key_words is non-empty");
-        let threshold = spell_cfg.threshold;
+        let options = rule.spellcheck_options(SpellcheckTarget::FooterKeyword);
 
         // Find best match by similarity
-        let best_match = footer_rule
-            .start_key_words
-            .iter()
-            .map(|k| (k, strsim::normalized_levenshtein(keyword, k)))
-            .max_by(|a, b| a.1.total_cmp(&b.1));
-
-        if let Some((correct, similarity)) = best_match {
-            if similarity < threshold {
-                // Similarity is too low → treat as not a footer
-                return Err(CommitMsgError::Footer(FooterStartKeywordInvalid {
-                    actual: keyword.to_string(),
-                    allowed: footer_rule.start_key_words.clone(),
-                }));
-            }
-
-            if similarity < 1.0 {
-                // Similarity is high enough but not a perfect match → spelling error
-                return Err(CommitMsgError::Footer(FooterKeywordTypoError {
-                    wrong: keyword.to_string(),
-                    correct: correct.clone(),
-                    similarity,
-                    threshold,
-                }));
-            }
+        let Some((best, others)) = detect_typo(keyword, &footer_rule.start_key_words, &options)
+        else {
+            // Similarity is too low → treat as not a footer
+            return Err(CommitMsgError::Footer(FooterStartKeywordInvalid {
+                actual: keyword.to_string(),
+                allowed: footer_rule.start_key_words.clone(),
+            }));
+        };
 
-            // similarity == 1.0 → completely correct
-        }
+        // Similarity is high enough but not an exact match → spelling error
+        return Err(CommitMsgError::Footer(FooterKeywordTypoError {
+            wrong: keyword.to_string(),
+            correct: best.candidate,
+            similarity: best.similarity,
+            threshold: options.threshold,
+            others,
+        }));
     }
 
-    if let Some(footer) = &parsed.footer {
-        for (i, line) in footer.lines().enumerate() {
-            let width = line.chars().count();
+    Ok(())
+}
 
-            if width < footer_rule.min_line_length || width > footer_rule.max_line_length {
-                return Err(CommitMsgError::Footer(FooterLineLengthInvalid {
-                    line_number: i + 1,
-                    min: footer_rule.min_line_length,
-                    max: footer_rule.max_line_length,
-                    actual: width,
-                }));
-            }
+pub fn validate_footer_line_length(
+    parsed: &ParsedCommitMessage,
+    rule: &ParsedCommitMsgRule,
+) -> Result<(), CommitMsgError> {
+    let (Some(footer_rule), Some(footer)) = (&rule.footer, parsed.footer.as_deref()) else {
+        return Ok(());
+    };
+
+    for (i, line) in footer.lines().enumerate() {
+        let width = line.chars().count();
+
+        if width < footer_rule.min_line_length || width > footer_rule.max_line_length {
+            return Err(CommitMsgError::Footer(FooterLineLengthInvalid {
+                line_number: i + 1,
+                min: footer_rule.min_line_length,
+                max: footer_rule.max_line_length,
+                actual: width,
+            }));
         }
     }
 
+    Ok(())
+}
+
+pub fn validate_footer_trailing_whitespace(
+    parsed: &ParsedCommitMessage,
+    rule: &ParsedCommitMsgRule,
+) -> Result<(), CommitMsgError> {
+    let (Some(footer_rule), Some(footer)) = (&rule.footer, parsed.footer.as_deref()) else {
+        return Ok(());
+    };
+
     // validate footer trailing whitespace
-    if footer_rule.forbid_trailing_whitespace
-        && let Some(footer) = &parsed.footer
-    {
+    if footer_rule.forbid_trailing_whitespace {
         for (i, line) in footer.lines().enumerate() {
             if line.ends_with(' ') {
                 return Err(CommitMsgError::Footer(FooterTrailingWhitespace {
@@ -365,6 +681,81 @@ pub fn validate_footer(
     Ok(())
 }
 
+pub fn validate_footer_required_keywords(
+    parsed: &ParsedCommitMessage,
+    rule: &ParsedCommitMsgRule,
+) -> Result<(), CommitMsgError> {
+    let Some(required) = rule
+        .footer
+        .as_ref()
+        .and_then(|f| f.required_key_words.as_ref())
+    else {
+        return Ok(());
+    };
+
+    let footer = parsed.footer.as_deref().unwrap_or("");
+    for keyword in required {
+        let present = footer.lines().any(|line| {
+            line.split_once(':')
+                .is_some_and(|(k, _)| k.trim() == keyword.as_str())
+        });
+
+        if !present {
+            return Err(CommitMsgError::Footer(MissingRequiredKeyword {
+                keyword: keyword.clone(),
+            }));
+        }
+    }
+
+    Ok(())
+}
+
+/// A commit message with a misspelled type or scope replaced by its suggestion
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fix {
+    pub message: String,
+    /// What was changed, e.g. ``commit type `fature` → `feat` ``
+    pub description: String,
+}
+
+/// Fix the error of a failed validation when it is a misspelled type or scope, see
+/// `auto_fix` in [`crate::config::user`]. Only the header is changed.
+pub fn fix_commit_msg(raw: &str, error: &CommitMsgError) -> Option<Fix> {
+    let (start, line) = raw
+        .split_inclusive('\n')
+        .scan(0, |offset, line| {
+            let start = *offset;
+            *offset += line.len();
+            Some((start, line))
+        })
+        .find(|(_, line)| !line.trim().is_empty())?;
+
+    let (fixed, description) = match error {
+        CommitMsgError::Header(TypeTypo { wrong, correct, .. }) => {
+            let indent = line.len() - line.trim_start().len();
+            let rest = line[indent..].strip_prefix(wrong.as_str())?;
+            (
+                format!("{}{}{}", &line[..indent], correct, rest),
+                format!("commit type `{}` → `{}`", wrong, correct),
+            )
+        }
+        CommitMsgError::Header(ScopeTypo { wrong, correct, .. }) => {
+            let scope = format!("({})", wrong);
+            line.find(&scope)?;
+            (
+                line.replacen(&scope, &format!("({})", correct), 1),
+                format!("scope `{}` → `{}`", wrong, correct),
+            )
+        }
+        _ => return None,
+    };
+
+    Some(Fix {
+        message: format!("{}{}{}", &raw[..start], fixed, &raw[start + line.len()..]),
+        description,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -406,6 +797,7 @@ mod tests {
                 wrong,
                 correct,
                 similarity,
+                others: _,
                 allowed_types: _,
             }) => {
                 assert_eq!(*correct, "feat".to_string());
@@ -419,6 +811,136 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_commit_kind_policy() {
+        let rule =
+            config::commit_msg_rule::parse_commit_msg_rule(COMMIT_MSG_RULE_TEMPLATE).unwrap();
+
+        // merge commits are allowed by default
+        let validated =
+            validate_raw_commit_msg("Merge branch 'dev'", &rule, ValidationMode::Hook).unwrap();
+        assert_eq!(validated.kind, CommitKind::Merge);
+
+        // revert with a reference and a valid original header
+        let revert = "Revert \"feat: add parser\"\n\nThis reverts commit 1a2b3c4d.\n";
+        assert!(validate_raw_commit_msg(revert, &rule, ValidationMode::Hook).is_ok());
+
+        // revert without `This reverts commit <sha>`
+        let revert = "Revert \"feat: add parser\"";
+        assert!(matches!(
+            validate_raw_commit_msg(revert, &rule, ValidationMode::Hook),
+            Err(CommitMsgError::CommitKind(
+                CommitKindError::MissingRevertReference
+            ))
+        ));
+
+        // revert of a header that is not valid itself
+        let revert = "Revert \"feats: add parser\"\n\nThis reverts commit 1a2b3c4d.";
+        assert!(matches!(
+            validate_raw_commit_msg(revert, &rule, ValidationMode::Hook),
+            Err(CommitMsgError::CommitKind(
+                CommitKindError::InvalidRevertedHeader { .. }
+            ))
+        ));
+
+        // fixup commits pass locally but not in range linting
+        let fixup = "fixup! feat: add parser";
+        assert!(validate_raw_commit_msg(fixup, &rule, ValidationMode::Hook).is_ok());
+        assert!(matches!(
+            validate_raw_commit_msg(fixup, &rule, ValidationMode::Range),
+            Err(CommitMsgError::CommitKind(
+                CommitKindError::AutosquashForbidden { .. }
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_severity() {
+        let template = COMMIT_MSG_RULE_TEMPLATE
+            .replace("subject_period = \"error\"", "subject_period = \"warning\"")
+            .replace(
+                "body_trailing_whitespace = \"error\"",
+                "body_trailing_whitespace = \"off\"",
+            );
+        let rule = config::commit_msg_rule::parse_commit_msg_rule(&template).unwrap();
+        let msg = "feat: add parser.\n\nbody with trailing space \n";
+
+        // period is a warning, trailing whitespace is off → passes with one warning
+        let validated = validate_raw_commit_msg(msg, &rule, ValidationMode::Hook).unwrap();
+        assert_eq!(validated.warnings.len(), 1);
+        assert_eq!(validated.warnings[0].check, RuleCheck::SubjectPeriod);
+
+        // strict promotes the warning to an error
+        assert!(matches!(
+            validated.into_strict(),
+            Err(CommitMsgError::Header(SubjectEndsWithPeriod))
+        ));
+
+        // errors are still reported after a warning
+        let msg = "feats: add parser.";
+        assert!(matches!(
+            validate_raw_commit_msg(msg, &rule, ValidationMode::Hook),
+            Err(CommitMsgError::Header(TypeTypo { .. }))
+        ));
+    }
+
+    #[test]
+    fn test_scope_typo_and_casing() {
+        let rule =
+            config::commit_msg_rule::parse_commit_msg_rule(COMMIT_MSG_RULE_TEMPLATE).unwrap();
+
+        // scope close to an allowed one → suggestion
+        let err = validate_raw_commit_msg("feat(docss): add parser", &rule, ValidationMode::Hook)
+            .unwrap_err();
+        match err {
+            CommitMsgError::Header(ScopeTypo { correct, .. }) => assert_eq!(correct, "docs"),
+            other => panic!("unexpected error: {:?}", other),
+        }
+
+        // wrong casing → canonical casing is suggested
+        let err =
+            validate_raw_commit_msg("Feat: add parser", &rule, ValidationMode::Hook).unwrap_err();
+        match err {
+            CommitMsgError::Header(TypeTypo {
+                correct,
+                similarity,
+                ..
+            }) => {
+                assert_eq!(correct, "feat");
+                assert_eq!(similarity, 1.0);
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_fix_commit_msg() {
+        let rule =
+            config::commit_msg_rule::parse_commit_msg_rule(COMMIT_MSG_RULE_TEMPLATE).unwrap();
+
+        let mut msg = "\nfeats(docss): add parser\n\nfeats(docss) stays in the body\n".to_string();
+        let mut fixes = Vec::new();
+        while let Err(e) = validate_raw_commit_msg(&msg, &rule, ValidationMode::Hook) {
+            let fix = fix_commit_msg(&msg, &e).unwrap();
+            msg = fix.message;
+            fixes.push(fix.description);
+        }
+
+        assert_eq!(
+            msg,
+            "\nfeat(docs): add parser\n\nfeats(docss) stays in the body\n"
+        );
+        assert_eq!(
+            fixes,
+            ["commit type `feats` → `feat`", "scope `docss` → `docs`"]
+        );
+
+        // only typos with a suggestion are fixed
+        let err =
+            validate_raw_commit_msg("feat: add parser.", &rule, ValidationMode::Hook).unwrap_err();
+        assert_eq!(fix_commit_msg("feat: add parser.", &err), None);
+    }
+
     #[test]
     fn test_valid_body_and_footer() {
         let msg = "\
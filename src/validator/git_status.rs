@@ -1,12 +1,23 @@
 use crate::error::git_error::ConfigStatusCheckError;
-use crate::util::colored_print::print_warning;
+use std::path::Path;
 use std::process::Command;
 
-/// Run a Git command and return its stdout as a String.
+/// Git status of the configuration file, as far as it matters for committing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigStatus {
+    /// Clean or staged: the committed rules are the ones in effect
+    Tracked,
+    /// The file exists but is not tracked by git
+    Untracked,
+}
+
+/// Run a Git command in the repository at `workdir` and return its stdout as a String.
 /// Git commands must use paths relative to the repository root.
 /// Passing absolute paths will cause Git to misinterpret file status.
-fn run_git(args: &[&str]) -> Result<String, ConfigStatusCheckError> {
+fn run_git(workdir: &Path, args: &[&str]) -> Result<String, ConfigStatusCheckError> {
     let output = Command::new("git")
+        .arg("-C")
+        .arg(workdir)
         .args(args)
         .output()
         .map_err(|e| ConfigStatusCheckError::GitError(e.to_string()))?;
@@ -26,8 +37,8 @@ fn run_git(args: &[&str]) -> Result<String, ConfigStatusCheckError> {
 ///   "?? file" → untracked  
 ///
 /// Returns (' ', ' ') if the file does not appear in the porcelain output.  
-fn get_git_status(path: &str) -> Result<(char, char), ConfigStatusCheckError> {
-    let out = run_git(&["status", "--porcelain"])?;
+fn get_git_status(workdir: &Path, path: &str) -> Result<(char, char), ConfigStatusCheckError> {
+    let out = run_git(workdir, &["status", "--porcelain"])?;
     for line in out.lines() {
         if line.len() < 3 {
             continue;
@@ -50,32 +61,40 @@ fn get_git_status(path: &str) -> Result<(char, char), ConfigStatusCheckError> {
     Ok((' ', ' ')) // Clean: no staged or unstaged changes
 }
 
-/// Validate the Git status of the configuration file.
+/// Warning shown when the configuration file is not tracked by git
+pub fn untracked_config_warning(config: &str) -> String {
+    format!(
+        "warning: configuration file '{}' exists but is not tracked by git. \
+         It is recommended to add it to version control.",
+        config
+    )
+}
+
+/// Validate the Git status of the configuration file at `config`, relative to the root of the
+/// repository at `workdir`.
 ///
 /// Rules:
-/// - Untracked file → warn but allow commit
+/// - Untracked file → `ConfigStatus::Untracked`, the caller should warn but allow commit
 /// - Modified but not staged (Y == 'M') → block commit
 /// - All other states → allow commit
 ///
 /// This ensures users do not accidentally commit with unstaged config changes,
 /// including partially staged modifications.
-pub fn check_config_status(config: &str) -> Result<(), ConfigStatusCheckError> {
+pub fn check_config_status(
+    workdir: &Path,
+    config: &str,
+) -> Result<ConfigStatus, ConfigStatusCheckError> {
     // Ensure the file exists before checking Git status.
-    if !std::path::Path::new(config).exists() {
+    if !workdir.join(config).exists() {
         return Err(ConfigStatusCheckError::ConfigNotExist(config.to_string()));
     }
 
-    let (x, y) = get_git_status(config)?;
+    let (x, y) = get_git_status(workdir, config)?;
 
-    // Untracked file: warn but do not block the commit.
+    // Untracked file: do not block the commit, the caller warns about it.
     // Users may intentionally add the config file later.
     if x == '?' && y == '?' {
-        print_warning(&format!(
-            "warning: configuration file '{}' exists but is not tracked by git. \
-             It is recommended to add it to version control.",
-            config
-        ));
-        return Ok(());
+        return Ok(ConfigStatus::Untracked);
     }
 
     // Working tree modification not staged (Y == 'M'):
@@ -88,17 +107,18 @@ pub fn check_config_status(config: &str) -> Result<(), ConfigStatusCheckError> {
     }
 
     // All other states (clean, staged-only, etc.) → allow commit.
-    Ok(())
+    Ok(ConfigStatus::Tracked)
 }
 
 #[cfg(test)]
 mod tests {
     use crate::constant::COMMIT_MSG_RULE_FILE_NAME;
     use crate::validator::git_status::check_config_status;
+    use std::path::Path;
 
     #[test]
     fn test_check_config_status() {
         let file = COMMIT_MSG_RULE_FILE_NAME;
-        println!("{:?}", check_config_status(file));
+        println!("{:?}", check_config_status(Path::new("."), file));
     }
 }
@@ -1,5 +1,8 @@
 pub mod commit_msg;
 pub mod git_status;
+pub mod nested;
+pub mod range;
+pub mod skip;
 
 pub struct ErrorCollector<E> {
     pub errors: Vec<E>,
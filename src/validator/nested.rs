@@ -0,0 +1,188 @@
+//! Validation of a commit message with several rule files, combined with
+//! `global.nested_rules` of the root rule file, see [`crate::config::nested`]
+
+use crate::config::commit_msg_rule::{NestedRulesPolicy, ParsedCommitMsgRule};
+use crate::error::commit_msg_error::CommitMsgError;
+use crate::parser::commit_kind::CommitKind;
+use crate::validator::commit_msg::Validated;
+use std::path::Path;
+
+/// A rule file that rejected a commit message
+#[derive(Debug)]
+pub struct RuleFileRejection<'a> {
+    pub rule_file: &'a Path,
+    pub error: CommitMsgError,
+}
+
+/// Validate a commit message with each rule file of `targets`, `validate` validating it with
+/// one rule file, and combine the results with `policy`:
+/// - `all` rejects at the first rule file that rejects, keeping the warnings of all of them
+/// - `any` accepts at the first rule file that accepts, or rejects with every rejection
+///
+/// A rule file with `enable_validation = false` accepts under `any` and is skipped otherwise.
+pub fn validate_with_rule_files<'a>(
+    targets: &[(&'a Path, &'a ParsedCommitMsgRule)],
+    policy: NestedRulesPolicy,
+    mut validate: impl FnMut(&ParsedCommitMsgRule) -> Result<Validated, CommitMsgError>,
+) -> Result<Validated, Vec<RuleFileRejection<'a>>> {
+    let mut combined = Validated {
+        kind: CommitKind::Regular,
+        warnings: Vec::new(),
+    };
+    let mut rejections = Vec::new();
+
+    for &(rule_file, rule) in targets {
+        // a subdirectory rule file can turn validation off for its files
+        if rule.global.as_ref().and_then(|g| g.enable_validation) == Some(false) {
+            if policy == NestedRulesPolicy::Any {
+                return Ok(Validated {
+                    kind: CommitKind::Regular,
+                    warnings: Vec::new(),
+                });
+            }
+            continue;
+        }
+
+        match validate(rule) {
+            Ok(validated) if policy == NestedRulesPolicy::Any => return Ok(validated),
+            Ok(validated) => {
+                combined.kind = validated.kind;
+                combined.warnings.extend(validated.warnings);
+            }
+            Err(error) => {
+                rejections.push(RuleFileRejection { rule_file, error });
+                if policy != NestedRulesPolicy::Any {
+                    return Err(rejections);
+                }
+            }
+        }
+    }
+
+    if rejections.is_empty() {
+        Ok(combined)
+    } else {
+        Err(rejections)
+    }
+}
+
+/// The error of `rejections`, one line per rule file. With `name_rule_files`, each line names
+/// its rule file as shown by `display`.
+pub fn rejection_message(
+    rejections: &[RuleFileRejection],
+    name_rule_files: bool,
+    display: impl Fn(&Path) -> String,
+) -> String {
+    rejections
+        .iter()
+        .map(|r| {
+            if name_rule_files {
+                format!("error: {} (rule file: {})", r.error, display(r.rule_file))
+            } else {
+                format!("error: {}", r.error)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::commit_msg_rule::parse_commit_msg_rule;
+    use crate::validator::commit_msg::{ValidationMode, validate_raw_commit_msg};
+
+    fn rule(allowed_types: &str) -> ParsedCommitMsgRule {
+        parse_commit_msg_rule(&format!(
+            "[header.type]\nallowed_types = [{}]\n",
+            allowed_types
+        ))
+        .unwrap()
+    }
+
+    fn validate(
+        msg: &'static str,
+    ) -> impl FnMut(&ParsedCommitMsgRule) -> Result<Validated, CommitMsgError> {
+        move |rule| validate_raw_commit_msg(msg, rule, ValidationMode::Hook)
+    }
+
+    fn display(path: &Path) -> String {
+        path.display().to_string()
+    }
+
+    #[test]
+    fn test_all_rejects_with_one_rule_file() {
+        let (web, api) = (rule("\"feat\", \"fix\""), rule("\"feat\""));
+        let targets = [
+            (Path::new("web/rule.toml"), &web),
+            (Path::new("api/rule.toml"), &api),
+        ];
+
+        assert!(
+            validate_with_rule_files(&targets, NestedRulesPolicy::All, validate("feat: add"))
+                .is_ok()
+        );
+
+        let rejections =
+            validate_with_rule_files(&targets, NestedRulesPolicy::All, validate("fix: typo"))
+                .unwrap_err();
+        assert_eq!(rejections.len(), 1);
+        assert_eq!(rejections[0].rule_file, Path::new("api/rule.toml"));
+    }
+
+    #[test]
+    fn test_any_accepts_with_one_rule_file() {
+        let (api, web) = (rule("\"feat\""), rule("\"fix\""));
+        let targets = [
+            (Path::new("api/rule.toml"), &api),
+            (Path::new("web/rule.toml"), &web),
+        ];
+
+        assert!(
+            validate_with_rule_files(&targets, NestedRulesPolicy::Any, validate("fix: typo"))
+                .is_ok()
+        );
+
+        let rejections =
+            validate_with_rule_files(&targets, NestedRulesPolicy::Any, validate("docs: readme"))
+                .unwrap_err();
+        assert_eq!(rejections.len(), 2);
+    }
+
+    #[test]
+    fn test_disabled_rule_file() {
+        let disabled = parse_commit_msg_rule("[global]\nenable_validation = false\n").unwrap();
+        let api = rule("\"feat\"");
+        let targets = [
+            (Path::new("docs/rule.toml"), &disabled),
+            (Path::new("api/rule.toml"), &api),
+        ];
+
+        // accepted without validating with the other rule files
+        let mut validated = 0;
+        let result = validate_with_rule_files(&targets, NestedRulesPolicy::Any, |rule| {
+            validated += 1;
+            validate("docs: readme")(rule)
+        });
+        assert!(result.is_ok());
+        assert_eq!(validated, 0);
+
+        assert!(
+            validate_with_rule_files(&targets, NestedRulesPolicy::All, validate("docs: readme"))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_rejection_message() {
+        let api = rule("\"feat\"");
+        let targets = [(Path::new("api/rule.toml"), &api)];
+        let rejections =
+            validate_with_rule_files(&targets, NestedRulesPolicy::All, validate("fix: typo"))
+                .unwrap_err();
+
+        let named = rejection_message(&rejections, true, display);
+        assert!(named.starts_with("error: "));
+        assert!(named.ends_with("(rule file: api/rule.toml)"));
+        assert!(!rejection_message(&rejections, false, display).contains("rule file"));
+    }
+}
@@ -0,0 +1,227 @@
+use crate::config::commit_msg_rule::ParsedCommitMsgRule;
+use crate::error::commit_msg_error::CommitMsgError;
+use crate::parser::get_first_non_empty_line;
+use crate::util::git_log::CommitInfo;
+use crate::validator::commit_msg::{ValidationMode, validate_raw_commit_msg};
+use crate::validator::nested::validate_with_rule_files;
+use crate::validator::skip::{check_recorded_skip, find_skip_trailer, skip_policy};
+use std::path::{Path, PathBuf};
+
+/// Result of linting a range of commits
+#[derive(Debug, Default)]
+pub struct RangeLintReport {
+    /// Commits whose message failed validation, once per rule file that rejected it
+    pub violations: Vec<RangeViolation>,
+    /// Failed checks of severity `warning`, one entry per check
+    pub warnings: Vec<RangeViolation>,
+    /// Commits that skipped validation, recorded by a `Validation-Skipped` trailer
+    pub skipped: Vec<SkippedCommit>,
+}
+
+/// A commit in a range whose message failed a check
+#[derive(Debug)]
+pub struct RangeViolation {
+    pub sha: String,
+    pub header: String,
+    /// The rule file of the check, when it is not the root rule file
+    pub rule_file: Option<PathBuf>,
+    pub error: CommitMsgError,
+}
+
+impl RangeLintReport {
+    /// Shas of the commits whose message failed validation, each once, in range order
+    pub fn failed_commits(&self) -> Vec<&str> {
+        let mut shas: Vec<&str> = Vec::new();
+        for violation in &self.violations {
+            if !shas.contains(&violation.sha.as_str()) {
+                shas.push(&violation.sha);
+            }
+        }
+        shas
+    }
+}
+
+/// A commit in a range that skipped validation when it was created
+#[derive(Debug)]
+pub struct SkippedCommit {
+    pub sha: String,
+    pub header: String,
+    pub reason: String,
+}
+
+/// Abbreviated sha, as shown by `git log --oneline`
+pub fn short_sha(sha: &str) -> &str {
+    sha.get(..10).unwrap_or(sha)
+}
+
+/// Validate every commit message in `commits` with [`ValidationMode::Range`],
+/// collecting all violations instead of stopping at the first one.
+///
+/// Commits carrying a `Validation-Skipped` trailer are not validated,
+/// but reported so that reviewers can see the bypass. The skip is checked against the
+/// `[global.skip_validation]` policy again, a trailer the policy does not allow is a violation.
+/// With `strict`, failed checks of severity `warning` are reported as violations.
+pub fn lint_commits(
+    commits: &[CommitInfo],
+    rule: &ParsedCommitMsgRule,
+    strict: bool,
+) -> RangeLintReport {
+    let root = Path::new("");
+    lint_commits_with_rule_files(commits, Some((root, rule)), strict, |_| vec![(root, rule)])
+}
+
+/// Like [`lint_commits`], validating each commit with the rule files `rule_files` selects for
+/// it, combined with `global.nested_rules` of the root rule file, see
+/// [`validate_with_rule_files`]. The root rule file also holds the skip policy. Commits without
+/// rule files are not validated; violations of other rule files than the root one name them.
+pub fn lint_commits_with_rule_files<'a>(
+    commits: &[CommitInfo],
+    root: Option<(&Path, &ParsedCommitMsgRule)>,
+    strict: bool,
+    mut rule_files: impl FnMut(&CommitInfo) -> Vec<(&'a Path, &'a ParsedCommitMsgRule)>,
+) -> RangeLintReport {
+    let mut report = RangeLintReport::default();
+    let global = root.and_then(|(_, rule)| rule.global.as_ref());
+    let policy = skip_policy(global);
+    let nested_rules = global.and_then(|g| g.nested_rules).unwrap_or_default();
+
+    for commit in commits {
+        let header = get_first_non_empty_line(&commit.message).unwrap_or_default();
+
+        if let Some(reason) = find_skip_trailer(&commit.message) {
+            if let Err(e) = check_recorded_skip(&reason, &policy, &commit.author_email) {
+                report.violations.push(RangeViolation {
+                    sha: commit.sha.clone(),
+                    header,
+                    rule_file: None,
+                    error: e.into(),
+                });
+                continue;
+            }
+            report.skipped.push(SkippedCommit {
+                sha: commit.sha.clone(),
+                header,
+                reason,
+            });
+            continue;
+        }
+
+        let targets = rule_files(commit);
+        if targets.is_empty() {
+            continue;
+        }
+        let validated = validate_with_rule_files(&targets, nested_rules, |rule| {
+            validate_raw_commit_msg(&commit.message, rule, ValidationMode::Range)
+                .and_then(|v| if strict { v.into_strict() } else { Ok(v) })
+        });
+
+        // the root rule file is not named, as with a single rule file
+        let rule_file =
+            |path: &Path| (root.is_none_or(|(root, _)| root != path)).then(|| path.to_path_buf());
+        match validated {
+            Ok(validated) => {
+                report
+                    .warnings
+                    .extend(validated.warnings.into_iter().map(|w| RangeViolation {
+                        sha: commit.sha.clone(),
+                        header: header.clone(),
+                        rule_file: None,
+                        error: w.error,
+                    }));
+            }
+            Err(rejections) => {
+                report
+                    .violations
+                    .extend(rejections.into_iter().map(|r| RangeViolation {
+                        sha: commit.sha.clone(),
+                        header: header.clone(),
+                        rule_file: rule_file(r.rule_file),
+                        error: r.error,
+                    }));
+            }
+        }
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::commit_msg_rule::{SkipValidationRule, parse_commit_msg_rule};
+    use crate::constant::COMMIT_MSG_RULE_TEMPLATE;
+    use crate::error::skip_error::SkipError;
+
+    #[test]
+    fn test_lint_commits() {
+        let rule = parse_commit_msg_rule(COMMIT_MSG_RULE_TEMPLATE).unwrap();
+        let commits = vec![
+            CommitInfo {
+                sha: "1111111111111111".to_string(),
+                author_email: "dev@example.com".to_string(),
+                message: "feat: add parser".to_string(),
+            },
+            CommitInfo {
+                sha: "2222222222222222".to_string(),
+                author_email: "dev@example.com".to_string(),
+                message: "fixup! feat: add parser".to_string(),
+            },
+            CommitInfo {
+                sha: "3333333333333333".to_string(),
+                author_email: "dev@example.com".to_string(),
+                message: "Merge branch 'dev'".to_string(),
+            },
+            CommitInfo {
+                sha: "4444444444444444".to_string(),
+                author_email: "dev@example.com".to_string(),
+                message: "wip\n\nValidation-Skipped: incident 123".to_string(),
+            },
+        ];
+
+        let report = lint_commits(&commits, &rule, false);
+        assert_eq!(report.violations.len(), 1);
+        assert_eq!(short_sha(&report.violations[0].sha), "2222222222");
+        assert_eq!(report.violations[0].header, "fixup! feat: add parser");
+
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(report.skipped[0].reason, "incident 123");
+    }
+
+    #[test]
+    fn test_lint_commits_rechecks_skip_policy() {
+        let mut rule = parse_commit_msg_rule(COMMIT_MSG_RULE_TEMPLATE).unwrap();
+        if let Some(global) = rule.global.as_mut() {
+            global.skip_validation = Some(SkipValidationRule {
+                require_reason: Some(true),
+                allowed_authors: Some(vec!["*@example.com".to_string()]),
+                ..Default::default()
+            });
+        }
+        let commit = |sha: &str, author_email: &str, trailer: &str| CommitInfo {
+            sha: sha.to_string(),
+            author_email: author_email.to_string(),
+            message: format!("wip\n\nValidation-Skipped: {}", trailer),
+        };
+        let commits = vec![
+            commit("1111111111111111", "dev@example.com", "incident 123"),
+            commit(
+                "2222222222222222",
+                "dev@example.com",
+                "SKIP (no reason given)",
+            ),
+            commit("3333333333333333", "someone@elsewhere.org", "incident 123"),
+        ];
+
+        let report = lint_commits(&commits, &rule, false);
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(report.violations.len(), 2);
+        assert!(matches!(
+            report.violations[0].error,
+            CommitMsgError::Skip(SkipError::ReasonRequired { .. })
+        ));
+        assert!(matches!(
+            report.violations[1].error,
+            CommitMsgError::Skip(SkipError::AuthorNotAllowed { .. })
+        ));
+    }
+}
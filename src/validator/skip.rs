@@ -0,0 +1,430 @@
+use crate::config::commit_msg_rule::{GlobalRule, SkipValidationRule};
+use crate::config::overrides::Override;
+use crate::constant::SKIP_TRAILER_KEY;
+use crate::error::skip_error::SkipError;
+use crate::util::git_log::run_git_checked;
+use globset::Glob;
+
+/// End of the `Validation-Skipped` trailer value of a skip without reason
+const NO_REASON_SUFFIX: &str = " (no reason given)";
+
+/// Trailer prefixes git writes itself, they make a paragraph a trailer block
+/// even when it has other lines
+const GIT_GENERATED_TRAILERS: [&str; 2] = ["Signed-off-by: ", "(cherry picked from commit "];
+
+/// A skip keyword found on the first line of a commit message,
+/// e.g. `SKIP` or `SKIP: hotfix for incident 123`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SkipRequest {
+    /// The matched skip keyword
+    pub word: String,
+    /// Text after `<word>:`, if any
+    pub reason: Option<String>,
+}
+
+/// Who is skipping validation and where
+#[derive(Debug, Clone, Default)]
+pub struct SkipContext {
+    pub branch: Option<String>,
+    pub author_email: Option<String>,
+}
+
+impl SkipContext {
+    /// Read the current branch and author email from git.
+    /// Values that cannot be determined (e.g. detached HEAD during a rebase) are left empty.
+    pub fn from_git() -> Self {
+        let branch = run_git_checked(&["symbolic-ref", "--quiet", "--short", "HEAD"])
+            .ok()
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty());
+
+        // `git var GIT_AUTHOR_IDENT` honors GIT_AUTHOR_EMAIL as well as user.email
+        // Format: `Name <email> 1700000000 +0800`
+        let author_email = run_git_checked(&["var", "GIT_AUTHOR_IDENT"])
+            .ok()
+            .and_then(|ident| {
+                let start = ident.find('<')?;
+                let end = ident.find('>')?;
+                ident.get(start + 1..end).map(|s| s.to_string())
+            });
+
+        Self {
+            branch,
+            author_email,
+        }
+    }
+}
+
+/// Match the first line of a commit message against the skip keywords.
+///
+/// The keyword must either be the whole line or be followed by `:` and a reason.
+/// Matching is case-sensitive; a case-insensitive match is reported as an error
+/// so that users notice the keyword was not applied.
+pub fn match_skip_line(
+    first_line: &str,
+    skip_words: &[String],
+) -> Result<Option<SkipRequest>, SkipError> {
+    let line = first_line.trim();
+
+    for word in skip_words {
+        if line == word {
+            return Ok(Some(SkipRequest {
+                word: word.clone(),
+                reason: None,
+            }));
+        }
+
+        if let Some(reason) = line
+            .strip_prefix(word.as_str())
+            .and_then(|rest| rest.strip_prefix(':'))
+        {
+            let reason = reason.trim();
+            return Ok(Some(SkipRequest {
+                word: word.clone(),
+                reason: (!reason.is_empty()).then(|| reason.to_string()),
+            }));
+        }
+    }
+
+    let (head, _) = line.split_once(':').unwrap_or((line, ""));
+    if skip_words
+        .iter()
+        .any(|w| w.eq_ignore_ascii_case(head.trim()))
+    {
+        return Err(SkipError::CaseMismatch);
+    }
+
+    Ok(None)
+}
+
+/// The skip request of a `GITRU_SKIP` / `gitru.skip` override that is set, named after the
+/// variable or key. It has no reason and goes through the same policy as the skip words.
+pub fn override_skip_request(skip: &Override<bool>) -> Option<SkipRequest> {
+    skip.value.then(|| SkipRequest {
+        word: skip.source.key().to_string(),
+        reason: None,
+    })
+}
+
+/// Check whether the skip request is allowed by the `[global.skip_validation]` policy.
+pub fn check_skip_policy(
+    request: &SkipRequest,
+    policy: &SkipValidationRule,
+    ctx: &SkipContext,
+) -> Result<(), SkipError> {
+    if policy.require_reason.unwrap_or(false) && request.reason.is_none() {
+        return Err(SkipError::ReasonRequired {
+            word: request.word.clone(),
+        });
+    }
+
+    if let Some(allowed) = &policy.allowed_branches {
+        let branch = ctx.branch.clone().unwrap_or_else(|| "HEAD".to_string());
+        if !matches_any(&branch, allowed)? {
+            return Err(SkipError::BranchNotAllowed {
+                branch,
+                allowed: allowed.clone(),
+            });
+        }
+    }
+
+    if let Some(allowed) = &policy.allowed_authors {
+        let email = ctx.author_email.clone().unwrap_or_default();
+        if !matches_any(&email, allowed)? {
+            return Err(SkipError::AuthorNotAllowed {
+                email,
+                allowed: allowed.clone(),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Effective skip policy of the global section, defaults when not configured.
+pub fn skip_policy(global: Option<&GlobalRule>) -> SkipValidationRule {
+    global
+        .and_then(|g| g.skip_validation.clone())
+        .unwrap_or_default()
+}
+
+/// Value of the `Validation-Skipped` trailer for a skip request
+pub fn skip_trailer_value(request: &SkipRequest) -> String {
+    request
+        .reason
+        .clone()
+        .unwrap_or_else(|| format!("{}{}", request.word, NO_REASON_SUFFIX))
+}
+
+/// The skip request a `Validation-Skipped` trailer value records, see [`skip_trailer_value`]
+pub fn parse_skip_trailer_value(value: &str) -> SkipRequest {
+    match value.strip_suffix(NO_REASON_SUFFIX) {
+        Some(word) => SkipRequest {
+            word: word.trim().to_string(),
+            reason: None,
+        },
+        None => SkipRequest {
+            word: SKIP_TRAILER_KEY.to_string(),
+            reason: (!value.trim().is_empty()).then(|| value.trim().to_string()),
+        },
+    }
+}
+
+/// Check a skip recorded by a `Validation-Skipped` trailer against the policy when linting a
+/// range. The branch the commit was made on is not known anymore, only the reason and the
+/// author of the commit are checked.
+pub fn check_recorded_skip(
+    value: &str,
+    policy: &SkipValidationRule,
+    author_email: &str,
+) -> Result<(), SkipError> {
+    let policy = SkipValidationRule {
+        allowed_branches: None,
+        ..policy.clone()
+    };
+    let ctx = SkipContext {
+        branch: None,
+        author_email: Some(author_email.to_string()),
+    };
+    check_skip_policy(&parse_skip_trailer_value(value), &policy, &ctx)
+}
+
+/// Find the value of a `Validation-Skipped:` trailer in the trailer block of a commit message,
+/// the last paragraph when git would treat it as trailers, see [`trailer_block`]
+pub fn find_skip_trailer(message: &str) -> Option<String> {
+    // the commit-msg hook leaves only the trailer of a message that was just `SKIP: <reason>`
+    let message = message.trim();
+    let lines = match trailer_block(message) {
+        Some(lines) => lines,
+        None if !message.contains('\n') => vec![message],
+        None => return None,
+    };
+    lines.into_iter().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        key.trim()
+            .eq_ignore_ascii_case(SKIP_TRAILER_KEY)
+            .then(|| value.trim().to_string())
+    })
+}
+
+/// The lines of the trailer block of `message`, following `git interpret-trailers`: the last
+/// paragraph after the subject, when all its lines are trailers, or at least a quarter of them
+/// with one written by git (`Signed-off-by:`, `(cherry picked from commit`)
+fn trailer_block(message: &str) -> Option<Vec<&str>> {
+    let lines: Vec<&str> = message
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .collect();
+    let end = lines.iter().rposition(|line| !line.trim().is_empty())? + 1;
+    let start = lines[..end]
+        .iter()
+        .rposition(|line| line.trim().is_empty())
+        .map(|i| i + 1)?;
+    // the first paragraph is the subject
+    if lines[..start].iter().all(|line| line.trim().is_empty()) {
+        return None;
+    }
+
+    let block = &lines[start..end];
+    let mut trailers = 0;
+    let mut git_generated = false;
+    for line in block {
+        if line.starts_with([' ', '\t']) {
+            continue;
+        }
+        if GIT_GENERATED_TRAILERS.iter().any(|p| line.starts_with(p)) {
+            git_generated = true;
+            trailers += 1;
+        } else if is_trailer_line(line) {
+            trailers += 1;
+        }
+    }
+    let others = block
+        .iter()
+        .filter(|line| !line.starts_with([' ', '\t']))
+        .count()
+        - trailers;
+
+    (trailers > 0 && (others == 0 || (git_generated && trailers * 3 >= others)))
+        .then(|| block.to_vec())
+}
+
+/// `Token: value`, with a token of letters, digits and dashes
+fn is_trailer_line(line: &str) -> bool {
+    line.split_once(':').is_some_and(|(key, _)| {
+        let key = key.trim_end();
+        !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    })
+}
+
+fn matches_any(value: &str, patterns: &[String]) -> Result<bool, SkipError> {
+    for pattern in patterns {
+        let glob = Glob::new(pattern).map_err(|e| SkipError::InvalidPattern {
+            pattern: pattern.clone(),
+            reason: e.to_string(),
+        })?;
+        if glob.compile_matcher().is_match(value) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::overrides::RunOverrides;
+
+    fn words() -> Vec<String> {
+        vec!["--no-verify".to_string(), "SKIP".to_string()]
+    }
+
+    #[test]
+    fn test_match_skip_line() {
+        assert_eq!(
+            match_skip_line("SKIP", &words()).unwrap(),
+            Some(SkipRequest {
+                word: "SKIP".to_string(),
+                reason: None
+            })
+        );
+        assert_eq!(
+            match_skip_line("SKIP: hotfix for incident 123", &words())
+                .unwrap()
+                .and_then(|r| r.reason),
+            Some("hotfix for incident 123".to_string())
+        );
+        assert!(
+            match_skip_line("feat: add parser", &words())
+                .unwrap()
+                .is_none()
+        );
+        assert!(matches!(
+            match_skip_line("skip: hotfix", &words()),
+            Err(SkipError::CaseMismatch)
+        ));
+    }
+
+    #[test]
+    fn test_check_skip_policy() {
+        let policy = SkipValidationRule {
+            require_reason: Some(true),
+            allowed_branches: Some(vec!["hotfix/*".to_string()]),
+            allowed_authors: Some(vec!["*@example.com".to_string()]),
+            record_trailer: Some(true),
+        };
+        let ctx = SkipContext {
+            branch: Some("hotfix/incident-123".to_string()),
+            author_email: Some("dev@example.com".to_string()),
+        };
+        let with_reason = SkipRequest {
+            word: "SKIP".to_string(),
+            reason: Some("incident 123".to_string()),
+        };
+
+        assert!(check_skip_policy(&with_reason, &policy, &ctx).is_ok());
+
+        let without_reason = SkipRequest {
+            word: "SKIP".to_string(),
+            reason: None,
+        };
+        assert!(matches!(
+            check_skip_policy(&without_reason, &policy, &ctx),
+            Err(SkipError::ReasonRequired { .. })
+        ));
+
+        let on_main = SkipContext {
+            branch: Some("main".to_string()),
+            ..ctx.clone()
+        };
+        assert!(matches!(
+            check_skip_policy(&with_reason, &policy, &on_main),
+            Err(SkipError::BranchNotAllowed { .. })
+        ));
+
+        let other_author = SkipContext {
+            author_email: Some("someone@elsewhere.org".to_string()),
+            ..ctx
+        };
+        assert!(matches!(
+            check_skip_policy(&with_reason, &policy, &other_author),
+            Err(SkipError::AuthorNotAllowed { .. })
+        ));
+    }
+
+    #[test]
+    fn test_find_skip_trailer() {
+        let msg = "feat: add parser\n\nValidation-Skipped: incident 123\n";
+        assert_eq!(find_skip_trailer(msg), Some("incident 123".to_string()));
+        assert_eq!(find_skip_trailer("feat: add parser"), None);
+
+        // only the trailer block counts, not the body or the subject
+        let in_body =
+            "feat: add parser\n\nValidation-Skipped: incident 123\nsee above\n\nCloses: #1";
+        assert_eq!(find_skip_trailer(in_body), None);
+        assert_eq!(
+            find_skip_trailer("feat: add parser\nValidation-Skipped: incident 123"),
+            None
+        );
+        assert_eq!(
+            find_skip_trailer("Validation-Skipped: incident 123"),
+            Some("incident 123".to_string())
+        );
+        let mixed = "feat: add parser\n\nsome text\nValidation-Skipped: incident 123\n\
+                     Signed-off-by: Dev <dev@example.com>";
+        assert_eq!(find_skip_trailer(mixed), Some("incident 123".to_string()));
+    }
+
+    #[test]
+    fn test_override_skip_policy() {
+        let overrides = RunOverrides::resolve(
+            |name| (name == "GITRU_SKIP").then(|| "1".to_string()),
+            |_| None,
+        )
+        .unwrap();
+        let request = override_skip_request(overrides.skip.as_ref().unwrap()).unwrap();
+        assert_eq!(request.word, "GITRU_SKIP");
+        assert_eq!(skip_trailer_value(&request), "GITRU_SKIP (no reason given)");
+
+        let policy = SkipValidationRule {
+            allowed_branches: Some(vec!["hotfix/*".to_string()]),
+            ..Default::default()
+        };
+        let on = |branch: &str| SkipContext {
+            branch: Some(branch.to_string()),
+            author_email: None,
+        };
+        assert!(matches!(
+            check_skip_policy(&request, &policy, &on("feature/login")),
+            Err(SkipError::BranchNotAllowed { .. })
+        ));
+        assert!(check_skip_policy(&request, &policy, &on("hotfix/crash")).is_ok());
+
+        let unset = RunOverrides::resolve(
+            |name| (name == "GITRU_SKIP").then(|| "0".to_string()),
+            |_| None,
+        )
+        .unwrap();
+        assert_eq!(override_skip_request(unset.skip.as_ref().unwrap()), None);
+    }
+
+    #[test]
+    fn test_check_recorded_skip() {
+        let policy = SkipValidationRule {
+            require_reason: Some(true),
+            allowed_branches: Some(vec!["hotfix/*".to_string()]),
+            allowed_authors: Some(vec!["*@example.com".to_string()]),
+            record_trailer: Some(true),
+        };
+
+        assert!(check_recorded_skip("incident 123", &policy, "dev@example.com").is_ok());
+        assert!(matches!(
+            check_recorded_skip("SKIP (no reason given)", &policy, "dev@example.com"),
+            Err(SkipError::ReasonRequired { word }) if word == "SKIP"
+        ));
+        assert!(matches!(
+            check_recorded_skip("incident 123", &policy, "someone@elsewhere.org"),
+            Err(SkipError::AuthorNotAllowed { .. })
+        ));
+    }
+}
@@ -0,0 +1,81 @@
+#!/bin/sh
+
+# Git Hook: {{hook_name}}
+# Purpose: Run gitru for this hook: validation and the commands of .gitru/hooks.toml
+# Note: This file is initialized by gitru, manual modification is usually unnecessary
+
+# How gitru is found, chosen with `gitru install --exec`:
+#   absolute: the binary below, or gitru on PATH when it moved
+#   path:     gitru on PATH
+#   repo:     the binary below, relative to the repository root (hooks run from there)
+#   cargo:    `cargo run --bin gitru` in the repository
+EXEC_STRATEGY="{{exec_strategy}}"
+VALIDATOR_SCRIPT="{{program_exec}}"
+
+case "$EXEC_STRATEGY" in
+  absolute | path)
+    # git GUIs on Linux often run hooks without Cargo's bin directory on PATH
+    PATH="$PATH:${CARGO_HOME:-$HOME/.cargo}/bin"
+    export PATH
+    if [ "$EXEC_STRATEGY" = "path" ] || [ ! -x "$VALIDATOR_SCRIPT" ]; then
+      VALIDATOR_SCRIPT="gitru"
+    fi
+    ;;
+  cargo)
+    PATH="$PATH:${CARGO_HOME:-$HOME/.cargo}/bin"
+    export PATH
+    VALIDATOR_SCRIPT="cargo"
+    ;;
+esac
+
+if ! command -v "$VALIDATOR_SCRIPT" >/dev/null 2>&1; then
+  echo "gitru not found ($EXEC_STRATEGY: $VALIDATOR_SCRIPT). Run 'gitru doctor' for details, or reinstall the {{hook_name}} hook using 'gitru install {{hook_name}} -f'."
+  exit 1
+fi
+
+run_gitru() {
+  if [ "$EXEC_STRATEGY" = "cargo" ]; then
+    cargo run --quiet --bin gitru -- "$@"
+  else
+    "$VALIDATOR_SCRIPT" "$@"
+  fi
+}
+
+# Hook that existed before gitru, kept as {{hook_name}}.local by `gitru install --chain`.
+# "before" or "after": when it runs relative to gitru, empty: not chained
+CHAIN_ORDER="{{chain_order}}"
+LOCAL_HOOK="$(dirname "$0")/{{hook_name}}.local"
+
+# Input git writes to the hook (the refs of pre-push), kept for both gitru and the local hook
+HOOK_STDIN=""
+if [ "{{reads_stdin}}" = "true" ]; then
+  HOOK_STDIN="$(mktemp)" || exit 1
+  trap 'rm -f "$HOOK_STDIN"' EXIT
+  cat > "$HOOK_STDIN"
+fi
+
+with_stdin() {
+  if [ -n "$HOOK_STDIN" ]; then
+    "$@" < "$HOOK_STDIN"
+  else
+    "$@"
+  fi
+}
+
+run_local_hook() {
+  # like git, skip a hook that is not executable
+  if [ -x "$LOCAL_HOOK" ]; then
+    with_stdin "$LOCAL_HOOK" "$@" || exit $?
+  fi
+}
+
+if [ "$CHAIN_ORDER" = "before" ]; then
+  run_local_hook "$@"
+fi
+
+with_stdin run_gitru run {{gitru_args}} || exit $?
+
+if [ "$CHAIN_ORDER" = "after" ]; then
+  run_local_hook "$@"
+fi
+exit 0